@@ -1,5 +1,6 @@
 #include <cxx_juce_utils.h>
 
+#include <cxx-juce/src/juce_audio_basics/audio_source.rs.h>
 #include <cxx-juce/src/juce_audio_basics/filters.rs.h>
 #include <cxx-juce/src/juce_audio_basics/midi.rs.h>
 
@@ -7,4 +8,121 @@ CXX_JUCE_ASSERT_SIZE_ALIGN (SingleThreadedIIRFilter)
 CXX_JUCE_ASSERT_SIZE_ALIGN (IIRCoefficients)
 CXX_JUCE_ASSERT_SIZE_ALIGN (MidiBuffer)
 CXX_JUCE_ASSERT_SIZE_ALIGN (MidiMessage)
+CXX_JUCE_ASSERT_SIZE_ALIGN (MidiMessageSequence)
 CXX_JUCE_ASSERT_SIZE_ALIGN (MidiFile)
+
+namespace cxx_juce
+{
+CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (AudioSource)
+
+std::unique_ptr<juce::AudioSource> wrap (BoxDynAudioSource source) noexcept
+{
+    struct AudioSource : juce::AudioSource
+    {
+        explicit AudioSource (BoxDynAudioSource source)
+            : _source { std::move (source) }
+        {
+        }
+
+        void prepareToPlay (int samplesPerBlockExpected, double sampleRate) override
+        {
+            AudioSourceImpl::prepare_to_play (_source, samplesPerBlockExpected, sampleRate);
+        }
+
+        void releaseResources() override
+        {
+            AudioSourceImpl::release_resources (_source);
+        }
+
+        void getNextAudioBlock (const juce::AudioSourceChannelInfo& info) override
+        {
+            AudioSourceImpl::get_next_audio_block (_source, *info.buffer, info.startSample, info.numSamples);
+        }
+
+        BoxDynAudioSource _source;
+    };
+
+    return std::make_unique<AudioSource> (std::move (source));
+}
+
+const juce::MidiMessage& midiMessageSequenceGet (const juce::MidiMessageSequence& sequence, int index) noexcept
+{
+    return sequence.getEventPointer (index)->message;
+}
+
+juce::MidiMessage& midiMessageSequenceGetMut (juce::MidiMessageSequence& sequence, int index) noexcept
+{
+    return sequence.getEventPointer (index)->message;
+}
+
+void midiBufferAddToSequence (const juce::MidiBuffer& buffer, juce::MidiMessageSequence& sequence)
+{
+    for (const auto metadata : buffer)
+        sequence.addEvent (metadata.getMessage());
+}
+
+juce::MidiMessage midiMessageFromBytes (rust::Slice<const rust::u8> data)
+{
+    return { data.data(), static_cast<int> (data.size()) };
+}
+
+juce::MidiMessage midiMessageFullFrame (int hours, int minutes, int seconds, int frames, int frameRate)
+{
+    return juce::MidiMessage::fullFrame (hours,
+                                         minutes,
+                                         seconds,
+                                         frames,
+                                         static_cast<juce::MidiMessage::SmpteTimecodeType> (frameRate));
+}
+
+void midiMessageGetFullFrameParameters (const juce::MidiMessage& message,
+                                        int& hours,
+                                        int& minutes,
+                                        int& seconds,
+                                        int& frames,
+                                        int& frameRate) noexcept
+{
+    auto timecodeType = juce::MidiMessage::fps24;
+    message.getFullFrameParameters (hours, minutes, seconds, frames, timecodeType);
+    frameRate = static_cast<int> (timecodeType);
+}
+
+bool midiFileReadFrom (juce::MidiFile& file, rust::Slice<const rust::u8> data)
+{
+    juce::MemoryInputStream stream { data.data(), data.size(), false };
+    return file.readFrom (stream);
+}
+
+bool midiFileWriteTo (const juce::MidiFile& file, int midiFileType, rust::Vec<rust::u8>& bytes)
+{
+    juce::MemoryOutputStream stream;
+
+    if (! file.writeTo (stream, midiFileType))
+        return false;
+
+    const auto* data = static_cast<const rust::u8*> (stream.getData());
+    bytes.reserve (stream.getDataSize());
+    std::copy (data, data + stream.getDataSize(), std::back_inserter (bytes));
+    return true;
+}
+
+// AudioProcessLoadMeasurer guards its own state, so that it can be updated on the audio thread
+// while other threads read the load, which lets Rust share it between threads.
+void audioProcessLoadMeasurerReset (const juce::AudioProcessLoadMeasurer& measurer, double sampleRate, int blockSize)
+{
+    const_cast<juce::AudioProcessLoadMeasurer&> (measurer).reset (sampleRate, blockSize);
+}
+
+void audioProcessLoadMeasurerRegisterBlockRenderTime (const juce::AudioProcessLoadMeasurer& measurer,
+                                                      double millisecondsTaken)
+{
+    const_cast<juce::AudioProcessLoadMeasurer&> (measurer).registerBlockRenderTime (millisecondsTaken);
+}
+
+void audioProcessLoadMeasurerRegisterRenderTime (const juce::AudioProcessLoadMeasurer& measurer,
+                                                 double millisecondsTaken,
+                                                 int numSamples)
+{
+    const_cast<juce::AudioProcessLoadMeasurer&> (measurer).registerRenderTime (millisecondsTaken, numSamples);
+}
+} // namespace cxx_juce
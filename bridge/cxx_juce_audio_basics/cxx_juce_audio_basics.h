@@ -5,8 +5,40 @@
 #include <cxx_juce_utils.h>
 #include <rust/cxx.h>
 
+namespace juce
+{
+using MidiEventHolder = MidiMessageSequence::MidiEventHolder;
+} // namespace juce
+
+namespace cxx_juce
+{
+CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE(AudioSource, juce::AudioSource)
+
+const juce::MidiMessage& midiMessageSequenceGet (const juce::MidiMessageSequence& sequence, int index) noexcept;
+juce::MidiMessage& midiMessageSequenceGetMut (juce::MidiMessageSequence& sequence, int index) noexcept;
+void midiBufferAddToSequence (const juce::MidiBuffer& buffer, juce::MidiMessageSequence& sequence);
+juce::MidiMessage midiMessageFromBytes (rust::Slice<const rust::u8> data);
+juce::MidiMessage midiMessageFullFrame (int hours, int minutes, int seconds, int frames, int frameRate);
+void midiMessageGetFullFrameParameters (const juce::MidiMessage& message,
+                                        int& hours,
+                                        int& minutes,
+                                        int& seconds,
+                                        int& frames,
+                                        int& frameRate) noexcept;
+bool midiFileReadFrom (juce::MidiFile& file, rust::Slice<const rust::u8> data);
+bool midiFileWriteTo (const juce::MidiFile& file, int midiFileType, rust::Vec<rust::u8>& bytes);
+
+void audioProcessLoadMeasurerReset (const juce::AudioProcessLoadMeasurer& measurer, double sampleRate, int blockSize);
+void audioProcessLoadMeasurerRegisterBlockRenderTime (const juce::AudioProcessLoadMeasurer& measurer,
+                                                      double millisecondsTaken);
+void audioProcessLoadMeasurerRegisterRenderTime (const juce::AudioProcessLoadMeasurer& measurer,
+                                                 double millisecondsTaken,
+                                                 int numSamples);
+} // namespace cxx_juce
+
 CXX_JUCE_DECLARE_RELOCATABLE(IIRCoefficients)
 CXX_JUCE_DECLARE_RELOCATABLE(SingleThreadedIIRFilter)
 CXX_JUCE_DECLARE_RELOCATABLE(MidiBuffer)
 CXX_JUCE_DECLARE_RELOCATABLE(MidiMessage)
+CXX_JUCE_DECLARE_RELOCATABLE(MidiMessageSequence)
 CXX_JUCE_DECLARE_RELOCATABLE(MidiFile)
@@ -30,6 +30,173 @@ CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (AudioDevice)
 CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (AudioDeviceCallback)
 CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (AudioDeviceType)
 CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (MidiInputCallback)
+CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (MidiDeviceListCallback)
+CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (AudioDeviceSetupCallback)
+CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (AudioDeviceListCallback)
+
+void audioDeviceCallbackAboutToStart (juce::AudioIODeviceCallback& callback, juce::AudioIODevice& device)
+{
+    callback.audioDeviceAboutToStart (&device);
+}
+
+void audioDeviceCallbackProcessBlock (juce::AudioIODeviceCallback& callback,
+                                      const juce::AudioSampleBuffer& input,
+                                      juce::AudioSampleBuffer& output,
+                                      bool hasHostTime,
+                                      std::uint64_t hostTimeNs)
+{
+    juce::AudioIODeviceCallbackContext context;
+
+    if (hasHostTime)
+        context.hostTimeNs = &hostTimeNs;
+
+    callback.audioDeviceIOCallbackWithContext (
+        input.getArrayOfReadPointers(),
+        input.getNumChannels(),
+        output.getArrayOfWritePointers(),
+        output.getNumChannels(),
+        output.getNumChannels() > 0 ? output.getNumSamples() : input.getNumSamples(),
+        context);
+}
+
+AudioDeviceCallbackHandle::AudioDeviceCallbackHandle (std::shared_ptr<Target> target)
+    : _target { std::move (target) }
+{
+}
+
+void AudioDeviceCallbackHandle::aboutToStart()
+{
+    const std::scoped_lock lock { _target->mutex };
+
+    if (_target->callback != nullptr)
+        _target->callback->audioDeviceAboutToStart (_target->device);
+}
+
+void AudioDeviceCallbackHandle::processBlock (const juce::AudioSampleBuffer& input,
+                                              juce::AudioSampleBuffer& output,
+                                              bool hasHostTime,
+                                              std::uint64_t hostTimeNs)
+{
+    // This runs on the audio thread, so rather than waiting while the device is being started
+    // or stopped, the block is skipped and left silent.
+    const std::unique_lock lock { _target->mutex, std::try_to_lock };
+
+    if (lock.owns_lock() && _target->callback != nullptr)
+        audioDeviceCallbackProcessBlock (*_target->callback, input, output, hasHostTime, hostTimeNs);
+    else
+        output.clear();
+}
+
+void AudioDeviceCallbackHandle::stopped()
+{
+    const std::scoped_lock lock { _target->mutex };
+
+    if (_target->callback != nullptr)
+        _target->callback->audioDeviceStopped();
+}
+
+void AudioDeviceCallbackHandle::error (const juce::String& message)
+{
+    const std::scoped_lock lock { _target->mutex };
+
+    if (_target->callback != nullptr)
+        _target->callback->audioDeviceError (message);
+}
+
+AudioDeviceListNotifier::AudioDeviceListNotifier (std::shared_ptr<Target> target)
+    : _target { std::move (target) }
+{
+}
+
+void AudioDeviceListNotifier::notify() const
+{
+    const auto callListeners = [target = _target]
+    {
+        // Listeners may rescan or recreate device types, so they're called without holding the
+        // lock. This is safe because the type is only destroyed on the message thread, which is
+        // where this runs.
+        const auto type = [&]
+        {
+            const std::scoped_lock lock { target->mutex };
+            return target->type;
+        }();
+
+        if (type != nullptr)
+            type->callDeviceChangeListeners();
+    };
+
+    if (juce::MessageManager::existsAndIsCurrentThread())
+        callListeners();
+    else
+        juce::MessageManager::callAsync (callListeners);
+}
+
+AudioDeviceListListener::AudioDeviceListListener (BoxDynAudioDeviceListCallback callback)
+    : _callback { std::move (callback) }
+{
+}
+
+AudioDeviceListListener::~AudioDeviceListListener()
+{
+    for (auto* type : _types)
+        type->removeListener (this);
+}
+
+void AudioDeviceListListener::attachToDeviceTypes (juce::AudioDeviceManager& manager)
+{
+    for (auto* type : manager.getAvailableDeviceTypes())
+    {
+        if (_types.addIfNotAlreadyThere (type))
+            type->addListener (this);
+    }
+}
+
+void AudioDeviceListListener::audioDeviceListChanged()
+{
+    AudioDeviceListCallbackImpl::call (_callback);
+}
+
+std::unique_ptr<juce::ChangeListener> makeAudioDeviceSetupListener (juce::AudioDeviceManager& manager,
+                                                                    BoxDynAudioDeviceSetupCallback callback)
+{
+    struct AudioDeviceSetupListener : juce::ChangeListener
+    {
+        AudioDeviceSetupListener (juce::AudioDeviceManager& manager,
+                                  BoxDynAudioDeviceSetupCallback callback)
+            : _manager { manager }
+            , _callback { std::move (callback) }
+            , _setup { manager.getAudioDeviceSetup() }
+        {
+            _manager.addChangeListener (this);
+        }
+
+        ~AudioDeviceSetupListener() override
+        {
+            _manager.removeChangeListener (this);
+        }
+
+        void changeListenerCallback (juce::ChangeBroadcaster*) override
+        {
+            auto setup = _manager.getAudioDeviceSetup();
+            AudioDeviceSetupCallbackImpl::call (_callback, _setup, setup);
+            _setup = std::move (setup);
+        }
+
+        juce::AudioDeviceManager& _manager;
+        BoxDynAudioDeviceSetupCallback _callback;
+        juce::AudioDeviceSetup _setup;
+    };
+
+    return std::make_unique<AudioDeviceSetupListener> (manager, std::move (callback));
+}
+
+std::unique_ptr<AudioDeviceListListener> makeAudioDeviceListListener (juce::AudioDeviceManager& manager,
+                                                                      BoxDynAudioDeviceListCallback callback)
+{
+    auto listener = std::make_unique<AudioDeviceListListener> (std::move (callback));
+    listener->attachToDeviceTypes (manager);
+    return listener;
+}
 
 std::unique_ptr<juce::AudioIODevice> wrap (BoxDynAudioDevice device) noexcept
 {
@@ -41,6 +208,15 @@ std::unique_ptr<juce::AudioIODevice> wrap (BoxDynAudioDevice device) noexcept
                   static_cast<std::string> (AudioDeviceImpl::type_name (device)))
             , _device { std::move (device) }
         {
+            _target->device = this;
+        }
+
+        ~AudioDevice() override
+        {
+            stop();
+
+            const std::scoped_lock lock { _target->mutex };
+            _target->device = nullptr;
         }
 
         juce::StringArray getOutputChannelNames() override
@@ -86,14 +262,22 @@ std::unique_ptr<juce::AudioIODevice> wrap (BoxDynAudioDevice device) noexcept
             return AudioDeviceImpl::is_open (_device);
         }
 
-        void start (juce::AudioIODeviceCallback* /*callback*/) override
+        void start (juce::AudioIODeviceCallback* callback) override
         {
-            AudioDeviceImpl::start (_device);
+            {
+                const std::scoped_lock lock { _target->mutex };
+                _target->callback = callback;
+            }
+
+            AudioDeviceImpl::start (_device, std::make_unique<AudioDeviceCallbackHandle> (_target));
         }
 
         void stop() override
         {
             AudioDeviceImpl::stop (_device);
+
+            const std::scoped_lock lock { _target->mutex };
+            _target->callback = nullptr;
         }
 
         bool isPlaying() override
@@ -162,6 +346,8 @@ std::unique_ptr<juce::AudioIODevice> wrap (BoxDynAudioDevice device) noexcept
         }
 
         BoxDynAudioDevice _device;
+        std::shared_ptr<AudioDeviceCallbackHandle::Target> _target =
+            std::make_shared<AudioDeviceCallbackHandle::Target>();
     };
 
     return std::make_unique<AudioDevice> (std::move (device));
@@ -186,7 +372,7 @@ std::unique_ptr<juce::AudioIODeviceCallback> wrap (BoxDynAudioDeviceCallback cal
                                                float* const* outputChannelData,
                                                int numOutputChannels,
                                                int numSamples,
-                                               const juce::AudioIODeviceCallbackContext&) override
+                                               const juce::AudioIODeviceCallbackContext& context) override
         {
             juce::AudioSampleBuffer inputBuffer;
             if (inputChannelData)
@@ -204,7 +390,12 @@ std::unique_ptr<juce::AudioIODeviceCallback> wrap (BoxDynAudioDeviceCallback cal
                                                numSamples);
             }
 
-            AudioDeviceCallbackImpl::process_block (_callback, inputBuffer, outputBuffer);
+            const auto hasHostTime = context.hostTimeNs != nullptr;
+            AudioDeviceCallbackImpl::process_block_with_context (_callback,
+                                                                 inputBuffer,
+                                                                 outputBuffer,
+                                                                 hasHostTime,
+                                                                 hasHostTime ? *context.hostTimeNs : 0);
         }
 
         void audioDeviceStopped() override
@@ -212,6 +403,11 @@ std::unique_ptr<juce::AudioIODeviceCallback> wrap (BoxDynAudioDeviceCallback cal
             AudioDeviceCallbackImpl::stopped (_callback);
         }
 
+        void audioDeviceError (const juce::String& errorMessage) override
+        {
+            AudioDeviceCallbackImpl::error (_callback, errorMessage);
+        }
+
         BoxDynAudioDeviceCallback _callback;
     };
 
@@ -227,6 +423,16 @@ std::unique_ptr<juce::AudioIODeviceType> wrap (BoxDynAudioDeviceType deviceType)
                   static_cast<std::string> (AudioDeviceTypeImpl::name (deviceType)))
             , _deviceType { std::move (deviceType) }
         {
+            _target->type = this;
+            AudioDeviceTypeImpl::set_device_list_notifier (
+                _deviceType,
+                std::make_unique<AudioDeviceListNotifier> (_target));
+        }
+
+        ~AudioDeviceType() override
+        {
+            const std::scoped_lock lock { _target->mutex };
+            _target->type = nullptr;
         }
 
         void scanForDevices() override
@@ -271,6 +477,8 @@ std::unique_ptr<juce::AudioIODeviceType> wrap (BoxDynAudioDeviceType deviceType)
             }
         }
 
+        std::shared_ptr<AudioDeviceListNotifier::Target> _target =
+            std::make_shared<AudioDeviceListNotifier::Target>();
         BoxDynAudioDeviceType _deviceType;
     };
 
@@ -286,17 +494,123 @@ std::unique_ptr<juce::MidiInputCallback> wrap (BoxDynMidiInputCallback callback)
         {
         }
 
-        void handleIncomingMidiMessage (juce::MidiInput*,
+        // JUCE allows messages without a source, such as those injected by an
+        // AudioProcessorPlayer, but the Rust callback always expects one, so they're ignored.
+        void handleIncomingMidiMessage (juce::MidiInput* source,
                                         const juce::MidiMessage& message) override
         {
+            if (source == nullptr)
+                return;
+
             MidiInputCallbackImpl::handle_incoming_midi_message (
                 _callback,
+                *source,
                 message);
         }
 
+        void handlePartialSysexMessage (juce::MidiInput* source,
+                                        const juce::uint8* messageData,
+                                        int numBytesSoFar,
+                                        double timestamp) override
+        {
+            if (source == nullptr)
+                return;
+
+            MidiInputCallbackImpl::handle_partial_sysex_message (
+                _callback,
+                *source,
+                { messageData, static_cast<size_t> (numBytesSoFar) },
+                timestamp);
+        }
+
         BoxDynMidiInputCallback _callback;
     };
 
     return std::make_unique<MidiInputCallback> (std::move (callback));
 }
+
+static std::shared_ptr<juce::LevelMeter> toSharedPtr (juce::LevelMeter::Ptr meter)
+{
+    // The manager only measures levels while something other than itself holds a reference, so
+    // the shared pointer keeps one for as long as it is alive.
+    auto* rawMeter = meter.get();
+    return { rawMeter, [meter = std::move (meter)] (juce::LevelMeter*) {} };
+}
+
+std::shared_ptr<juce::LevelMeter> getInputLevelMeter (juce::AudioDeviceManager& manager)
+{
+    return toSharedPtr (manager.getInputLevelGetter());
+}
+
+std::shared_ptr<juce::LevelMeter> getOutputLevelMeter (juce::AudioDeviceManager& manager)
+{
+    return toSharedPtr (manager.getOutputLevelGetter());
+}
+
+void startAudioIODevice (juce::AudioIODevice& device, juce::AudioIODeviceCallback* callback)
+{
+    device.start (callback);
+}
+
+juce::String createAudioDeviceManagerStateXml (const juce::AudioDeviceManager& manager)
+{
+    if (auto xml = manager.createStateXml())
+        return xml->toString();
+
+    return {};
+}
+
+juce::String initialiseAudioDeviceManager (juce::AudioDeviceManager& manager,
+                                           int numInputChannels,
+                                           int numOutputChannels,
+                                           const juce::String& savedState,
+                                           bool selectDefaultDeviceOnFailure,
+                                           const juce::String& preferredDefaultDeviceName,
+                                           const juce::AudioDeviceSetup* preferredSetupOptions)
+{
+    std::unique_ptr<juce::XmlElement> xml;
+
+    if (savedState.isNotEmpty())
+    {
+        xml = juce::parseXML (savedState);
+
+        if (xml == nullptr)
+            return "The saved audio device state isn't valid XML";
+    }
+
+    return manager.initialise (numInputChannels,
+                               numOutputChannels,
+                               xml.get(),
+                               selectDefaultDeviceOnFailure,
+                               preferredDefaultDeviceName,
+                               preferredSetupOptions);
+}
+
+std::unique_ptr<juce::MidiDeviceListConnection> makeMidiDeviceListConnection (BoxDynMidiDeviceListCallback callback)
+{
+    auto callbackPtr = std::make_shared<BoxDynMidiDeviceListCallback> (std::move (callback));
+
+    return std::make_unique<juce::MidiDeviceListConnection> (
+        juce::MidiDeviceListConnection::make ([callbackPtr]
+                                              { MidiDeviceListCallbackImpl::call (*callbackPtr); }));
+}
+
+std::unique_ptr<juce::MidiInput> createNewMidiInputDevice ([[maybe_unused]] const juce::String& deviceName,
+                                                           [[maybe_unused]] juce::MidiInputCallback* callback)
+{
+#if JUCE_LINUX || JUCE_BSD || JUCE_MAC || JUCE_IOS
+    return juce::MidiInput::createNewDevice (deviceName, callback);
+#else
+    return nullptr;
+#endif
+}
+
+std::unique_ptr<juce::MidiOutput> createNewMidiOutputDevice ([[maybe_unused]] const juce::String& deviceName)
+{
+#if JUCE_LINUX || JUCE_BSD || JUCE_MAC || JUCE_IOS
+    return juce::MidiOutput::createNewDevice (deviceName);
+#else
+    return nullptr;
+#endif
+}
 } // namespace cxx_juce
@@ -5,10 +5,14 @@
 #include <cxx_juce_utils.h>
 #include <rust/cxx.h>
 
+#include <memory>
+#include <mutex>
+
 namespace juce
 {
 using AudioIODeviceTypeArray = OwnedArray<AudioIODeviceType>;
 using AudioDeviceSetup = AudioDeviceManager::AudioDeviceSetup;
+using LevelMeter = AudioDeviceManager::LevelMeter;
 using MidiDeviceInfoArray = Array<MidiDeviceInfo>;
 } // namespace juce
 
@@ -18,6 +22,103 @@ CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE(AudioDevice, juce::AudioIODevice)
 CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE(AudioDeviceCallback, juce::AudioIODeviceCallback)
 CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE(AudioDeviceType, juce::AudioIODeviceType)
 CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE(MidiInputCallback, juce::MidiInputCallback)
+CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE_CUSTOM(MidiDeviceListCallback)
+CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE_CUSTOM(AudioDeviceSetupCallback)
+CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE_CUSTOM(AudioDeviceListCallback)
+
+void audioDeviceCallbackAboutToStart (juce::AudioIODeviceCallback& callback, juce::AudioIODevice& device);
+void audioDeviceCallbackProcessBlock (juce::AudioIODeviceCallback& callback,
+                                      const juce::AudioSampleBuffer& input,
+                                      juce::AudioSampleBuffer& output,
+                                      bool hasHostTime,
+                                      std::uint64_t hostTimeNs);
+
+/// The callback that a Rust AudioDevice drives while it is running. Calls made after the
+/// device has been stopped or destroyed are ignored.
+class AudioDeviceCallbackHandle
+{
+public:
+    struct Target
+    {
+        std::mutex mutex;
+        juce::AudioIODevice* device = nullptr;
+        juce::AudioIODeviceCallback* callback = nullptr;
+    };
+
+    explicit AudioDeviceCallbackHandle (std::shared_ptr<Target> target);
+
+    void aboutToStart();
+    void processBlock (const juce::AudioSampleBuffer& input,
+                       juce::AudioSampleBuffer& output,
+                       bool hasHostTime,
+                       std::uint64_t hostTimeNs);
+    void stopped();
+    void error (const juce::String& message);
+
+private:
+    std::shared_ptr<Target> _target;
+};
+
+/// Lets a Rust AudioDeviceType tell its listeners that its list of devices has changed.
+/// Calls made after the device type has been destroyed are ignored.
+class AudioDeviceListNotifier
+{
+public:
+    struct Target
+    {
+        std::mutex mutex;
+        juce::AudioIODeviceType* type = nullptr;
+    };
+
+    explicit AudioDeviceListNotifier (std::shared_ptr<Target> target);
+
+    void notify() const;
+
+private:
+    std::shared_ptr<Target> _target;
+};
+
+/// Listens for changes to the device lists of the device types registered with an
+/// AudioDeviceManager.
+class AudioDeviceListListener : private juce::AudioIODeviceType::Listener
+{
+public:
+    explicit AudioDeviceListListener (BoxDynAudioDeviceListCallback callback);
+    ~AudioDeviceListListener() override;
+
+    void attachToDeviceTypes (juce::AudioDeviceManager& manager);
+
+private:
+    void audioDeviceListChanged() override;
+
+    BoxDynAudioDeviceListCallback _callback;
+    juce::Array<juce::AudioIODeviceType*> _types;
+};
+
+std::unique_ptr<juce::ChangeListener> makeAudioDeviceSetupListener (juce::AudioDeviceManager& manager,
+                                                                    BoxDynAudioDeviceSetupCallback callback);
+std::unique_ptr<AudioDeviceListListener> makeAudioDeviceListListener (juce::AudioDeviceManager& manager,
+                                                                      BoxDynAudioDeviceListCallback callback);
+
+std::shared_ptr<juce::LevelMeter> getInputLevelMeter (juce::AudioDeviceManager& manager);
+std::shared_ptr<juce::LevelMeter> getOutputLevelMeter (juce::AudioDeviceManager& manager);
+
+void startAudioIODevice (juce::AudioIODevice& device, juce::AudioIODeviceCallback* callback);
+
+juce::String createAudioDeviceManagerStateXml (const juce::AudioDeviceManager& manager);
+juce::String initialiseAudioDeviceManager (juce::AudioDeviceManager& manager,
+                                           int numInputChannels,
+                                           int numOutputChannels,
+                                           const juce::String& savedState,
+                                           bool selectDefaultDeviceOnFailure,
+                                           const juce::String& preferredDefaultDeviceName,
+                                           const juce::AudioDeviceSetup* preferredSetupOptions);
+
+std::unique_ptr<juce::MidiDeviceListConnection> makeMidiDeviceListConnection (BoxDynMidiDeviceListCallback callback);
+
+std::unique_ptr<juce::MidiInput> createNewMidiInputDevice (const juce::String& deviceName,
+                                                           juce::MidiInputCallback* callback);
+std::unique_ptr<juce::MidiOutput> createNewMidiOutputDevice (const juce::String& deviceName);
 } // namespace cxx_juce
 
 CXX_JUCE_DECLARE_RELOCATABLE(AudioDeviceManager::AudioDeviceSetup)
@@ -0,0 +1,137 @@
+#include "cxx_juce_audio_formats.h"
+
+#include <array>
+
+namespace cxx_juce
+{
+namespace
+{
+template <typename Format>
+std::unique_ptr<juce::AudioFormatReader> createReader (const juce::File& file)
+{
+    auto stream = file.createInputStream();
+
+    if (stream == nullptr)
+        return nullptr;
+
+    std::unique_ptr<juce::AudioFormatReader> reader {
+        Format {}.createReaderFor (stream.get(), true)
+    };
+
+    if (reader != nullptr)
+        stream.release();
+
+    return reader;
+}
+
+template <typename Format>
+std::unique_ptr<juce::AudioFormatWriter> createWriter (const juce::File& file,
+                                                       double sampleRate,
+                                                       rust::u32 numChannels,
+                                                       rust::i32 bitsPerSample)
+{
+    if (file.exists() && ! file.deleteFile())
+        return nullptr;
+
+    auto stream = file.createOutputStream();
+
+    if (stream == nullptr)
+        return nullptr;
+
+    std::unique_ptr<juce::AudioFormatWriter> writer {
+        Format {}.createWriterFor (stream.get(), sampleRate, numChannels, bitsPerSample, {}, 0)
+    };
+
+    if (writer != nullptr)
+        stream.release();
+
+    return writer;
+}
+} // namespace
+
+std::unique_ptr<juce::AudioFormatReader> createWavReader (const juce::File& file)
+{
+    return createReader<juce::WavAudioFormat> (file);
+}
+
+std::unique_ptr<juce::AudioFormatWriter> createWavWriter (const juce::File& file,
+                                                          double sampleRate,
+                                                          rust::u32 numChannels,
+                                                          rust::i32 bitsPerSample)
+{
+    return createWriter<juce::WavAudioFormat> (file, sampleRate, numChannels, bitsPerSample);
+}
+
+std::unique_ptr<juce::AudioFormatReader> createFlacReader (const juce::File& file)
+{
+    return createReader<juce::FlacAudioFormat> (file);
+}
+
+std::unique_ptr<juce::AudioFormatWriter> createFlacWriter (const juce::File& file,
+                                                           double sampleRate,
+                                                           rust::u32 numChannels,
+                                                           rust::i32 bitsPerSample)
+{
+    return createWriter<juce::FlacAudioFormat> (file, sampleRate, numChannels, bitsPerSample);
+}
+
+double audioFormatReaderSampleRate (const juce::AudioFormatReader& reader) noexcept
+{
+    return reader.sampleRate;
+}
+
+std::int64_t audioFormatReaderLengthInSamples (const juce::AudioFormatReader& reader) noexcept
+{
+    return reader.lengthInSamples;
+}
+
+rust::u32 audioFormatReaderNumChannels (const juce::AudioFormatReader& reader) noexcept
+{
+    return reader.numChannels;
+}
+
+rust::u32 audioFormatReaderBitsPerSample (const juce::AudioFormatReader& reader) noexcept
+{
+    return reader.bitsPerSample;
+}
+
+bool audioFormatReaderRead (juce::AudioFormatReader& reader,
+                            juce::AudioSampleBuffer& buffer,
+                            int startSample,
+                            int numSamples,
+                            std::int64_t readerStartSample)
+{
+    return reader.read (&buffer, startSample, numSamples, readerStartSample, true, true);
+}
+
+bool audioFormatWriterWriteFromAudioSampleBuffer (juce::AudioFormatWriter& writer,
+                                                  const juce::AudioSampleBuffer& source,
+                                                  int startSample,
+                                                  int numSamples)
+{
+    return writer.writeFromAudioSampleBuffer (source, startSample, numSamples);
+}
+
+std::unique_ptr<juce::ThreadedWriter> makeThreadedWriter (std::unique_ptr<juce::AudioFormatWriter> writer,
+                                                          const juce::TimeSliceThread& thread,
+                                                          int numSamplesToBuffer)
+{
+    // TimeSliceThread guards its list of clients with a lock, so clients can be added from any thread.
+    auto& backgroundThread = const_cast<juce::TimeSliceThread&> (thread);
+    return std::make_unique<juce::ThreadedWriter> (writer.release(), backgroundThread, numSamplesToBuffer);
+}
+
+bool threadedWriterWrite (juce::ThreadedWriter& writer,
+                          const juce::AudioSampleBuffer& buffer,
+                          int startSample,
+                          int numSamples) noexcept
+{
+    std::array<const float*, maxThreadedWriterChannels> channels {};
+    const auto numChannels = std::min (buffer.getNumChannels(), maxThreadedWriterChannels);
+
+    for (auto channel = 0; channel < numChannels; ++channel)
+        channels[(size_t) channel] = buffer.getReadPointer (channel, startSample);
+
+    return writer.write (channels.data(), numSamples);
+}
+} // namespace cxx_juce
@@ -0,0 +1,50 @@
+#pragma once
+
+#include <juce_audio_formats/juce_audio_formats.h>
+
+#include <cxx_juce_utils.h>
+#include <rust/cxx.h>
+
+namespace juce
+{
+using ThreadedWriter = AudioFormatWriter::ThreadedWriter;
+} // namespace juce
+
+namespace cxx_juce
+{
+std::unique_ptr<juce::AudioFormatReader> createWavReader (const juce::File& file);
+std::unique_ptr<juce::AudioFormatWriter> createWavWriter (const juce::File& file,
+                                                          double sampleRate,
+                                                          rust::u32 numChannels,
+                                                          rust::i32 bitsPerSample);
+std::unique_ptr<juce::AudioFormatReader> createFlacReader (const juce::File& file);
+std::unique_ptr<juce::AudioFormatWriter> createFlacWriter (const juce::File& file,
+                                                           double sampleRate,
+                                                           rust::u32 numChannels,
+                                                           rust::i32 bitsPerSample);
+
+double audioFormatReaderSampleRate (const juce::AudioFormatReader& reader) noexcept;
+std::int64_t audioFormatReaderLengthInSamples (const juce::AudioFormatReader& reader) noexcept;
+rust::u32 audioFormatReaderNumChannels (const juce::AudioFormatReader& reader) noexcept;
+rust::u32 audioFormatReaderBitsPerSample (const juce::AudioFormatReader& reader) noexcept;
+bool audioFormatReaderRead (juce::AudioFormatReader& reader,
+                            juce::AudioSampleBuffer& buffer,
+                            int startSample,
+                            int numSamples,
+                            std::int64_t readerStartSample);
+bool audioFormatWriterWriteFromAudioSampleBuffer (juce::AudioFormatWriter& writer,
+                                                  const juce::AudioSampleBuffer& source,
+                                                  int startSample,
+                                                  int numSamples);
+
+/// The most channels a ThreadedWriter can be fed from a buffer without allocating.
+constexpr int maxThreadedWriterChannels = 64;
+
+std::unique_ptr<juce::ThreadedWriter> makeThreadedWriter (std::unique_ptr<juce::AudioFormatWriter> writer,
+                                                          const juce::TimeSliceThread& thread,
+                                                          int numSamplesToBuffer);
+bool threadedWriterWrite (juce::ThreadedWriter& writer,
+                          const juce::AudioSampleBuffer& buffer,
+                          int startSample,
+                          int numSamples) noexcept;
+} // namespace cxx_juce
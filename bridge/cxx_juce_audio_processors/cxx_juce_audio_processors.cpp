@@ -3,6 +3,7 @@
 #include <cxx-juce/src/juce_audio_processors/plugin_description.rs.h>
 #include <cxx-juce/src/juce_audio_processors/plugin_formats.rs.h>
 #include <cxx-juce/src/juce_audio_processors/plugin_instance.rs.h>
+#include <cxx-juce/src/juce_audio_processors/processor_player.rs.h>
 #include <cxx_juce_utils.h>
 
 CXX_JUCE_ASSERT_SIZE_ALIGN (AudioPluginFormatManager)
@@ -36,6 +37,36 @@ juce::String audioProcessorGetName (const juce::AudioProcessor& processor) noexc
 CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (AudioPluginFormat)
 CXX_JUCE_DEFINE_BOXED_TRAIT_TYPE (AudioPlugin)
 
+AudioProcessorPlayerMidiInput::AudioProcessorPlayerMidiInput (std::shared_ptr<Target> target)
+    : _target { std::move (target) }
+{
+}
+
+void AudioProcessorPlayerMidiInput::addMessage (const juce::MidiMessage& message) const
+{
+    const std::scoped_lock lock { _target->mutex };
+
+    if (_target->player != nullptr)
+        _target->player->handleIncomingMidiMessage (nullptr, message);
+}
+
+AudioProcessorPlayer::AudioProcessorPlayer()
+    : _target { std::make_shared<AudioProcessorPlayerMidiInput::Target>() }
+{
+    _target->player = this;
+}
+
+AudioProcessorPlayer::~AudioProcessorPlayer()
+{
+    const std::scoped_lock lock { _target->mutex };
+    _target->player = nullptr;
+}
+
+std::unique_ptr<AudioProcessorPlayerMidiInput> AudioProcessorPlayer::makeMidiInput() const
+{
+    return std::make_unique<AudioProcessorPlayerMidiInput> (_target);
+}
+
 std::unique_ptr<juce::AudioPluginFormat> wrap (BoxDynAudioPluginFormat format) noexcept
 {
     struct AudioPluginFormat : juce::AudioPluginFormat
@@ -1,10 +1,14 @@
 #pragma once
 
 #include <juce_audio_processors/juce_audio_processors.h>
+#include <juce_audio_utils/juce_audio_utils.h>
 
 #include <cxx_juce_utils.h>
 #include <rust/cxx.h>
 
+#include <memory>
+#include <mutex>
+
 namespace juce
 {
 using OwnedArrayPluginDescription = OwnedArray<PluginDescription>;
@@ -17,6 +21,38 @@ juce::String audioProcessorGetName (const juce::AudioProcessor& processor) noexc
 
 CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE(AudioPluginFormat, juce::AudioPluginFormat)
 CXX_JUCE_DECLARE_BOXED_TRAIT_TYPE(AudioPlugin, juce::AudioPluginInstance)
+
+/// Forwards MIDI messages to an AudioProcessorPlayer. Messages sent after the player has been
+/// destroyed are ignored.
+class AudioProcessorPlayerMidiInput
+{
+public:
+    struct Target
+    {
+        std::mutex mutex;
+        juce::AudioProcessorPlayer* player = nullptr;
+    };
+
+    explicit AudioProcessorPlayerMidiInput (std::shared_ptr<Target> target);
+
+    void addMessage (const juce::MidiMessage& message) const;
+
+private:
+    std::shared_ptr<Target> _target;
+};
+
+/// An AudioProcessorPlayer that can hand out MIDI inputs which outlive it.
+class AudioProcessorPlayer : public juce::AudioProcessorPlayer
+{
+public:
+    AudioProcessorPlayer();
+    ~AudioProcessorPlayer() override;
+
+    std::unique_ptr<AudioProcessorPlayerMidiInput> makeMidiInput() const;
+
+private:
+    std::shared_ptr<AudioProcessorPlayerMidiInput::Target> _target;
+};
 } // namespace cxx_juce
 
 CXX_JUCE_DECLARE_RELOCATABLE(AudioPluginFormatManager)
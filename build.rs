@@ -6,17 +6,21 @@ fn main() {
     }
 
     let mut bridges = vec![
+        "src/juce_audio_basics/audio_source.rs",
         "src/juce_audio_basics/buffer.rs",
         "src/juce_audio_basics/filters.rs",
+        "src/juce_audio_basics/load_measurer.rs",
         "src/juce_audio_basics/midi.rs",
         "src/juce_audio_devices/device.rs",
         "src/juce_audio_devices/device_callback.rs",
         "src/juce_audio_devices/device_manager.rs",
         "src/juce_audio_devices/device_type.rs",
         "src/juce_audio_devices/midi_device_info.rs",
+        "src/juce_audio_devices/midi_device_list.rs",
         "src/juce_audio_devices/midi_input.rs",
         "src/juce_audio_devices/midi_output.rs",
         "src/juce_audio_devices/mod.rs",
+        "src/juce_audio_devices/source_player.rs",
         "src/juce_core/array.rs",
         "src/juce_core/bigint.rs",
         "src/juce_core/file.rs",
@@ -29,11 +33,19 @@ fn main() {
         "src/juce_events/mod.rs",
     ];
 
+    if cfg!(feature = "juce_audio_formats") {
+        bridges.append(&mut vec![
+            "src/juce_audio_formats/format.rs",
+            "src/juce_audio_formats/recorder.rs",
+        ]);
+    }
+
     if cfg!(feature = "juce_audio_processors") {
         bridges.append(&mut vec![
             "src/juce_audio_processors/plugin_description.rs",
             "src/juce_audio_processors/plugin_formats.rs",
             "src/juce_audio_processors/plugin_instance.rs",
+            "src/juce_audio_processors/processor_player.rs",
         ]);
     }
 
@@ -71,6 +83,10 @@ fn main() {
         cmake.define("CXX_JUCE_SYSTEM_JUCE_SOURCE", juce_path);
     }
 
+    if cfg!(feature = "juce_audio_formats") {
+        cmake.define("CXX_JUCE_AUDIO_FORMATS", "ON");
+    }
+
     if cfg!(feature = "juce_audio_processors") {
         cmake.define("CXX_JUCE_AUDIO_PROCESSORS", "ON");
 
@@ -6,16 +6,29 @@ fn main() -> Result<(), JuceError> {
     let mut audio_device_manager = AudioDeviceManager::new(&juce);
     audio_device_manager.initialise(2, 2)?;
 
-    let device_type = audio_device_manager.current_device_type().unwrap();
+    for device_type in audio_device_manager.probe_devices() {
+        println!("{}:", device_type.name);
 
-    println!("Inputs:");
-    for input in &device_type.get_input_device_names() {
-        println!("  {}", input);
-    }
+        for device in device_type.devices {
+            println!("  {}", device.name);
+            println!("    Inputs: {:?}", device.input_channel_names);
+            println!("    Outputs: {:?}", device.output_channel_names);
+            println!("    Sample rates: {:?}", device.sample_rates);
+            println!(
+                "    Buffer sizes: {:?} (default {})",
+                device.buffer_sizes, device.default_buffer_size
+            );
 
-    println!("Outputs:");
-    for output in &device_type.get_output_device_names() {
-        println!("  {}", output);
+            match device.open_error {
+                Some(error) => println!("    Couldn't be opened: {error}"),
+                None => println!(
+                    "    Latency: {} in, {} out, {} bits",
+                    device.input_latency.unwrap_or_default(),
+                    device.output_latency.unwrap_or_default(),
+                    device.bit_depth.unwrap_or_default()
+                ),
+            }
+        }
     }
 
     Ok(())
@@ -0,0 +1,46 @@
+use cxx_juce::{
+    juce_audio_basics::{MidiFile, MidiMessage, MidiMessageSequence},
+    juce_audio_devices::{MidiFilePlayer, MidiOutput},
+    JUCE,
+};
+use std::{thread::sleep, time::Duration};
+
+fn main() {
+    let _juce = JUCE::initialise();
+
+    let default_device = MidiOutput::get_default_device();
+    let output = MidiOutput::open_device(&default_device.identifier);
+    if output.is_null() {
+        println!("Failed to open device");
+        return;
+    }
+
+    const TICKS_PER_QUARTER_NOTE: i32 = 480;
+    const NOTES: [i32; 4] = [60, 64, 67, 72];
+
+    let mut track = MidiMessageSequence::default();
+    for (beat, note) in NOTES.into_iter().enumerate() {
+        let start = f64::from(beat as i32 * TICKS_PER_QUARTER_NOTE);
+        let end = start + f64::from(TICKS_PER_QUARTER_NOTE / 2);
+
+        track.add_event(&MidiMessage::note_on(1, note, 0.8), start);
+        track.add_event(&MidiMessage::note_off(1, note, 0.0), end);
+    }
+
+    let mut file = MidiFile::default();
+    file.set_ticks_per_quarter_note(TICKS_PER_QUARTER_NOTE);
+    file.add_track(&track);
+
+    let player = MidiFilePlayer::new(&file, output);
+    player.set_loop(Some(0.0..player.length() + 0.25));
+
+    println!("Playing at the file's tempo...");
+    player.play();
+    sleep(Duration::from_secs(4));
+
+    println!("Playing at 180 BPM...");
+    player.set_tempo_override(Some(180.0));
+    sleep(Duration::from_secs(4));
+
+    player.stop();
+}
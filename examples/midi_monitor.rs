@@ -0,0 +1,50 @@
+use cxx_juce::{
+    juce_audio_basics::MidiMessage,
+    juce_audio_devices::{MidiInput, MidiInputCallback},
+    JUCE,
+};
+use std::sync::{Arc, Mutex};
+
+struct Monitor;
+
+impl MidiInputCallback for Monitor {
+    fn handle_incoming_midi_message(&mut self, source: &MidiInput, message: &MidiMessage) {
+        println!("{}: {message:?}", source.get_device_info().name);
+    }
+
+    fn handle_partial_sysex_message(&mut self, source: &MidiInput, data: &[u8], _: f64) {
+        println!(
+            "{}: receiving sysex, {} bytes so far",
+            source.get_device_info().name,
+            data.len()
+        );
+    }
+}
+
+fn main() {
+    let _juce = JUCE::initialise();
+
+    let monitor = Arc::new(Mutex::new(Monitor));
+
+    let mut inputs: Vec<_> = MidiInput::get_available_devices()
+        .as_slice()
+        .iter()
+        .filter_map(|device| MidiInput::open_with_callback(&device.identifier, monitor.clone()))
+        .collect();
+
+    if inputs.is_empty() {
+        println!("No MIDI input devices found");
+        return;
+    }
+
+    for input in &mut inputs {
+        println!("Monitoring {:?}", input.get_device_info());
+        input.pin_mut().start();
+    }
+
+    std::thread::sleep(std::time::Duration::from_secs(10));
+
+    for input in &mut inputs {
+        input.pin_mut().stop();
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{define_trait, juce_audio_basics::AudioSampleBuffer};
+use cxx::UniquePtr;
+use std::pin::Pin;
+
+#[cxx::bridge(namespace = "juce")]
+mod juce {
+    unsafe extern "C++" {
+        include!("cxx_juce.h");
+
+        /// A source of audio that can be pulled from block by block.
+        type AudioSource;
+
+        type AudioSampleBuffer = crate::juce_audio_basics::AudioSampleBuffer;
+
+        #[namespace = "cxx_juce"]
+        type BoxDynAudioSource = Box<dyn super::AudioSource>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "wrap"]
+        fn wrap_audio_source(source: BoxDynAudioSource) -> UniquePtr<AudioSource>;
+    }
+
+    #[namespace = "cxx_juce"]
+    extern "Rust" {
+        type AudioSourceImpl;
+
+        #[Self = "AudioSourceImpl"]
+        unsafe fn drop(source: *mut BoxDynAudioSource);
+
+        #[Self = "AudioSourceImpl"]
+        fn prepare_to_play(
+            source: &mut BoxDynAudioSource,
+            samples_per_block_expected: i32,
+            sample_rate: f64,
+        );
+
+        #[Self = "AudioSourceImpl"]
+        fn release_resources(source: &mut BoxDynAudioSource);
+
+        #[Self = "AudioSourceImpl"]
+        fn get_next_audio_block(
+            source: &mut BoxDynAudioSource,
+            buffer: Pin<&mut AudioSampleBuffer>,
+            start_sample: i32,
+            num_samples: i32,
+        );
+    }
+}
+
+define_trait! {
+    /// A trait that can be implemented to produce audio on demand.
+    ///
+    /// Types that implement this trait can be played through an audio device with
+    /// [`AudioSourcePlayer`](crate::juce_audio_devices::AudioSourcePlayer).
+    ///
+    /// This trait requires that implementors are [`Send`] because blocks are requested on the audio thread.
+    AudioSource: Send,
+    AudioSourceImpl,
+    "cxx_juce::BoxDynAudioSource",
+
+    /// Called before playback starts, with the expected block size and the sample rate.
+    fn prepare_to_play(&mut self, samples_per_block_expected: i32, sample_rate: f64);
+
+    /// Called when playback stops, so that any resources allocated for it can be released.
+    fn release_resources(&mut self) {}
+
+    /// Fills `num_samples` samples of each channel of `buffer`, starting at `start_sample`, with
+    /// the next block of audio.
+    fn get_next_audio_block(
+        &mut self,
+        buffer: Pin<&mut AudioSampleBuffer>,
+        start_sample: i32,
+        num_samples: i32,
+    );
+}
+
+/// The JUCE object that forwards to an [`AudioSource`].
+pub(crate) type JuceAudioSource = juce::AudioSource;
+
+pub(crate) fn wrap_audio_source(source: impl AudioSource + 'static) -> UniquePtr<JuceAudioSource> {
+    juce::wrap_audio_source(Box::new(source))
+}
@@ -1,8 +1,16 @@
+use cxx::UniquePtr;
 use std::pin::Pin;
 
 pub use juce::AudioSampleBuffer;
 
 impl AudioSampleBuffer {
+    /// Creates a buffer with the given number of channels and samples, all set to zero.
+    pub fn new(num_channels: i32, num_samples: i32) -> UniquePtr<Self> {
+        let mut buffer = juce::make_audio_sample_buffer(num_channels.max(0), num_samples.max(0));
+        buffer.pin_mut().clear();
+        buffer
+    }
+
     /// Returns the samples for the given channel as a read-only slice.
     pub fn get_read_slice(&self, channel: i32) -> &[f32] {
         if channel >= self.get_num_channels() {
@@ -41,6 +49,12 @@ mod juce {
         /// A multi-channel buffer of floating point audio samples.
         type AudioSampleBuffer;
 
+        #[cxx_name = "makeUnique"]
+        fn make_audio_sample_buffer(
+            num_channels: i32,
+            num_samples: i32,
+        ) -> UniquePtr<AudioSampleBuffer>;
+
         /// Returns the number of channels in the buffer.
         #[rust_name = "get_num_channels"]
         fn getNumChannels(self: &AudioSampleBuffer) -> i32;
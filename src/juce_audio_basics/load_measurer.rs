@@ -0,0 +1,127 @@
+use cxx::UniquePtr;
+use std::time::{Duration, Instant};
+
+pub use juce::AudioProcessLoadMeasurer;
+
+// JUCE's measurer is designed to be updated on the audio thread while other threads read the
+// load, and guards its state accordingly.
+unsafe impl Send for AudioProcessLoadMeasurer {}
+unsafe impl Sync for AudioProcessLoadMeasurer {}
+
+impl AudioProcessLoadMeasurer {
+    /// Creates a measurer, which must be [reset](AudioProcessLoadMeasurer::reset) with the
+    /// sample rate and block size before it measures anything.
+    pub fn new() -> UniquePtr<Self> {
+        juce::audio_process_load_measurer_new()
+    }
+
+    /// Resets the measurements for the given sample rate and block size.
+    pub fn reset(&self, sample_rate: f64, block_size: i32) {
+        juce::audio_process_load_measurer_reset(self, sample_rate, block_size);
+    }
+
+    /// Records how long it took to render a block of the size given to
+    /// [`AudioProcessLoadMeasurer::reset`].
+    pub fn register_block_render_time(&self, duration: Duration) {
+        juce::audio_process_load_measurer_register_block_render_time(
+            self,
+            duration.as_secs_f64() * 1000.0,
+        );
+    }
+
+    /// Records how long it took to render `num_samples` samples.
+    pub fn register_render_time(&self, duration: Duration, num_samples: i32) {
+        juce::audio_process_load_measurer_register_render_time(
+            self,
+            duration.as_secs_f64() * 1000.0,
+            num_samples,
+        );
+    }
+
+    /// Calls `f`, recording how long it took to render `num_samples` samples.
+    pub fn measure<R>(&self, num_samples: i32, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.register_render_time(start.elapsed(), num_samples);
+        result
+    }
+}
+
+#[cxx::bridge(namespace = "juce")]
+mod juce {
+    unsafe extern "C++" {
+        include!("cxx_juce.h");
+
+        /// Measures how much of the available time is spent rendering audio, and counts the
+        /// blocks that took too long.
+        type AudioProcessLoadMeasurer;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "makeUnique"]
+        fn audio_process_load_measurer_new() -> UniquePtr<AudioProcessLoadMeasurer>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "audioProcessLoadMeasurerReset"]
+        fn audio_process_load_measurer_reset(
+            measurer: &AudioProcessLoadMeasurer,
+            sample_rate: f64,
+            block_size: i32,
+        );
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "audioProcessLoadMeasurerRegisterBlockRenderTime"]
+        fn audio_process_load_measurer_register_block_render_time(
+            measurer: &AudioProcessLoadMeasurer,
+            milliseconds_taken: f64,
+        );
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "audioProcessLoadMeasurerRegisterRenderTime"]
+        fn audio_process_load_measurer_register_render_time(
+            measurer: &AudioProcessLoadMeasurer,
+            milliseconds_taken: f64,
+            num_samples: i32,
+        );
+
+        /// Returns the average load, where 1.0 means all of the available time was used.
+        #[cxx_name = "getLoadAsProportion"]
+        fn get_load_as_proportion(self: &AudioProcessLoadMeasurer) -> f64;
+
+        /// Returns the average load as a percentage.
+        #[cxx_name = "getLoadAsPercentage"]
+        fn get_load_as_percentage(self: &AudioProcessLoadMeasurer) -> f64;
+
+        /// Returns the number of blocks that took longer to render than the time available.
+        #[cxx_name = "getXRunCount"]
+        fn get_xrun_count(self: &AudioProcessLoadMeasurer) -> i32;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn measures_load_and_overruns() {
+        let measurer = AudioProcessLoadMeasurer::new();
+        measurer.reset(1000.0, 10);
+
+        measurer.register_block_render_time(Duration::from_millis(5));
+        assert!(measurer.get_load_as_proportion() > 0.0);
+        assert_eq!(measurer.get_xrun_count(), 0);
+
+        measurer.register_render_time(Duration::from_millis(20), 10);
+        assert_eq!(measurer.get_xrun_count(), 1);
+    }
+
+    #[test]
+    fn measures_closures() {
+        let measurer = AudioProcessLoadMeasurer::new();
+        measurer.reset(48000.0, 64);
+
+        let result = measurer.measure(64, || 1 + 1);
+
+        assert_eq!(result, 2);
+        assert!(measurer.get_load_as_percentage() >= 0.0);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::define_juce_type;
+use crate::{
+    define_array_into_iter, define_juce_type,
+    juce_audio_basics::{SmpteFrameRate, SmpteTimecode},
+};
 
 define_juce_type! {
     /// A buffer for holding a sequence of timestamped MIDI events.
@@ -20,6 +23,122 @@ define_juce_type! {
     debug = MidiMessage::get_description,
 }
 
+define_juce_type! {
+    /// A sequence of timestamped MIDI messages.
+    MidiMessageSequence,
+    layout = juce::MidiMessageSequenceLayout,
+    cxx_name = "juce::MidiMessageSequence",
+    default = juce::midi_message_sequence_new,
+    drop = juce::midi_message_sequence_drop,
+    clone = juce::midi_message_sequence_clone,
+    send,
+}
+
+impl MidiBuffer {
+    /// Returns the events in the buffer in order, with each message's timestamp set to its
+    /// sample position.
+    pub fn iter(&self) -> impl Iterator<Item = MidiMessage> {
+        let mut sequence = MidiMessageSequence::default();
+        juce::midi_buffer_add_to_sequence(self, &mut sequence);
+        sequence.into_iter()
+    }
+}
+
+impl MidiMessage {
+    /// Creates a message from its raw bytes, or returns [`None`] if `data` is empty.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        (!data.is_empty()).then(|| juce::midi_message_from_bytes(data))
+    }
+
+    /// Creates an MTC full-frame message for the given timecode.
+    pub fn full_frame(timecode: &SmpteTimecode) -> Self {
+        juce::midi_message_full_frame(
+            timecode.hours,
+            timecode.minutes,
+            timecode.seconds,
+            timecode.frames,
+            timecode.frame_rate as i32,
+        )
+    }
+
+    /// Returns the timecode of an MTC full-frame message, or [`None`] if this isn't one.
+    pub fn get_full_frame(&self) -> Option<SmpteTimecode> {
+        if !self.is_full_frame() {
+            return None;
+        }
+
+        let (mut hours, mut minutes, mut seconds, mut frames, mut frame_rate) = (0, 0, 0, 0, 0);
+        juce::midi_message_get_full_frame_parameters(
+            self,
+            &mut hours,
+            &mut minutes,
+            &mut seconds,
+            &mut frames,
+            &mut frame_rate,
+        );
+
+        Some(SmpteTimecode {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            frame_rate: SmpteFrameRate::from_index(frame_rate)?,
+        })
+    }
+}
+
+impl MidiMessageSequence {
+    /// Returns `true` if the sequence contains no events.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the message at the given index, or [`None`] if out of bounds.
+    pub fn get(&self, index: i32) -> Option<&MidiMessage> {
+        if index < 0 || index >= self.len() {
+            return None;
+        }
+
+        Some(juce::midi_message_sequence_get(self, index))
+    }
+
+    /// Returns a mutable reference to the message at the given index, or [`None`] if out of bounds.
+    ///
+    /// Call [`MidiMessageSequence::update_matched_pairs`] after changing note numbers or
+    /// channels so that note-ons are linked to the right note-offs.
+    pub fn get_mut(&mut self, index: i32) -> Option<&mut MidiMessage> {
+        if index < 0 || index >= self.len() {
+            return None;
+        }
+
+        Some(juce::midi_message_sequence_get_mut(self, index))
+    }
+
+    /// Inserts a copy of the message into the sequence, keeping the events in time order.
+    ///
+    /// The `time_adjustment` is added to the message's timestamp before it is inserted.
+    pub fn add_event(&mut self, message: &MidiMessage, time_adjustment: f64) {
+        self.add_event_raw(message, time_adjustment);
+    }
+
+    /// Returns an iterator over the messages in the sequence.
+    pub fn iter(&self) -> MidiMessageSequenceIterRef<'_> {
+        self.into_iter()
+    }
+}
+
+define_array_into_iter! {
+    MidiMessageSequence => MidiMessageSequenceIter,
+    MidiMessage,
+    MidiMessageSequence::get
+}
+
+define_array_into_iter! {
+    MidiMessageSequence => MidiMessageSequenceIterRef,
+    ref MidiMessage,
+    MidiMessageSequence::get
+}
+
 define_juce_type! {
     /// A MIDI file.
     MidiFile,
@@ -27,6 +146,34 @@ define_juce_type! {
     cxx_name = "juce::MidiFile",
     default = juce::midi_file_new,
     drop = juce::midi_file_drop,
+    clone = juce::midi_file_clone,
+}
+
+impl MidiFile {
+    /// Returns the track at the given index, or [`None`] if out of bounds.
+    pub fn get_track(&self, index: i32) -> Option<&MidiMessageSequence> {
+        unsafe { self.get_track_raw(index).as_ref() }
+    }
+
+    /// Returns an iterator over the tracks in the file.
+    pub fn tracks(&self) -> impl Iterator<Item = &MidiMessageSequence> {
+        (0..self.get_num_tracks()).filter_map(|index| self.get_track(index))
+    }
+
+    /// Parses a standard MIDI file, or returns [`None`] if the data is not a valid MIDI file.
+    ///
+    /// Timestamps in the returned file are in ticks.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut file = Self::default();
+        juce::midi_file_read_from(&mut file, data).then_some(file)
+    }
+
+    /// Writes the file out as a standard MIDI file of the given type (0, 1 or 2), or returns
+    /// [`None`] if it couldn't be written.
+    pub fn to_bytes(&self, midi_file_type: i32) -> Option<Vec<u8>> {
+        let mut bytes = Vec::new();
+        juce::midi_file_write_to(self, midi_file_type, &mut bytes).then_some(bytes)
+    }
 }
 
 #[cxx::bridge(namespace = "juce")]
@@ -41,6 +188,11 @@ mod juce {
         Alignment = 8,
     }
 
+    enum MidiMessageSequenceLayout {
+        Size = 16,
+        Alignment = 8,
+    }
+
     enum MidiFileLayout {
         Size = 24,
         Alignment = 8,
@@ -80,6 +232,10 @@ mod juce {
         #[cxx_name = "clear"]
         fn clear_range(self: &mut MidiBuffer, start_sample: i32, num_samples: i32);
 
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "midiBufferAddToSequence"]
+        fn midi_buffer_add_to_sequence(buffer: &MidiBuffer, sequence: &mut MidiMessageSequence);
+
         type MidiMessage = super::MidiMessage;
 
         #[namespace = "cxx_juce"]
@@ -90,6 +246,10 @@ mod juce {
         #[cxx_name = "construct"]
         fn midi_message_clone(message: &MidiMessage) -> MidiMessage;
 
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "midiMessageFromBytes"]
+        fn midi_message_from_bytes(data: &[u8]) -> MidiMessage;
+
         /// Creates a note-on message.
         #[cxx_name = "noteOn"]
         #[Self = "MidiMessage"]
@@ -132,6 +292,274 @@ mod juce {
         #[cxx_name = "getTimeStamp"]
         fn get_time_stamp(self: &MidiMessage) -> f64;
 
+        /// Sets the timestamp of the message.
+        #[cxx_name = "setTimeStamp"]
+        fn set_time_stamp(self: &mut MidiMessage, new_timestamp: f64);
+
+        /// Adds a value to the timestamp of the message.
+        #[cxx_name = "addToTimeStamp"]
+        fn add_to_time_stamp(self: &mut MidiMessage, delta: f64);
+
+        /// Changes the channel (1-16) of a channel message.
+        #[cxx_name = "setChannel"]
+        fn set_channel(self: &mut MidiMessage, new_channel: i32);
+
+        /// Changes the note number of a note-on, note-off or aftertouch message.
+        #[cxx_name = "setNoteNumber"]
+        fn set_note_number(self: &mut MidiMessage, new_note_number: i32);
+
+        /// Changes the velocity (0.0-1.0) of a note-on or note-off message.
+        #[cxx_name = "setVelocity"]
+        fn set_velocity(self: &mut MidiMessage, new_velocity: f32);
+
+        /// Returns `true` if this is a note-on or note-off message.
+        #[cxx_name = "isNoteOnOrOff"]
+        fn is_note_on_or_off(self: &MidiMessage) -> bool;
+
+        /// Returns `true` if this is a polyphonic aftertouch message.
+        #[cxx_name = "isAftertouch"]
+        fn is_aftertouch(self: &MidiMessage) -> bool;
+
+        /// Returns `true` if this is a system-exclusive message.
+        #[cxx_name = "isSysEx"]
+        fn is_sys_ex(self: &MidiMessage) -> bool;
+
+        /// Returns `true` if this is a meta-event.
+        #[cxx_name = "isMetaEvent"]
+        fn is_meta_event(self: &MidiMessage) -> bool;
+
+        /// Creates a controller message.
+        #[cxx_name = "controllerEvent"]
+        #[Self = "MidiMessage"]
+        fn controller_event(channel: i32, controller_type: i32, value: i32) -> MidiMessage;
+
+        /// Returns `true` if this is a controller message.
+        #[cxx_name = "isController"]
+        fn is_controller(self: &MidiMessage) -> bool;
+
+        /// Returns the controller number of a controller message.
+        #[cxx_name = "getControllerNumber"]
+        fn get_controller_number(self: &MidiMessage) -> i32;
+
+        /// Returns the value of a controller message.
+        #[cxx_name = "getControllerValue"]
+        fn get_controller_value(self: &MidiMessage) -> i32;
+
+        /// Returns `true` if this is a program change message.
+        #[cxx_name = "isProgramChange"]
+        fn is_program_change(self: &MidiMessage) -> bool;
+
+        /// Returns `true` if this is a channel-pressure message.
+        #[cxx_name = "isChannelPressure"]
+        fn is_channel_pressure(self: &MidiMessage) -> bool;
+
+        /// Returns `true` if this is a pitch-wheel message.
+        #[cxx_name = "isPitchWheel"]
+        fn is_pitch_wheel(self: &MidiMessage) -> bool;
+
+        /// Creates an MTC quarter-frame message.
+        #[cxx_name = "quarterFrame"]
+        #[Self = "MidiMessage"]
+        fn quarter_frame(sequence_number: i32, value: i32) -> MidiMessage;
+
+        /// Returns `true` if this is an MTC quarter-frame message.
+        #[cxx_name = "isQuarterFrame"]
+        fn is_quarter_frame(self: &MidiMessage) -> bool;
+
+        /// Returns the sequence number (0-7) of an MTC quarter-frame message.
+        #[cxx_name = "getQuarterFrameSequenceNumber"]
+        fn get_quarter_frame_sequence_number(self: &MidiMessage) -> i32;
+
+        /// Returns the value (0-15) of an MTC quarter-frame message.
+        #[cxx_name = "getQuarterFrameValue"]
+        fn get_quarter_frame_value(self: &MidiMessage) -> i32;
+
+        /// Returns `true` if this is an MTC full-frame message.
+        #[cxx_name = "isFullFrame"]
+        fn is_full_frame(self: &MidiMessage) -> bool;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "midiMessageFullFrame"]
+        fn midi_message_full_frame(
+            hours: i32,
+            minutes: i32,
+            seconds: i32,
+            frames: i32,
+            frame_rate: i32,
+        ) -> MidiMessage;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "midiMessageGetFullFrameParameters"]
+        fn midi_message_get_full_frame_parameters(
+            message: &MidiMessage,
+            hours: &mut i32,
+            minutes: &mut i32,
+            seconds: &mut i32,
+            frames: &mut i32,
+            frame_rate: &mut i32,
+        );
+
+        /// Creates a MIDI clock message.
+        #[cxx_name = "midiClock"]
+        #[Self = "MidiMessage"]
+        fn midi_clock() -> MidiMessage;
+
+        /// Returns `true` if this is a MIDI clock message.
+        #[cxx_name = "isMidiClock"]
+        fn is_midi_clock(self: &MidiMessage) -> bool;
+
+        /// Creates a MIDI start message.
+        #[cxx_name = "midiStart"]
+        #[Self = "MidiMessage"]
+        fn midi_start() -> MidiMessage;
+
+        /// Returns `true` if this is a MIDI start message.
+        #[cxx_name = "isMidiStart"]
+        fn is_midi_start(self: &MidiMessage) -> bool;
+
+        /// Creates a MIDI continue message.
+        #[cxx_name = "midiContinue"]
+        #[Self = "MidiMessage"]
+        fn midi_continue() -> MidiMessage;
+
+        /// Returns `true` if this is a MIDI continue message.
+        #[cxx_name = "isMidiContinue"]
+        fn is_midi_continue(self: &MidiMessage) -> bool;
+
+        /// Creates a MIDI stop message.
+        #[cxx_name = "midiStop"]
+        #[Self = "MidiMessage"]
+        fn midi_stop() -> MidiMessage;
+
+        /// Returns `true` if this is a MIDI stop message.
+        #[cxx_name = "isMidiStop"]
+        fn is_midi_stop(self: &MidiMessage) -> bool;
+
+        /// Creates a song position pointer message, with the position given in MIDI beats
+        /// (sixteenth notes).
+        #[cxx_name = "songPositionPointer"]
+        #[Self = "MidiMessage"]
+        fn song_position_pointer(position_in_midi_beats: i32) -> MidiMessage;
+
+        /// Returns `true` if this is a song position pointer message.
+        #[cxx_name = "isSongPositionPointer"]
+        fn is_song_position_pointer(self: &MidiMessage) -> bool;
+
+        /// Returns the position, in MIDI beats, of a song position pointer message.
+        #[cxx_name = "getSongPositionPointerMidiBeat"]
+        fn get_song_position_pointer_midi_beat(self: &MidiMessage) -> i32;
+
+        /// Returns `true` if this is an active-sense message.
+        #[cxx_name = "isActiveSense"]
+        fn is_active_sense(self: &MidiMessage) -> bool;
+
+        /// Creates a tempo meta-event.
+        #[cxx_name = "tempoMetaEvent"]
+        #[Self = "MidiMessage"]
+        fn tempo_meta_event(microseconds_per_quarter_note: i32) -> MidiMessage;
+
+        /// Returns `true` if this is a tempo meta-event.
+        #[cxx_name = "isTempoMetaEvent"]
+        fn is_tempo_meta_event(self: &MidiMessage) -> bool;
+
+        /// Returns the tempo of a tempo meta-event, in seconds per quarter note.
+        #[cxx_name = "getTempoSecondsPerQuarterNote"]
+        fn get_tempo_seconds_per_quarter_note(self: &MidiMessage) -> f64;
+
+        type MidiMessageSequence = super::MidiMessageSequence;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "construct"]
+        fn midi_message_sequence_new() -> MidiMessageSequence;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "drop"]
+        fn midi_message_sequence_drop(sequence: &mut MidiMessageSequence);
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "construct"]
+        fn midi_message_sequence_clone(sequence: &MidiMessageSequence) -> MidiMessageSequence;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "midiMessageSequenceGet"]
+        fn midi_message_sequence_get(sequence: &MidiMessageSequence, index: i32) -> &MidiMessage;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "midiMessageSequenceGetMut"]
+        fn midi_message_sequence_get_mut(
+            sequence: &mut MidiMessageSequence,
+            index: i32,
+        ) -> &mut MidiMessage;
+
+        type MidiEventHolder;
+
+        /// Returns the number of events in the sequence.
+        #[cxx_name = "getNumEvents"]
+        fn len(self: &MidiMessageSequence) -> i32;
+
+        #[doc(hidden)]
+        #[cxx_name = "addEvent"]
+        fn add_event_raw(
+            self: &mut MidiMessageSequence,
+            message: &MidiMessage,
+            time_adjustment: f64,
+        ) -> *mut MidiEventHolder;
+
+        /// Removes the event at the given index, optionally along with its matching note-off.
+        #[cxx_name = "deleteEvent"]
+        fn delete_event(self: &mut MidiMessageSequence, index: i32, delete_matching_note_up: bool);
+
+        /// Merges another sequence into this one, offsetting its events by `time_adjustment`.
+        #[cxx_name = "addSequence"]
+        fn add_sequence(
+            self: &mut MidiMessageSequence,
+            other: &MidiMessageSequence,
+            time_adjustment: f64,
+        );
+
+        /// Removes all events from the sequence.
+        fn clear(self: &mut MidiMessageSequence);
+
+        /// Re-links each note-on with its matching note-off.
+        #[cxx_name = "updateMatchedPairs"]
+        fn update_matched_pairs(self: &mut MidiMessageSequence);
+
+        /// Sorts the events by timestamp.
+        fn sort(self: &mut MidiMessageSequence);
+
+        /// Returns the index of the note-off matching the note-on at the given index, or -1.
+        #[cxx_name = "getIndexOfMatchingKeyUp"]
+        fn get_index_of_matching_key_up(self: &MidiMessageSequence, index: i32) -> i32;
+
+        /// Returns the time of the note-off matching the note-on at the given index.
+        #[cxx_name = "getTimeOfMatchingKeyUp"]
+        fn get_time_of_matching_key_up(self: &MidiMessageSequence, index: i32) -> f64;
+
+        /// Returns the index of the first event at or after the given time.
+        #[cxx_name = "getNextIndexAtTime"]
+        fn get_next_index_at_time(self: &MidiMessageSequence, time_stamp: f64) -> i32;
+
+        /// Returns the timestamp of the first event.
+        #[cxx_name = "getStartTime"]
+        fn get_start_time(self: &MidiMessageSequence) -> f64;
+
+        /// Returns the timestamp of the last event.
+        #[cxx_name = "getEndTime"]
+        fn get_end_time(self: &MidiMessageSequence) -> f64;
+
+        /// Adds a value to the timestamps of all the events.
+        #[cxx_name = "addTimeToMessages"]
+        fn add_time_to_messages(self: &mut MidiMessageSequence, delta_time: f64);
+
+        /// Copies the messages for a channel (1-16) into another sequence.
+        #[cxx_name = "extractMidiChannelMessages"]
+        fn extract_midi_channel_messages(
+            self: &MidiMessageSequence,
+            channel: i32,
+            destination: &mut MidiMessageSequence,
+            also_include_meta_events: bool,
+        );
+
         type MidiFile = super::MidiFile;
 
         #[namespace = "cxx_juce"]
@@ -142,6 +570,33 @@ mod juce {
         #[cxx_name = "construct"]
         fn midi_file_new() -> MidiFile;
 
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "construct"]
+        fn midi_file_clone(file: &MidiFile) -> MidiFile;
+
+        #[doc(hidden)]
+        #[cxx_name = "getTrack"]
+        fn get_track_raw(self: &MidiFile, index: i32) -> *const MidiMessageSequence;
+
+        /// Adds a copy of the sequence as a new track.
+        #[cxx_name = "addTrack"]
+        fn add_track(self: &mut MidiFile, track: &MidiMessageSequence);
+
+        /// Removes all the tracks from the file.
+        fn clear(self: &mut MidiFile);
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "midiFileReadFrom"]
+        fn midi_file_read_from(file: &mut MidiFile, data: &[u8]) -> bool;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "midiFileWriteTo"]
+        fn midi_file_write_to(file: &MidiFile, midi_file_type: i32, bytes: &mut Vec<u8>) -> bool;
+
+        /// Copies all the tempo meta-events from every track into a sequence.
+        #[cxx_name = "findAllTempoEvents"]
+        fn find_all_tempo_events(self: &MidiFile, tempo_change_events: &mut MidiMessageSequence);
+
         /// Returns the number of tracks in the file.
         #[cxx_name = "getNumTracks"]
         fn get_num_tracks(self: &MidiFile) -> i32;
@@ -0,0 +1,300 @@
+use crate::juce_audio_basics::{MidiFile, MidiMessage, MidiMessageSequence};
+use std::ops::RangeInclusive;
+
+impl MidiMessageSequence {
+    /// Moves notes towards the nearest multiple of `grid`.
+    ///
+    /// A `strength` of `1.0` snaps each note-on exactly onto the grid, `0.0` leaves it where it
+    /// is. Note-offs move by the same amount as their note-on, so note lengths are preserved.
+    pub fn quantize(&mut self, grid: f64, strength: f64) {
+        if grid <= 0.0 {
+            return;
+        }
+
+        let strength = strength.clamp(0.0, 1.0);
+        self.update_matched_pairs();
+
+        let mut offsets = vec![0.0; self.len() as usize];
+        for (index, message) in self.iter().enumerate() {
+            if !message.is_note_on(false) {
+                continue;
+            }
+
+            let time = message.get_time_stamp();
+            let offset = ((time / grid).round() * grid - time) * strength;
+            offsets[index] = offset;
+
+            let note_off = self.get_index_of_matching_key_up(index as i32);
+            if note_off >= 0 {
+                offsets[note_off as usize] = offset;
+            }
+        }
+
+        let messages = self
+            .iter()
+            .zip(offsets)
+            .map(|(message, offset)| {
+                let mut message = message.clone();
+                message.add_to_time_stamp(offset);
+                message
+            })
+            .collect();
+
+        self.rebuild(messages);
+    }
+
+    /// Transposes notes by a number of semitones, clamping the results to the given note range.
+    ///
+    /// Notes that would fall outside `range` are pinned to its nearest end, so notes near the
+    /// edges may end up sharing a pitch.
+    pub fn transpose(&mut self, semitones: i32, range: RangeInclusive<i32>) {
+        let low = (*range.start()).clamp(0, 127);
+        let high = (*range.end()).clamp(low, 127);
+
+        for index in 0..self.len() {
+            if let Some(message) = self.get_mut(index) {
+                if message.is_note_on_or_off() || message.is_aftertouch() {
+                    let note = (message.get_note_number() + semitones).clamp(low, high);
+                    message.set_note_number(note);
+                }
+            }
+        }
+
+        self.update_matched_pairs();
+    }
+
+    /// Multiplies the velocity of every note-on by `factor`.
+    ///
+    /// Velocities are kept in the range 1-127 so that no note-on turns into a note-off.
+    pub fn scale_velocities(&mut self, factor: f32) {
+        for index in 0..self.len() {
+            if let Some(message) = self.get_mut(index) {
+                if message.is_note_on(false) {
+                    let velocity = (f32::from(message.get_velocity()) * factor)
+                        .round()
+                        .clamp(1.0, 127.0);
+                    message.set_velocity(velocity / 127.0);
+                }
+            }
+        }
+    }
+
+    /// Maps the channel of every channel message through `map`.
+    ///
+    /// The returned channel is clamped to the range 1-16. System and meta messages are left
+    /// untouched.
+    pub fn remap_channels(&mut self, mut map: impl FnMut(i32) -> i32) {
+        for index in 0..self.len() {
+            if let Some(message) = self.get_mut(index) {
+                let channel = message.get_channel();
+                if channel > 0 {
+                    message.set_channel(map(channel).clamp(1, 16));
+                }
+            }
+        }
+
+        self.update_matched_pairs();
+    }
+
+    /// Merges several sequences into a single sequence.
+    pub fn merge<'a>(sequences: impl IntoIterator<Item = &'a MidiMessageSequence>) -> Self {
+        let messages = sequences
+            .into_iter()
+            .flat_map(|sequence| sequence.iter().cloned())
+            .collect();
+
+        let mut merged = Self::default();
+        merged.rebuild(messages);
+        merged
+    }
+
+    /// Replaces the contents of the sequence with the given messages.
+    ///
+    /// The messages are sorted by time, with note-offs placed ahead of note-ons that share a
+    /// timestamp, so that back-to-back notes on the same pitch stay paired correctly.
+    fn rebuild(&mut self, mut messages: Vec<MidiMessage>) {
+        messages.sort_by(|a, b| {
+            a.get_time_stamp()
+                .total_cmp(&b.get_time_stamp())
+                .then_with(|| b.is_note_off(true).cmp(&a.is_note_off(true)))
+        });
+
+        self.clear();
+        for message in &messages {
+            self.add_event(message, 0.0);
+        }
+
+        self.update_matched_pairs();
+    }
+}
+
+impl MidiFile {
+    /// Splits the file into one file per track.
+    ///
+    /// Each file keeps the time format of the original. Tempo and other meta events stay with
+    /// the track that contained them.
+    pub fn split_by_track(&self) -> Vec<MidiFile> {
+        self.tracks()
+            .map(|track| {
+                let mut file = self.empty_copy();
+                file.add_track(track);
+                file
+            })
+            .collect()
+    }
+
+    /// Splits the file into one file per MIDI channel, returning the channel (1-16) alongside
+    /// each file.
+    ///
+    /// Each file keeps the track layout of the original, with every track filtered down to a
+    /// single channel. Meta events such as tempo changes are copied into every file. Channels
+    /// that are not used by the file are skipped.
+    pub fn split_by_channel(&self) -> Vec<(i32, MidiFile)> {
+        (1..=16)
+            .filter(|&channel| {
+                self.tracks()
+                    .flat_map(MidiMessageSequence::iter)
+                    .any(|message| message.get_channel() == channel)
+            })
+            .map(|channel| {
+                let mut file = self.empty_copy();
+                for track in self.tracks() {
+                    let mut filtered = MidiMessageSequence::default();
+                    track.extract_midi_channel_messages(channel, &mut filtered, true);
+                    file.add_track(&filtered);
+                }
+                (channel, file)
+            })
+            .collect()
+    }
+
+    fn empty_copy(&self) -> MidiFile {
+        let mut file = self.clone();
+        file.clear();
+        file
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn note(sequence: &mut MidiMessageSequence, note: i32, start: f64, end: f64) {
+        sequence.add_event(&MidiMessage::note_on(1, note, 0.5), start);
+        sequence.add_event(&MidiMessage::note_off(1, note, 0.0), end);
+    }
+
+    fn times(sequence: &MidiMessageSequence) -> Vec<f64> {
+        sequence.iter().map(MidiMessage::get_time_stamp).collect()
+    }
+
+    #[test]
+    fn quantizing_preserves_note_lengths() {
+        let mut sequence = MidiMessageSequence::default();
+        note(&mut sequence, 60, 0.75, 1.25);
+        note(&mut sequence, 62, 2.25, 2.75);
+
+        sequence.quantize(1.0, 1.0);
+        assert_eq!(times(&sequence), [1.0, 1.5, 2.0, 2.5]);
+
+        let mut sequence = MidiMessageSequence::default();
+        note(&mut sequence, 60, 0.5, 1.0);
+        sequence.quantize(1.0, 0.5);
+        assert_eq!(times(&sequence), [0.75, 1.25]);
+    }
+
+    #[test]
+    fn quantizing_keeps_repeated_notes_paired() {
+        let mut sequence = MidiMessageSequence::default();
+        note(&mut sequence, 60, 0.0, 1.0);
+        note(&mut sequence, 60, 1.25, 2.0);
+
+        sequence.quantize(1.0, 1.0);
+
+        assert_eq!(times(&sequence), [0.0, 1.0, 1.0, 1.75]);
+        assert!(sequence.get(1).unwrap().is_note_off(true));
+        assert_eq!(sequence.get_time_of_matching_key_up(0), 1.0);
+        assert_eq!(sequence.get_time_of_matching_key_up(2), 1.75);
+    }
+
+    #[test]
+    fn transposing_clamps_to_range() {
+        let mut sequence = MidiMessageSequence::default();
+        note(&mut sequence, 60, 0.0, 1.0);
+        note(&mut sequence, 70, 1.0, 2.0);
+
+        sequence.transpose(5, 0..=72);
+
+        let notes: Vec<_> = sequence.iter().map(MidiMessage::get_note_number).collect();
+        assert_eq!(notes, [65, 65, 72, 72]);
+        assert_eq!(sequence.get_index_of_matching_key_up(0), 1);
+    }
+
+    #[test]
+    fn scaling_velocities_never_produces_note_offs() {
+        let mut sequence = MidiMessageSequence::default();
+        note(&mut sequence, 60, 0.0, 1.0);
+
+        sequence.scale_velocities(0.0);
+        assert!(sequence.get(0).unwrap().is_note_on(false));
+        assert_eq!(sequence.get(0).unwrap().get_velocity(), 1);
+
+        sequence.scale_velocities(1000.0);
+        assert_eq!(sequence.get(0).unwrap().get_velocity(), 127);
+    }
+
+    #[test]
+    fn merging_sequences() {
+        let mut a = MidiMessageSequence::default();
+        note(&mut a, 60, 0.0, 1.0);
+
+        let mut b = MidiMessageSequence::default();
+        note(&mut b, 64, 0.5, 1.5);
+
+        let merged = MidiMessageSequence::merge([&a, &b]);
+
+        assert_eq!(times(&merged), [0.0, 0.5, 1.0, 1.5]);
+        assert_eq!(merged.get_index_of_matching_key_up(0), 2);
+        assert_eq!(merged.get_index_of_matching_key_up(1), 3);
+    }
+
+    #[test]
+    fn remapping_channels() {
+        let mut sequence = MidiMessageSequence::default();
+        note(&mut sequence, 60, 0.0, 1.0);
+
+        sequence.remap_channels(|channel| channel + 9);
+
+        assert!(sequence.iter().all(|message| message.get_channel() == 10));
+        assert_eq!(sequence.get_index_of_matching_key_up(0), 1);
+    }
+
+    #[test]
+    fn splitting_a_file_by_channel() {
+        let mut track = MidiMessageSequence::default();
+        note(&mut track, 60, 0.0, 1.0);
+        track.add_event(&MidiMessage::note_on(2, 64, 0.5), 0.0);
+        track.add_event(&MidiMessage::note_off(2, 64, 0.0), 1.0);
+
+        let mut file = MidiFile::default();
+        file.set_ticks_per_quarter_note(480);
+        file.add_track(&track);
+
+        let split = file.split_by_channel();
+        let channels: Vec<_> = split.iter().map(|(channel, _)| *channel).collect();
+        assert_eq!(channels, [1, 2]);
+
+        for (channel, file) in &split {
+            assert_eq!(file.get_time_format(), 480);
+            assert_eq!(file.get_num_tracks(), 1);
+
+            let track = file.get_track(0).unwrap();
+            assert_eq!(track.len(), 2);
+            assert!(track
+                .iter()
+                .all(|message| message.get_channel() == *channel));
+        }
+
+        assert_eq!(file.split_by_track().len(), 1);
+    }
+}
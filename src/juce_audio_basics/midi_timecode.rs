@@ -0,0 +1,306 @@
+use crate::juce_audio_basics::MidiMessage;
+
+/// The frame rates supported by MIDI time code.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SmpteFrameRate {
+    /// 24 frames per second.
+    Fps24 = 0,
+    /// 25 frames per second.
+    Fps25 = 1,
+    /// 29.97 frames per second, drop-frame.
+    Fps30Drop = 2,
+    /// 30 frames per second.
+    Fps30 = 3,
+}
+
+impl SmpteFrameRate {
+    /// Returns the number of frames per second.
+    pub fn frames_per_second(self) -> f64 {
+        match self {
+            Self::Fps24 => 24.0,
+            Self::Fps25 => 25.0,
+            Self::Fps30Drop => 30_000.0 / 1001.0,
+            Self::Fps30 => 30.0,
+        }
+    }
+
+    /// Returns the number of frame labels in each second, e.g. 30 for drop-frame.
+    fn nominal_frames_per_second(self) -> i32 {
+        match self {
+            Self::Fps24 => 24,
+            Self::Fps25 => 25,
+            Self::Fps30Drop | Self::Fps30 => 30,
+        }
+    }
+
+    pub(crate) fn from_index(index: i32) -> Option<Self> {
+        match index {
+            0 => Some(Self::Fps24),
+            1 => Some(Self::Fps25),
+            2 => Some(Self::Fps30Drop),
+            3 => Some(Self::Fps30),
+            _ => None,
+        }
+    }
+}
+
+/// A position expressed as SMPTE hours, minutes, seconds and frames.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SmpteTimecode {
+    /// The hours (0-23).
+    pub hours: i32,
+    /// The minutes (0-59).
+    pub minutes: i32,
+    /// The seconds (0-59).
+    pub seconds: i32,
+    /// The frame within the second.
+    pub frames: i32,
+    /// The frame rate.
+    pub frame_rate: SmpteFrameRate,
+}
+
+const DROP_FRAMES_PER_MINUTE: i64 = 60 * 30 - 2;
+const DROP_FRAMES_PER_TEN_MINUTES: i64 = 10 * DROP_FRAMES_PER_MINUTE + 2;
+
+impl SmpteTimecode {
+    /// Returns the timecode of the frame that contains the given time, in seconds.
+    ///
+    /// Times wrap around after 24 hours.
+    pub fn from_seconds(seconds: f64, frame_rate: SmpteFrameRate) -> Self {
+        let frame = (seconds.max(0.0) * frame_rate.frames_per_second() + 1.0e-9).floor() as i64;
+        Self::from_frame_number(frame, frame_rate)
+    }
+
+    /// Returns the time, in seconds, at which this frame starts.
+    pub fn to_seconds(&self) -> f64 {
+        self.frame_number() as f64 / self.frame_rate.frames_per_second()
+    }
+
+    /// Returns the number of frames since midnight.
+    pub fn frame_number(&self) -> i64 {
+        let fps = i64::from(self.frame_rate.nominal_frames_per_second());
+        let total_minutes = i64::from(self.hours) * 60 + i64::from(self.minutes);
+        let labels = (total_minutes * 60 + i64::from(self.seconds)) * fps + i64::from(self.frames);
+
+        if self.frame_rate == SmpteFrameRate::Fps30Drop {
+            labels - 2 * (total_minutes - total_minutes / 10)
+        } else {
+            labels
+        }
+    }
+
+    /// Returns the timecode of the given number of frames since midnight.
+    pub fn from_frame_number(frame: i64, frame_rate: SmpteFrameRate) -> Self {
+        let fps = i64::from(frame_rate.nominal_frames_per_second());
+        let mut labels = frame.max(0);
+
+        if frame_rate == SmpteFrameRate::Fps30Drop {
+            let tens = labels / DROP_FRAMES_PER_TEN_MINUTES;
+            let remainder = labels % DROP_FRAMES_PER_TEN_MINUTES;
+            labels += 18 * tens;
+            if remainder > 1 {
+                labels += 2 * ((remainder - 2) / DROP_FRAMES_PER_MINUTE);
+            }
+        }
+
+        Self {
+            hours: (labels / (fps * 3600) % 24) as i32,
+            minutes: (labels / (fps * 60) % 60) as i32,
+            seconds: (labels / fps % 60) as i32,
+            frames: (labels % fps) as i32,
+            frame_rate,
+        }
+    }
+
+    /// Returns the timecode `frames` frames later (or earlier, if negative).
+    pub fn offset_by_frames(&self, frames: i64) -> Self {
+        Self::from_frame_number(self.frame_number() + frames, self.frame_rate)
+    }
+
+    /// Returns the quarter-frame value (0-15) for the given piece (0-7) of this timecode.
+    pub fn quarter_frame_value(&self, piece: i32) -> i32 {
+        match piece {
+            0 => self.frames & 0x0f,
+            1 => (self.frames >> 4) & 0x01,
+            2 => self.seconds & 0x0f,
+            3 => (self.seconds >> 4) & 0x03,
+            4 => self.minutes & 0x0f,
+            5 => (self.minutes >> 4) & 0x03,
+            6 => self.hours & 0x0f,
+            7 => ((self.hours >> 4) & 0x01) | ((self.frame_rate as i32) << 1),
+            _ => 0,
+        }
+    }
+
+    /// Returns the eight MTC quarter-frame messages that describe this timecode.
+    ///
+    /// The messages span two frames, so they are normally sent for every other frame at a rate of
+    /// four messages per frame.
+    pub fn quarter_frames(&self) -> [MidiMessage; 8] {
+        std::array::from_fn(|piece| {
+            let piece = piece as i32;
+            MidiMessage::quarter_frame(piece, self.quarter_frame_value(piece))
+        })
+    }
+}
+
+/// Reassembles MTC quarter-frame messages into timecodes.
+#[derive(Debug, Default, Clone)]
+pub struct MtcDecoder {
+    values: [i32; 8],
+    received: u8,
+    last_piece: Option<i32>,
+    timecode: Option<SmpteTimecode>,
+}
+
+impl MtcDecoder {
+    /// Creates a decoder that hasn't received any quarter frames yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Processes a message, returning the current timecode if it completes one.
+    ///
+    /// Full-frame messages are applied immediately. A run of eight quarter frames describes the
+    /// timecode of the frame at which the run started, so the decoded timecode is advanced by the
+    /// two frames that have passed since then.
+    pub fn process(&mut self, message: &MidiMessage) -> Option<SmpteTimecode> {
+        if let Some(timecode) = message.get_full_frame() {
+            self.reset();
+            self.timecode = Some(timecode);
+            return self.timecode;
+        }
+
+        if !message.is_quarter_frame() {
+            return None;
+        }
+
+        let piece = message.get_quarter_frame_sequence_number();
+        let in_order = match self.last_piece {
+            Some(last) => piece == (last + 1) % 8,
+            None => piece == 0,
+        };
+
+        if !in_order {
+            self.received = 0;
+            self.last_piece = None;
+            if piece != 0 {
+                return None;
+            }
+        }
+
+        self.values[piece as usize] = message.get_quarter_frame_value();
+        self.received |= 1 << piece;
+        self.last_piece = Some(piece);
+
+        if piece != 7 || self.received != 0xff {
+            return None;
+        }
+
+        self.received = 0;
+        let frame_rate = SmpteFrameRate::from_index((self.values[7] >> 1) & 0x03)?;
+        let timecode = SmpteTimecode {
+            hours: self.values[6] | ((self.values[7] & 0x01) << 4),
+            minutes: self.values[4] | (self.values[5] << 4),
+            seconds: self.values[2] | (self.values[3] << 4),
+            frames: self.values[0] | (self.values[1] << 4),
+            frame_rate,
+        }
+        .offset_by_frames(2);
+
+        self.timecode = Some(timecode);
+        self.timecode
+    }
+
+    /// Returns the most recently decoded timecode.
+    pub fn timecode(&self) -> Option<SmpteTimecode> {
+        self.timecode
+    }
+
+    /// Forgets any partially received quarter frames and the last decoded timecode.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn timecode(
+        hours: i32,
+        minutes: i32,
+        seconds: i32,
+        frames: i32,
+        frame_rate: SmpteFrameRate,
+    ) -> SmpteTimecode {
+        SmpteTimecode {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            frame_rate,
+        }
+    }
+
+    #[test]
+    fn converting_between_seconds_and_timecode() {
+        let tc = SmpteTimecode::from_seconds(3723.5, SmpteFrameRate::Fps25);
+        assert_eq!(tc, timecode(1, 2, 3, 12, SmpteFrameRate::Fps25));
+        assert_eq!(tc.to_seconds(), 3723.48);
+    }
+
+    #[test]
+    fn drop_frame_timecode_skips_frame_labels() {
+        let rate = SmpteFrameRate::Fps30Drop;
+
+        let tc = SmpteTimecode::from_frame_number(1800, rate);
+        assert_eq!(tc, timecode(0, 1, 0, 2, rate));
+        assert_eq!(tc.frame_number(), 1800);
+
+        let tc = SmpteTimecode::from_frame_number(17982, rate);
+        assert_eq!(tc, timecode(0, 10, 0, 0, rate));
+        assert_eq!(tc.frame_number(), 17982);
+    }
+
+    #[test]
+    fn full_frame_messages_round_trip() {
+        let tc = timecode(10, 20, 30, 15, SmpteFrameRate::Fps30);
+        let message = MidiMessage::full_frame(&tc);
+
+        assert!(message.is_full_frame());
+        assert_eq!(message.get_full_frame(), Some(tc));
+        assert_eq!(MidiMessage::midi_clock().get_full_frame(), None);
+    }
+
+    #[test]
+    fn quarter_frames_round_trip() {
+        let tc = timecode(23, 59, 58, 22, SmpteFrameRate::Fps24);
+        let mut decoder = MtcDecoder::new();
+
+        let decoded: Vec<_> = tc
+            .quarter_frames()
+            .iter()
+            .filter_map(|message| decoder.process(message))
+            .collect();
+
+        assert_eq!(decoded, [tc.offset_by_frames(2)]);
+    }
+
+    #[test]
+    fn quarter_frames_out_of_order_are_ignored() {
+        let tc = timecode(1, 0, 0, 0, SmpteFrameRate::Fps25);
+        let messages = tc.quarter_frames();
+        let mut decoder = MtcDecoder::new();
+
+        for message in &messages[4..] {
+            assert_eq!(decoder.process(message), None);
+        }
+
+        let decoded: Vec<_> = messages
+            .iter()
+            .filter_map(|message| decoder.process(message))
+            .collect();
+        assert_eq!(decoded.len(), 1);
+    }
+}
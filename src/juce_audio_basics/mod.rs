@@ -1,9 +1,18 @@
 //! Audio buffer manipulation, filtering, synthesis, etc.
 
+mod audio_source;
 mod buffer;
 mod filters;
+mod load_measurer;
 mod midi;
+mod midi_editing;
+mod midi_timecode;
 
+pub use audio_source::AudioSource;
 pub use buffer::AudioSampleBuffer;
 pub use filters::{IIRCoefficients, SingleThreadedIIRFilter};
-pub use midi::{MidiBuffer, MidiFile, MidiMessage};
+pub use load_measurer::AudioProcessLoadMeasurer;
+pub use midi::{MidiBuffer, MidiFile, MidiMessage, MidiMessageSequence};
+pub use midi_timecode::{MtcDecoder, SmpteFrameRate, SmpteTimecode};
+
+pub(crate) use audio_source::{wrap_audio_source, JuceAudioSource};
@@ -0,0 +1,261 @@
+use crate::{
+    juce_audio_basics::AudioSampleBuffer,
+    juce_audio_devices::{AudioDeviceCallback, AudioDeviceCallbackContext, AudioIODevice},
+};
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Ten buckets up to the buffer period, then buckets for overruns of up to 50% and 100%, and
+/// one for anything longer.
+const BUCKET_LIMITS: [f64; 12] = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.5, 2.0];
+const NUM_BUCKETS: usize = BUCKET_LIMITS.len() + 1;
+const FIRST_OVERRUN_BUCKET: usize = 10;
+
+/// The number of callbacks whose duration fell within a range of loads.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimingBucket {
+    /// The load that the bucket starts above, as a proportion of the buffer period.
+    pub min_load: f64,
+    /// The largest load in the bucket, as a proportion of the buffer period.
+    pub max_load: f64,
+    /// The number of callbacks in the bucket.
+    pub count: u64,
+}
+
+struct State {
+    buckets: [AtomicU64; NUM_BUCKETS],
+    max_load: AtomicU64,
+}
+
+/// A histogram of how long audio callbacks take, relative to the period of the buffer they
+/// process.
+///
+/// A load of 1.0 means a callback took the whole buffer period, and anything above that is an
+/// overrun that will have caused a dropout. Recording is lock-free, so it is safe on the audio
+/// thread, and clones of the histogram share the same counts so they can be read from other
+/// threads.
+#[derive(Clone)]
+pub struct CallbackTimingHistogram {
+    state: Arc<State>,
+}
+
+impl Default for CallbackTimingHistogram {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(State {
+                buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+                max_load: AtomicU64::new(0.0_f64.to_bits()),
+            }),
+        }
+    }
+}
+
+impl CallbackTimingHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a callback that took `duration` to process a buffer lasting `period`.
+    pub fn record(&self, duration: Duration, period: Duration) {
+        if period.is_zero() {
+            return;
+        }
+
+        let load = duration.as_secs_f64() / period.as_secs_f64();
+        let index = BUCKET_LIMITS
+            .iter()
+            .position(|&limit| load <= limit)
+            .unwrap_or(BUCKET_LIMITS.len());
+
+        self.state.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.state
+            .max_load
+            .fetch_max(load.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the counts for each range of loads, in increasing order.
+    pub fn buckets(&self) -> Vec<TimingBucket> {
+        self.state
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(index, count)| TimingBucket {
+                min_load: index
+                    .checked_sub(1)
+                    .map_or(0.0, |previous| BUCKET_LIMITS[previous]),
+                max_load: BUCKET_LIMITS.get(index).copied().unwrap_or(f64::INFINITY),
+                count: count.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Returns the number of callbacks recorded.
+    pub fn total(&self) -> u64 {
+        self.state
+            .buckets
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Returns the number of callbacks that took longer than the buffer period.
+    pub fn overruns(&self) -> u64 {
+        self.state.buckets[FIRST_OVERRUN_BUCKET..]
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Returns the highest load recorded.
+    pub fn max_load(&self) -> f64 {
+        // Loads are never negative, so their bit patterns order the same way as their values.
+        f64::from_bits(self.state.max_load.load(Ordering::Relaxed))
+    }
+
+    /// Clears all of the counts.
+    pub fn reset(&self) {
+        for count in &self.state.buckets {
+            count.store(0, Ordering::Relaxed);
+        }
+
+        self.state
+            .max_load
+            .store(0.0_f64.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Wraps an [`AudioDeviceCallback`] so that each of its blocks is recorded in this
+    /// histogram.
+    pub fn wrap<Callback>(&self, callback: Callback) -> TimedAudioDeviceCallback<Callback>
+    where
+        Callback: AudioDeviceCallback,
+    {
+        TimedAudioDeviceCallback {
+            callback,
+            histogram: self.clone(),
+            sample_rate: 0.0,
+        }
+    }
+}
+
+/// An [`AudioDeviceCallback`] that records how long another callback takes to process each
+/// block in a [`CallbackTimingHistogram`].
+///
+/// Created with [`CallbackTimingHistogram::wrap`].
+pub struct TimedAudioDeviceCallback<Callback> {
+    callback: Callback,
+    histogram: CallbackTimingHistogram,
+    sample_rate: f64,
+}
+
+impl<Callback> TimedAudioDeviceCallback<Callback> {
+    /// Returns the histogram the timings are recorded in.
+    pub fn histogram(&self) -> &CallbackTimingHistogram {
+        &self.histogram
+    }
+}
+
+impl<Callback> AudioDeviceCallback for TimedAudioDeviceCallback<Callback>
+where
+    Callback: AudioDeviceCallback,
+{
+    fn about_to_start(&mut self, mut device: Pin<&mut AudioIODevice>) {
+        self.sample_rate = device.as_mut().get_current_sample_rate();
+        self.callback.about_to_start(device);
+    }
+
+    fn process_block(&mut self, input: &AudioSampleBuffer, output: Pin<&mut AudioSampleBuffer>) {
+        self.process_block_with_context(input, output, &AudioDeviceCallbackContext::default());
+    }
+
+    fn process_block_with_context(
+        &mut self,
+        input: &AudioSampleBuffer,
+        output: Pin<&mut AudioSampleBuffer>,
+        context: &AudioDeviceCallbackContext,
+    ) {
+        let num_samples = output.get_num_samples();
+
+        let start = Instant::now();
+        self.callback
+            .process_block_with_context(input, output, context);
+        let duration = start.elapsed();
+
+        if self.sample_rate > 0.0 {
+            let period = Duration::from_secs_f64(f64::from(num_samples) / self.sample_rate);
+            self.histogram.record(duration, period);
+        }
+    }
+
+    fn stopped(&mut self) {
+        self.callback.stopped();
+    }
+
+    fn device_error(&mut self, message: &str) {
+        self.callback.device_error(message);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn records_loads_in_buckets() {
+        let histogram = CallbackTimingHistogram::new();
+        let period = Duration::from_millis(10);
+
+        histogram.record(Duration::from_millis(1), period);
+        histogram.record(Duration::from_micros(4500), period);
+        histogram.record(Duration::from_millis(10), period);
+        histogram.record(Duration::from_millis(12), period);
+        histogram.record(Duration::from_millis(20), period);
+
+        let counts: Vec<_> = histogram
+            .buckets()
+            .iter()
+            .map(|bucket| bucket.count)
+            .collect();
+
+        assert_eq!(counts, [1, 0, 0, 0, 1, 0, 0, 0, 0, 1, 1, 1, 0]);
+        assert_eq!(histogram.total(), 5);
+        assert_eq!(histogram.overruns(), 2);
+        assert_eq!(histogram.max_load(), 2.0);
+    }
+
+    #[test]
+    fn buckets_cover_every_load() {
+        let buckets = CallbackTimingHistogram::new().buckets();
+
+        assert_eq!(buckets.first().unwrap().min_load, 0.0);
+        assert_eq!(buckets.last().unwrap().max_load, f64::INFINITY);
+        assert!(buckets
+            .windows(2)
+            .all(|pair| pair[0].max_load == pair[1].min_load));
+    }
+
+    #[test]
+    fn reset_clears_the_counts() {
+        let histogram = CallbackTimingHistogram::new();
+        histogram.record(Duration::from_millis(20), Duration::from_millis(10));
+
+        histogram.reset();
+
+        assert_eq!(histogram.total(), 0);
+        assert_eq!(histogram.max_load(), 0.0);
+    }
+
+    #[test]
+    fn zero_periods_are_ignored() {
+        let histogram = CallbackTimingHistogram::new();
+        histogram.record(Duration::from_millis(1), Duration::ZERO);
+
+        assert_eq!(histogram.total(), 0);
+    }
+}
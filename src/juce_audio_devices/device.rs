@@ -1,11 +1,82 @@
 use crate::{
     define_trait,
+    juce_audio_basics::AudioSampleBuffer,
+    juce_audio_devices::{AudioDeviceCallback, AudioDeviceCallbackContext, AudioIODeviceCallback},
     juce_core::{DoubleArray, IntArray, JuceString, StringArray},
 };
 use cxx::UniquePtr;
+use std::pin::Pin;
 
 pub use juce::{AudioIODevice, BoxDynAudioDevice};
 
+impl AudioIODevice {
+    /// Starts an open device, which then calls `callback` for each block of audio until it is
+    /// stopped.
+    ///
+    /// This allows devices created with
+    /// [`AudioIODeviceType::create_device`](crate::juce_audio_devices::AudioIODeviceType::create_device)
+    /// to be used without an
+    /// [`AudioDeviceManager`](crate::juce_audio_devices::AudioDeviceManager). The returned
+    /// [`StartedAudioDevice`] owns both the device and the callback, and stops the device when
+    /// it is dropped.
+    pub fn start(
+        mut device: UniquePtr<Self>,
+        callback: impl AudioDeviceCallback + 'static,
+    ) -> StartedAudioDevice {
+        let callback: Box<dyn AudioDeviceCallback> = Box::new(callback);
+        let callback: UniquePtr<AudioIODeviceCallback> = callback.into();
+
+        if let Some(device) = device.as_mut() {
+            unsafe { juce::start_audio_io_device(device, callback.as_mut_ptr()) };
+        }
+
+        StartedAudioDevice {
+            device,
+            _callback: callback,
+        }
+    }
+}
+
+/// An audio device that has been started with an owned callback.
+pub struct StartedAudioDevice {
+    device: UniquePtr<AudioIODevice>,
+    _callback: UniquePtr<AudioIODeviceCallback>,
+}
+
+impl StartedAudioDevice {
+    /// Stops the device and returns it, so it can be started again or closed.
+    pub fn stop(mut self) -> UniquePtr<AudioIODevice> {
+        let mut device = std::mem::replace(&mut self.device, UniquePtr::null());
+
+        if let Some(device) = device.as_mut() {
+            device.stop();
+        }
+
+        device
+    }
+}
+
+impl std::ops::Deref for StartedAudioDevice {
+    type Target = UniquePtr<AudioIODevice>;
+    fn deref(&self) -> &Self::Target {
+        &self.device
+    }
+}
+
+impl std::ops::DerefMut for StartedAudioDevice {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.device
+    }
+}
+
+impl Drop for StartedAudioDevice {
+    fn drop(&mut self) {
+        if let Some(device) = self.device.as_mut() {
+            device.stop();
+        }
+    }
+}
+
 #[cxx::bridge(namespace = "juce")]
 mod juce {
     unsafe extern "C++" {
@@ -18,6 +89,29 @@ mod juce {
         type DoubleArray = crate::juce_core::DoubleArray;
         type JuceString = crate::juce_core::JuceString;
         type StringArray = crate::juce_core::StringArray;
+        type AudioIODeviceCallback = crate::juce_audio_devices::AudioIODeviceCallback;
+        type AudioSampleBuffer = crate::juce_audio_basics::AudioSampleBuffer;
+
+        #[namespace = "cxx_juce"]
+        type AudioDeviceCallbackHandle;
+
+        #[cxx_name = "aboutToStart"]
+        fn about_to_start(self: Pin<&mut AudioDeviceCallbackHandle>);
+
+        #[cxx_name = "processBlock"]
+        fn process_block(
+            self: Pin<&mut AudioDeviceCallbackHandle>,
+            input: &AudioSampleBuffer,
+            output: Pin<&mut AudioSampleBuffer>,
+            has_host_time: bool,
+            host_time_ns: u64,
+        );
+
+        #[cxx_name = "stopped"]
+        fn stopped(self: Pin<&mut AudioDeviceCallbackHandle>);
+
+        #[cxx_name = "error"]
+        fn error(self: Pin<&mut AudioDeviceCallbackHandle>, message: &JuceString);
 
         #[namespace = "cxx_juce"]
         /// A boxed [`AudioDevice`] trait object.
@@ -71,6 +165,75 @@ mod juce {
 
         /// Closes the device.
         pub fn close(self: Pin<&mut AudioIODevice>);
+
+        /// Returns `true` if the device is open.
+        #[cxx_name = "isOpen"]
+        pub fn is_open(self: Pin<&mut AudioIODevice>) -> bool;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "startAudioIODevice"]
+        unsafe fn start_audio_io_device(
+            device: Pin<&mut AudioIODevice>,
+            callback: *mut AudioIODeviceCallback,
+        );
+
+        /// Stops the device, after which the callback passed to [`AudioIODevice::start`]
+        /// receives no more blocks.
+        pub fn stop(self: Pin<&mut AudioIODevice>);
+
+        /// Returns `true` if the device is running a callback.
+        #[cxx_name = "isPlaying"]
+        pub fn is_playing(self: Pin<&mut AudioIODevice>) -> bool;
+
+        /// Returns the last error that occurred, or an empty string if there wasn't one.
+        #[cxx_name = "getLastError"]
+        pub fn get_last_error(self: Pin<&mut AudioIODevice>) -> JuceString;
+
+        /// Returns the names of the output channels.
+        #[cxx_name = "getOutputChannelNames"]
+        pub fn get_output_channel_names(self: Pin<&mut AudioIODevice>) -> StringArray;
+
+        /// Returns the names of the input channels.
+        #[cxx_name = "getInputChannelNames"]
+        pub fn get_input_channel_names(self: Pin<&mut AudioIODevice>) -> StringArray;
+
+        /// Returns the buffer size that the device would use by default.
+        #[cxx_name = "getDefaultBufferSize"]
+        pub fn get_default_buffer_size(self: Pin<&mut AudioIODevice>) -> i32;
+
+        /// Returns the bit depth of the samples the device is using.
+        #[cxx_name = "getCurrentBitDepth"]
+        pub fn get_current_bit_depth(self: Pin<&mut AudioIODevice>) -> i32;
+
+        /// Returns the output latency in samples.
+        #[cxx_name = "getOutputLatencyInSamples"]
+        pub fn get_output_latency_in_samples(self: Pin<&mut AudioIODevice>) -> i32;
+
+        /// Returns the input latency in samples.
+        #[cxx_name = "getInputLatencyInSamples"]
+        pub fn get_input_latency_in_samples(self: Pin<&mut AudioIODevice>) -> i32;
+
+        /// Returns the number of buffer underruns and overruns since the device was opened, or
+        /// -1 if the device can't report them.
+        #[cxx_name = "getXRunCount"]
+        pub fn get_xrun_count(self: &AudioIODevice) -> i32;
+
+        /// Returns `true` if the device has a control panel.
+        #[cxx_name = "hasControlPanel"]
+        pub fn has_control_panel(self: &AudioIODevice) -> bool;
+
+        /// Shows the device's control panel, returning `true` if the settings may have
+        /// changed.
+        #[cxx_name = "showControlPanel"]
+        pub fn show_control_panel(self: Pin<&mut AudioIODevice>) -> bool;
+
+        /// Enables or disables the operating system's audio preprocessing, returning `true` if
+        /// the setting could be changed.
+        #[cxx_name = "setAudioPreprocessingEnabled"]
+        pub fn set_audio_preprocessing_enabled(
+            self: Pin<&mut AudioIODevice>,
+            should_be_enabled: bool,
+        ) -> bool;
     }
 
     #[namespace = "cxx_juce"]
@@ -150,7 +313,7 @@ mod juce {
         fn xrun_count(device: &BoxDynAudioDevice) -> i32;
 
         #[Self = "AudioDeviceImpl"]
-        fn start(device: &mut BoxDynAudioDevice);
+        fn start(device: &mut BoxDynAudioDevice, callback: UniquePtr<AudioDeviceCallbackHandle>);
 
         #[Self = "AudioDeviceImpl"]
         fn stop(device: &mut BoxDynAudioDevice);
@@ -233,12 +396,85 @@ define_trait! {
     fn xrun_count(&self) -> i32;
 
     /// Starts the device.
-    fn start(&mut self);
+    ///
+    /// The device should call [`AudioDeviceCallbackHandle::about_to_start`] before delivering
+    /// any blocks, and then [`AudioDeviceCallbackHandle::process_block`] for each block until it
+    /// is stopped.
+    fn start(&mut self, callback: AudioDeviceCallbackHandle), @nobind;
 
     /// Stops the device.
+    ///
+    /// The device should call [`AudioDeviceCallbackHandle::stopped`] once it has delivered its
+    /// last block. The callback handle stops forwarding calls as soon as this returns.
     fn stop(&mut self);
 }
 
+impl AudioDeviceImpl {
+    fn start(
+        device: &mut Box<dyn AudioDevice>,
+        callback: UniquePtr<juce::AudioDeviceCallbackHandle>,
+    ) {
+        device.start(AudioDeviceCallbackHandle { handle: callback });
+    }
+}
+
+/// The callback that an [`AudioDevice`] drives while it is running.
+///
+/// A handle is passed to [`AudioDevice::start`], and can be moved to whichever thread produces
+/// the audio. Once the device has been stopped or destroyed, calls to the handle do nothing.
+pub struct AudioDeviceCallbackHandle {
+    handle: UniquePtr<juce::AudioDeviceCallbackHandle>,
+}
+
+// The handle forwards calls to the JUCE callback under a lock, and JUCE callbacks are made from
+// the audio thread rather than the thread that started the device.
+unsafe impl Send for AudioDeviceCallbackHandle {}
+
+impl AudioDeviceCallbackHandle {
+    /// Tells the callback that the device is about to start delivering blocks.
+    pub fn about_to_start(&mut self) {
+        self.handle.pin_mut().about_to_start();
+    }
+
+    /// Delivers a block of audio, filling `output` from `input`.
+    ///
+    /// Both buffers should have the same number of samples, and the number of channels
+    /// that the device reports as active. If the device is being stopped at the same time, the
+    /// block is skipped and `output` is cleared rather than waiting.
+    pub fn process_block(
+        &mut self,
+        input: &AudioSampleBuffer,
+        output: Pin<&mut AudioSampleBuffer>,
+    ) {
+        self.process_block_with_context(input, output, &AudioDeviceCallbackContext::default());
+    }
+
+    /// Delivers a block of audio along with its timing information.
+    pub fn process_block_with_context(
+        &mut self,
+        input: &AudioSampleBuffer,
+        output: Pin<&mut AudioSampleBuffer>,
+        context: &AudioDeviceCallbackContext,
+    ) {
+        self.handle.pin_mut().process_block(
+            input,
+            output,
+            context.host_time_ns.is_some(),
+            context.host_time_ns.unwrap_or_default(),
+        );
+    }
+
+    /// Tells the callback that the device has stopped.
+    pub fn stopped(&mut self) {
+        self.handle.pin_mut().stopped();
+    }
+
+    /// Reports an error that stopped the device from working.
+    pub fn error(&mut self, message: &str) {
+        self.handle.pin_mut().error(&JuceString::new(message));
+    }
+}
+
 impl From<Box<dyn AudioDevice>> for UniquePtr<AudioIODevice> {
     fn from(device: Box<dyn AudioDevice>) -> Self {
         juce::wrap_audio_device(device)
@@ -1,5 +1,6 @@
 use crate::{
     define_trait, juce_audio_basics::AudioSampleBuffer, juce_audio_devices::AudioIODevice,
+    juce_core::JuceString,
 };
 use cxx::UniquePtr;
 use std::pin::Pin;
@@ -28,6 +29,34 @@ mod juce {
 
         #[namespace = "juce"]
         type AudioSampleBuffer = crate::juce_audio_basics::AudioSampleBuffer;
+
+        #[namespace = "juce"]
+        type JuceString = crate::juce_core::JuceString;
+
+        #[cxx_name = "audioDeviceCallbackAboutToStart"]
+        fn about_to_start(
+            callback: Pin<&mut AudioIODeviceCallback>,
+            device: Pin<&mut AudioIODevice>,
+        );
+
+        #[cxx_name = "audioDeviceCallbackProcessBlock"]
+        fn process_block(
+            callback: Pin<&mut AudioIODeviceCallback>,
+            input: &AudioSampleBuffer,
+            output: Pin<&mut AudioSampleBuffer>,
+            has_host_time: bool,
+            host_time_ns: u64,
+        );
+
+        /// Tells the callback that the device has stopped.
+        #[namespace = "juce"]
+        #[cxx_name = "audioDeviceStopped"]
+        pub fn stopped(self: Pin<&mut AudioIODeviceCallback>);
+
+        /// Tells the callback that the device has encountered an error.
+        #[namespace = "juce"]
+        #[cxx_name = "audioDeviceError"]
+        pub fn device_error(self: Pin<&mut AudioIODeviceCallback>, message: &JuceString);
     }
 
     #[namespace = "cxx_juce"]
@@ -44,14 +73,19 @@ mod juce {
         );
 
         #[Self = "AudioDeviceCallbackImpl"]
-        fn process_block(
+        fn process_block_with_context(
             callback: &mut BoxDynAudioDeviceCallback,
             input: &AudioSampleBuffer,
             output: Pin<&mut AudioSampleBuffer>,
+            has_host_time: bool,
+            host_time_ns: u64,
         );
 
         #[Self = "AudioDeviceCallbackImpl"]
         fn stopped(callback: &mut BoxDynAudioDeviceCallback);
+
+        #[Self = "AudioDeviceCallbackImpl"]
+        fn error(callback: &mut BoxDynAudioDeviceCallback, message: &JuceString);
     }
 }
 
@@ -69,10 +103,81 @@ define_trait! {
     fn about_to_start(&mut self, device: Pin<&mut AudioIODevice>);
 
     /// Process a block of incoming and outgoing audio.
-    fn process_block(&mut self, input: &AudioSampleBuffer, output: Pin<&mut AudioSampleBuffer>);
+    fn process_block(&mut self, input: &AudioSampleBuffer, output: Pin<&mut AudioSampleBuffer>), @nobind;
+
+    /// Process a block of incoming and outgoing audio, along with the timing information the
+    /// device provided for it.
+    ///
+    /// By default this forwards to [`AudioDeviceCallback::process_block`].
+    fn process_block_with_context(
+        &mut self,
+        input: &AudioSampleBuffer,
+        output: Pin<&mut AudioSampleBuffer>,
+        context: &AudioDeviceCallbackContext,
+    ), @nobind {
+        let _ = context;
+        self.process_block(input, output);
+    }
 
     /// Called when the audio device has stopped.
     fn stopped(&mut self);
+
+    /// Called when the audio device encounters an error, such as being disconnected while
+    /// running.
+    fn device_error(&mut self, message: &str), @nobind {
+        let _ = message;
+    }
+}
+
+impl AudioDeviceCallbackImpl {
+    fn process_block_with_context(
+        callback: &mut Box<dyn AudioDeviceCallback>,
+        input: &AudioSampleBuffer,
+        output: Pin<&mut AudioSampleBuffer>,
+        has_host_time: bool,
+        host_time_ns: u64,
+    ) {
+        let context = AudioDeviceCallbackContext {
+            host_time_ns: has_host_time.then_some(host_time_ns),
+        };
+
+        callback.process_block_with_context(input, output, &context);
+    }
+
+    fn error(callback: &mut Box<dyn AudioDeviceCallback>, message: &JuceString) {
+        callback.device_error(message.as_ref());
+    }
+}
+
+/// Timing information for a block of audio delivered to an [`AudioDeviceCallback`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct AudioDeviceCallbackContext {
+    /// The host time at which the block was captured or will be played, in nanoseconds, if the
+    /// device provides it.
+    pub host_time_ns: Option<u64>,
+}
+
+impl AudioIODeviceCallback {
+    /// Tells the callback that `device` is about to start delivering blocks.
+    pub fn about_to_start(self: Pin<&mut Self>, device: Pin<&mut AudioIODevice>) {
+        juce::about_to_start(self, device);
+    }
+
+    /// Delivers a block of audio to the callback, along with its timing information.
+    pub fn process_block(
+        self: Pin<&mut Self>,
+        input: &AudioSampleBuffer,
+        output: Pin<&mut AudioSampleBuffer>,
+        context: &AudioDeviceCallbackContext,
+    ) {
+        juce::process_block(
+            self,
+            input,
+            output,
+            context.host_time_ns.is_some(),
+            context.host_time_ns.unwrap_or_default(),
+        );
+    }
 }
 
 impl From<Box<dyn AudioDeviceCallback>> for UniquePtr<AudioIODeviceCallback> {
@@ -1,12 +1,15 @@
 use crate::{
-    define_juce_type,
+    define_juce_type, define_trait,
     juce_audio_devices::{
-        AudioDeviceCallback, AudioDeviceType, AudioIODeviceCallback, AudioIODeviceType,
+        device_report::describe_open_device,
+        midi_input::{wrap_callback, JuceMidiInputCallback},
+        AudioDeviceCallback, AudioDeviceType, AudioDeviceTypeReport, AudioIODeviceCallback,
+        AudioIODeviceType, MidiInputCallback, MidiOutput,
     },
-    juce_core::{BigInteger, JuceString},
+    juce_core::{BigInteger, JuceString, StringArray},
     JuceError, JUCE,
 };
-use cxx::UniquePtr;
+use cxx::{SharedPtr, UniquePtr};
 use std::{
     collections::HashMap,
     ffi::{c_double, c_int},
@@ -29,10 +32,55 @@ impl AudioCallbackHandle {
     }
 }
 
+/// A handle to a registered [`AudioDeviceManager`] listener.
+#[must_use]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct AudioDeviceListenerHandle {
+    key: u64,
+}
+
+impl AudioDeviceListenerHandle {
+    fn get() -> AudioDeviceListenerHandle {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let next = NEXT.fetch_add(1, Ordering::Relaxed);
+        AudioDeviceListenerHandle { key: next }
+    }
+}
+
+pub use juce::LevelMeter;
+
+// The level is stored atomically by the audio thread, so it can be read from any thread.
+unsafe impl Send for LevelMeter {}
+unsafe impl Sync for LevelMeter {}
+
+/// A handle to a MIDI input callback registered with an [`AudioDeviceManager`].
+#[must_use]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct MidiInputCallbackHandle {
+    key: u64,
+}
+
+impl MidiInputCallbackHandle {
+    fn get() -> MidiInputCallbackHandle {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let next = NEXT.fetch_add(1, Ordering::Relaxed);
+        MidiInputCallbackHandle { key: next }
+    }
+}
+
+struct RegisteredMidiInputCallback {
+    identifier: JuceString,
+    callback: UniquePtr<JuceMidiInputCallback>,
+}
+
 /// Manages the state of an audio device.
 pub struct AudioDeviceManager {
     device_manager: UniquePtr<juce::AudioDeviceManager>,
     callbacks: HashMap<AudioCallbackHandle, UniquePtr<AudioIODeviceCallback>>,
+    setup_listeners: HashMap<AudioDeviceListenerHandle, UniquePtr<juce::ChangeListener>>,
+    device_list_listeners:
+        HashMap<AudioDeviceListenerHandle, UniquePtr<juce::AudioDeviceListListener>>,
+    midi_input_callbacks: HashMap<MidiInputCallbackHandle, RegisteredMidiInputCallback>,
 }
 
 impl AudioDeviceManager {
@@ -41,6 +89,9 @@ impl AudioDeviceManager {
         Self {
             device_manager: juce::make_audio_device_manager(),
             callbacks: HashMap::default(),
+            setup_listeners: HashMap::default(),
+            device_list_listeners: HashMap::default(),
+            midi_input_callbacks: HashMap::default(),
         }
     }
 
@@ -50,14 +101,65 @@ impl AudioDeviceManager {
         input_channels: i32,
         output_channels: i32,
     ) -> Result<(), JuceError> {
+        self.initialise_with(&AudioDeviceManagerOptions::new(
+            input_channels,
+            output_channels,
+        ))
+    }
+
+    /// Like [`AudioDeviceManager::initialise`], but restores the device, sample rate, buffer
+    /// size, channels and enabled MIDI inputs from a string returned by
+    /// [`AudioDeviceManager::create_state_xml`].
+    pub fn initialise_with_state(
+        &mut self,
+        input_channels: i32,
+        output_channels: i32,
+        saved_state: &str,
+    ) -> Result<(), JuceError> {
+        self.initialise_with(
+            &AudioDeviceManagerOptions::new(input_channels, output_channels)
+                .with_saved_state(saved_state),
+        )
+    }
+
+    /// Opens a device chosen according to the given options.
+    ///
+    /// Returns an error if the preferred device type isn't available.
+    pub fn initialise_with(
+        &mut self,
+        options: &AudioDeviceManagerOptions,
+    ) -> Result<(), JuceError> {
+        if let Some(device_type) = &options.preferred_device_type {
+            // Listing the types creates the built-in ones, which JUCE otherwise only does once
+            // it is initialised.
+            let is_available = self
+                .device_types()
+                .iter()
+                .any(|available| available.get_type_name() == device_type.as_str());
+
+            if !is_available {
+                return Err(JuceError(JuceString::new(format!(
+                    "There is no audio device type called \"{device_type}\""
+                ))));
+            }
+
+            self.set_current_audio_device_type(device_type, false);
+        }
+
+        let preferred_setup = options
+            .preferred_setup
+            .as_ref()
+            .map_or(std::ptr::null(), |setup| setup as *const AudioDeviceSetup);
+
         let result = unsafe {
-            self.device_manager.pin_mut().initialise(
-                input_channels,
-                output_channels,
-                std::ptr::null(),
-                false,
-                &JuceString::default(),
-                std::ptr::null(),
+            juce::initialise_audio_device_manager(
+                self.device_manager.pin_mut(),
+                options.input_channels,
+                options.output_channels,
+                &JuceString::new(options.saved_state.as_deref().unwrap_or_default()),
+                options.fall_back_to_default_device,
+                &JuceString::new(options.preferred_device_name.as_deref().unwrap_or_default()),
+                preferred_setup,
             )
         };
 
@@ -68,6 +170,15 @@ impl AudioDeviceManager {
         }
     }
 
+    /// Returns the settings that have been explicitly chosen, serialised as XML so they can be
+    /// saved and passed to [`AudioDeviceManager::initialise_with_state`] on the next launch.
+    ///
+    /// Returns [`None`] if no settings have been chosen yet.
+    pub fn create_state_xml(&self) -> Option<String> {
+        let state = juce::create_audio_device_manager_state_xml(&self.device_manager);
+        (!state.is_empty()).then(|| state.as_ref().to_owned())
+    }
+
     /// Get the current device setup.
     pub fn audio_device_setup(&self) -> AudioDeviceSetup {
         self.device_manager.get_audio_device_setup()
@@ -91,6 +202,34 @@ impl AudioDeviceManager {
         }
     }
 
+    /// Returns the average proportion of the available time spent in the audio callbacks, where
+    /// 1.0 means all of it.
+    pub fn cpu_usage(&self) -> f64 {
+        self.device_manager.get_cpu_usage()
+    }
+
+    /// Returns the number of dropouts since the current device was started, as reported by the
+    /// device or, failing that, measured from the callback timings.
+    pub fn xrun_count(&self) -> i32 {
+        self.device_manager.get_xrun_count()
+    }
+
+    /// Returns a meter for the level of the input of the current device.
+    ///
+    /// The manager measures the decaying RMS level of the input on the audio thread while any
+    /// meter is held, so meters should be dropped once they are no longer displayed.
+    pub fn input_level_meter(&mut self) -> SharedPtr<LevelMeter> {
+        juce::get_input_level_meter(self.device_manager.pin_mut())
+    }
+
+    /// Returns a meter for the level of the output of the current device.
+    ///
+    /// The manager measures the decaying RMS level of the output on the audio thread while any
+    /// meter is held, so meters should be dropped once they are no longer displayed.
+    pub fn output_level_meter(&mut self) -> SharedPtr<LevelMeter> {
+        juce::get_output_level_meter(self.device_manager.pin_mut())
+    }
+
     /// Play a test sound.
     pub fn play_test_sound(&mut self) {
         self.device_manager.pin_mut().play_test_sound();
@@ -114,6 +253,49 @@ impl AudioDeviceManager {
             .collect()
     }
 
+    /// Describes the capabilities of every device of every available device type.
+    ///
+    /// Each device type scans for devices first. Devices are opened briefly to find their
+    /// latency and bit depth, except for the current device, which is described as it is.
+    /// While a device is open, the other devices of its type aren't opened, as many drivers
+    /// can't run two of their devices at once, so their latency and bit depth are [`None`].
+    pub fn probe_devices(&mut self) -> Vec<AudioDeviceTypeReport> {
+        let current_device = self.device_manager.get_current_audio_device();
+        let current_type_name =
+            unsafe { current_device.as_ref() }.map(|device| device.get_type_name().to_string());
+
+        self.device_types()
+            .into_iter()
+            .map(|mut device_type| {
+                device_type.as_mut().scan_for_devices();
+                let name = device_type.get_type_name().to_string();
+
+                let devices = device_type
+                    .device_names()
+                    .into_iter()
+                    .filter_map(|device_name| {
+                        let current_device = unsafe { current_device.as_mut() }.filter(|device| {
+                            device.get_type_name() == name.as_str()
+                                && device.get_name() == device_name.as_str()
+                        });
+
+                        match current_device {
+                            Some(device) => {
+                                Some(describe_open_device(unsafe { Pin::new_unchecked(device) }))
+                            }
+                            None if current_type_name.as_ref() == Some(&name) => device_type
+                                .as_mut()
+                                .describe_device_without_opening(&device_name),
+                            None => device_type.as_mut().describe_device(&device_name),
+                        }
+                    })
+                    .collect();
+
+                AudioDeviceTypeReport { name, devices }
+            })
+            .collect()
+    }
+
     /// Get the current device type.
     pub fn current_device_type(&mut self) -> Option<Pin<&mut AudioIODeviceType>> {
         let device_type = self.device_manager.get_current_device_type_object();
@@ -157,6 +339,162 @@ impl AudioDeviceManager {
         }
     }
 
+    /// Registers a listener that is called with the previous and current setup whenever the
+    /// device changes, is restarted or disappears, or the list of available devices changes.
+    ///
+    /// Listeners are called on the message thread, either from the JUCE message loop or from
+    /// [`AudioDeviceManager::dispatch_pending_messages`]. The setups may be the same if the
+    /// device was restarted with the same settings.
+    pub fn add_change_listener(
+        &mut self,
+        listener: impl FnMut(&AudioDeviceSetup, &AudioDeviceSetup) + Send + 'static,
+    ) -> AudioDeviceListenerHandle {
+        struct Wrapper<Listener>(Listener);
+
+        impl<Listener> AudioDeviceSetupCallback for Wrapper<Listener>
+        where
+            Listener: FnMut(&AudioDeviceSetup, &AudioDeviceSetup) + Send,
+        {
+            fn call(&mut self, previous: &AudioDeviceSetup, current: &AudioDeviceSetup) {
+                (self.0)(previous, current);
+            }
+        }
+
+        let listener = juce::make_audio_device_setup_listener(
+            self.device_manager.pin_mut(),
+            Box::new(Wrapper(listener)),
+        );
+
+        let handle = AudioDeviceListenerHandle::get();
+        self.setup_listeners.insert(handle, listener);
+        handle
+    }
+
+    /// Removes a listener registered with [`AudioDeviceManager::add_change_listener`].
+    pub fn remove_change_listener(&mut self, handle: AudioDeviceListenerHandle) {
+        self.setup_listeners.remove(&handle);
+    }
+
+    /// Registers a listener that is called whenever a registered device type reports that
+    /// devices have been added or removed, so device pickers can be refreshed.
+    ///
+    /// Listeners are called on the message thread.
+    pub fn add_device_list_listener(
+        &mut self,
+        listener: impl FnMut() + Send + 'static,
+    ) -> AudioDeviceListenerHandle {
+        struct Wrapper<Listener>(Listener);
+
+        impl<Listener> AudioDeviceListCallback for Wrapper<Listener>
+        where
+            Listener: FnMut() + Send,
+        {
+            fn call(&mut self) {
+                (self.0)();
+            }
+        }
+
+        let listener = juce::make_audio_device_list_listener(
+            self.device_manager.pin_mut(),
+            Box::new(Wrapper(listener)),
+        );
+
+        let handle = AudioDeviceListenerHandle::get();
+        self.device_list_listeners.insert(handle, listener);
+        handle
+    }
+
+    /// Removes a listener registered with [`AudioDeviceManager::add_device_list_listener`].
+    pub fn remove_device_list_listener(&mut self, handle: AudioDeviceListenerHandle) {
+        self.device_list_listeners.remove(&handle);
+    }
+
+    /// Calls any change listeners that are waiting for the message loop.
+    pub fn dispatch_pending_messages(&mut self) {
+        self.device_manager.pin_mut().dispatch_pending_messages();
+    }
+
+    /// Opens or closes the MIDI input with the given [`MidiDeviceInfo::identifier`].
+    ///
+    /// Messages from enabled inputs are delivered to the callbacks registered with
+    /// [`AudioDeviceManager::add_midi_input_device_callback`], and the set of enabled inputs is
+    /// saved by [`AudioDeviceManager::create_state_xml`].
+    ///
+    /// [`MidiDeviceInfo::identifier`]: crate::juce_audio_devices::MidiDeviceInfo::identifier
+    pub fn set_midi_input_device_enabled(&mut self, identifier: &JuceString, enabled: bool) {
+        self.device_manager
+            .pin_mut()
+            .set_midi_input_device_enabled(identifier, enabled);
+    }
+
+    /// Returns `true` if the MIDI input with the given identifier is enabled and open.
+    pub fn is_midi_input_device_enabled(&self, identifier: &JuceString) -> bool {
+        self.device_manager.is_midi_input_device_enabled(identifier)
+    }
+
+    /// Registers a callback for messages from the enabled MIDI input with the given identifier,
+    /// or from all enabled MIDI inputs if the identifier is empty.
+    pub fn add_midi_input_device_callback(
+        &mut self,
+        identifier: &JuceString,
+        callback: impl MidiInputCallback + 'static,
+    ) -> MidiInputCallbackHandle {
+        let callback = wrap_callback(callback);
+
+        unsafe {
+            self.device_manager
+                .pin_mut()
+                .add_midi_input_device_callback(identifier, callback.as_mut_ptr());
+        }
+
+        let handle = MidiInputCallbackHandle::get();
+        self.midi_input_callbacks.insert(
+            handle,
+            RegisteredMidiInputCallback {
+                identifier: identifier.clone(),
+                callback,
+            },
+        );
+        handle
+    }
+
+    /// Removes a MIDI input callback.
+    pub fn remove_midi_input_device_callback(&mut self, handle: MidiInputCallbackHandle) {
+        if let Some(registered) = self.midi_input_callbacks.remove(&handle) {
+            unsafe {
+                self.device_manager
+                    .pin_mut()
+                    .remove_midi_input_device_callback(
+                        &registered.identifier,
+                        registered.callback.as_mut_ptr(),
+                    );
+            }
+        }
+    }
+
+    /// Opens the MIDI output with the given identifier as the default output, closing the
+    /// previous one. An empty identifier closes the default output.
+    ///
+    /// The default output is saved by [`AudioDeviceManager::create_state_xml`].
+    pub fn set_default_midi_output_device(&mut self, identifier: &JuceString) {
+        self.device_manager
+            .pin_mut()
+            .set_default_midi_output_device(identifier);
+    }
+
+    /// Returns the identifier of the default MIDI output.
+    pub fn default_midi_output_identifier(&self) -> JuceString {
+        self.device_manager
+            .get_default_midi_output_identifier()
+            .clone()
+    }
+
+    /// Returns the default MIDI output, if one is open.
+    pub fn default_midi_output(&mut self) -> Option<Pin<&mut MidiOutput>> {
+        let output = self.device_manager.get_default_midi_output();
+        unsafe { output.as_mut().map(|output| Pin::new_unchecked(output)) }
+    }
+
     /// Registers an audio device type.
     pub fn add_audio_device_type(&mut self, device_type: impl AudioDeviceType + 'static) {
         let device_type: Box<dyn AudioDeviceType> = Box::new(device_type);
@@ -164,6 +502,12 @@ impl AudioDeviceManager {
         self.device_manager
             .pin_mut()
             .add_audio_device_type(device_type.into());
+
+        for listener in self.device_list_listeners.values_mut() {
+            listener
+                .pin_mut()
+                .attach_to_device_types(self.device_manager.pin_mut());
+        }
     }
 
     /// Set the current audio device type to use.
@@ -178,6 +522,80 @@ impl AudioDeviceManager {
     }
 }
 
+/// Options for [`AudioDeviceManager::initialise_with`].
+///
+/// If there is a saved state it is restored first. Otherwise the preferred setup is used if
+/// there is one, and failing that the first device matching the preferred device name.
+pub struct AudioDeviceManagerOptions {
+    input_channels: i32,
+    output_channels: i32,
+    saved_state: Option<String>,
+    preferred_device_type: Option<String>,
+    preferred_device_name: Option<String>,
+    preferred_setup: Option<AudioDeviceSetup>,
+    fall_back_to_default_device: bool,
+}
+
+impl AudioDeviceManagerOptions {
+    /// Creates options that open the default device with the given number of channels.
+    pub fn new(input_channels: i32, output_channels: i32) -> Self {
+        Self {
+            input_channels,
+            output_channels,
+            saved_state: None,
+            preferred_device_type: None,
+            preferred_device_name: None,
+            preferred_setup: None,
+            fall_back_to_default_device: false,
+        }
+    }
+
+    /// Returns `self` with a state returned by [`AudioDeviceManager::create_state_xml`] to
+    /// restore.
+    pub fn with_saved_state(mut self, saved_state: impl Into<String>) -> Self {
+        self.saved_state = Some(saved_state.into());
+        self
+    }
+
+    /// Returns `self` with the device type to choose devices from.
+    pub fn with_preferred_device_type(mut self, device_type: impl Into<String>) -> Self {
+        self.preferred_device_type = Some(device_type.into());
+        self
+    }
+
+    /// Returns `self` with the name of the device to open if it is present.
+    ///
+    /// The name may contain `*` and `?` wildcards, and is matched case-insensitively. If no
+    /// device matches, the default device is opened.
+    pub fn with_preferred_device_name(mut self, device_name: impl Into<String>) -> Self {
+        self.preferred_device_name = Some(device_name.into());
+        self
+    }
+
+    /// Returns `self` with the setup to open, in place of the preferred device name.
+    pub fn with_preferred_setup(mut self, setup: AudioDeviceSetup) -> Self {
+        self.preferred_setup = Some(setup);
+        self
+    }
+
+    /// Returns `self` with whether to open the default device if the device in the saved state
+    /// is missing, rather than failing.
+    ///
+    /// This only applies to the saved state. A missing preferred device name always falls back
+    /// to the default device.
+    pub fn with_fall_back_to_default_device(mut self, fall_back: bool) -> Self {
+        self.fall_back_to_default_device = fall_back;
+        self
+    }
+}
+
+impl Drop for AudioDeviceManager {
+    fn drop(&mut self) {
+        self.setup_listeners.clear();
+        self.device_list_listeners.clear();
+    }
+}
+
 define_juce_type! {
     /// The properties of an audio device.
     AudioDeviceSetup,
@@ -284,6 +702,137 @@ impl AudioDeviceSetup {
 
         self
     }
+
+    /// Returns the indices of the selected input channels.
+    ///
+    /// Returns an empty list if the device's default input channels are used.
+    pub fn input_channel_indices(&self) -> Vec<i32> {
+        if self.use_default_input_channels {
+            Vec::new()
+        } else {
+            self.input_channels.set_bits().collect()
+        }
+    }
+
+    /// Returns `self` with exactly the input channels at `indices` selected.
+    ///
+    /// `channel_names` are the device's input channel names, as returned by
+    /// [`AudioIODevice::get_input_channel_names`](crate::juce_audio_devices::AudioIODevice::get_input_channel_names).
+    /// Returns an error if any of `indices` isn't the index of one of them.
+    pub fn with_input_channel_indices(
+        mut self,
+        channel_names: &StringArray,
+        indices: impl IntoIterator<Item = i32>,
+    ) -> Result<Self, JuceError> {
+        select_channels(&mut self.input_channels, channel_names, indices, "input")?;
+        self.use_default_input_channels = false;
+        Ok(self)
+    }
+
+    /// Returns `self` with exactly the input channels called `names` selected.
+    ///
+    /// `channel_names` are the device's input channel names, as returned by
+    /// [`AudioIODevice::get_input_channel_names`](crate::juce_audio_devices::AudioIODevice::get_input_channel_names).
+    /// Returns an error if any of `names` isn't one of them.
+    pub fn with_input_channel_names(
+        self,
+        channel_names: &StringArray,
+        names: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, JuceError> {
+        let indices = channel_indices(channel_names, names, "input")?;
+        self.with_input_channel_indices(channel_names, indices)
+    }
+
+    /// Returns the indices of the selected output channels.
+    ///
+    /// Returns an empty list if the device's default output channels are used.
+    pub fn output_channel_indices(&self) -> Vec<i32> {
+        if self.use_default_output_channels {
+            Vec::new()
+        } else {
+            self.output_channels.set_bits().collect()
+        }
+    }
+
+    /// Returns `self` with exactly the output channels at `indices` selected.
+    ///
+    /// `channel_names` are the device's output channel names, as returned by
+    /// [`AudioIODevice::get_output_channel_names`](crate::juce_audio_devices::AudioIODevice::get_output_channel_names).
+    /// Returns an error if any of `indices` isn't the index of one of them.
+    pub fn with_output_channel_indices(
+        mut self,
+        channel_names: &StringArray,
+        indices: impl IntoIterator<Item = i32>,
+    ) -> Result<Self, JuceError> {
+        select_channels(&mut self.output_channels, channel_names, indices, "output")?;
+        self.use_default_output_channels = false;
+        Ok(self)
+    }
+
+    /// Returns `self` with exactly the output channels called `names` selected.
+    ///
+    /// `channel_names` are the device's output channel names, as returned by
+    /// [`AudioIODevice::get_output_channel_names`](crate::juce_audio_devices::AudioIODevice::get_output_channel_names).
+    /// Returns an error if any of `names` isn't one of them.
+    pub fn with_output_channel_names(
+        self,
+        channel_names: &StringArray,
+        names: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, JuceError> {
+        let indices = channel_indices(channel_names, names, "output")?;
+        self.with_output_channel_indices(channel_names, indices)
+    }
+}
+
+fn select_channels(
+    channels: &mut BigInteger,
+    channel_names: &StringArray,
+    indices: impl IntoIterator<Item = i32>,
+    direction: &str,
+) -> Result<(), JuceError> {
+    let num_channels = channel_names.len();
+    let indices = indices
+        .into_iter()
+        .map(|index| {
+            if (0..num_channels).contains(&index) {
+                Ok(index)
+            } else {
+                Err(JuceError(JuceString::new(format!(
+                    "The device has no {direction} channel {index}"
+                ))))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    channels.clear();
+    for index in indices {
+        channels.set_bit(index);
+    }
+
+    Ok(())
+}
+
+fn channel_indices(
+    channel_names: &StringArray,
+    names: impl IntoIterator<Item = impl AsRef<str>>,
+    direction: &str,
+) -> Result<Vec<i32>, JuceError> {
+    names
+        .into_iter()
+        .map(|name| {
+            let name = name.as_ref();
+            channel_names
+                .as_ref()
+                .iter()
+                .position(|channel_name| channel_name == name)
+                .map(|index| index as i32)
+                .ok_or_else(|| {
+                    JuceError(JuceString::new(format!(
+                        "The device has no {direction} channel called \"{name}\""
+                    )))
+                })
+        })
+        .collect()
 }
 
 #[cxx::bridge(namespace = "juce")]
@@ -321,19 +870,23 @@ mod juce {
         #[cxx_name = "makeUnique"]
         pub fn make_audio_device_manager() -> UniquePtr<AudioDeviceManager>;
 
-        type XmlElement;
-
-        #[must_use]
-        unsafe fn initialise(
-            self: Pin<&mut AudioDeviceManager>,
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "initialiseAudioDeviceManager"]
+        unsafe fn initialise_audio_device_manager(
+            device_manager: Pin<&mut AudioDeviceManager>,
             num_input_channels: i32,
             num_output_channels: i32,
-            saved_state: *const XmlElement,
+            saved_state: &JuceString,
             select_default_device_on_failure: bool,
             preferred_default_device_name: &JuceString,
             preferred_setup_options: *const AudioDeviceSetup,
         ) -> JuceString;
 
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "createAudioDeviceManagerStateXml"]
+        fn create_audio_device_manager_state_xml(device_manager: &AudioDeviceManager)
+            -> JuceString;
+
         #[rust_name = "get_audio_device_setup"]
         pub fn getAudioDeviceSetup(self: &AudioDeviceManager) -> AudioDeviceSetup;
 
@@ -356,6 +909,31 @@ mod juce {
         #[rust_name = "get_current_device_type_object"]
         pub fn getCurrentDeviceTypeObject(self: &AudioDeviceManager) -> *mut AudioIODeviceType;
 
+        /// The level of the input or output of an [`AudioDeviceManager`].
+        type LevelMeter;
+
+        /// Returns the current level, between 0.0 and 1.0.
+        #[cxx_name = "getCurrentLevel"]
+        fn get_current_level(self: &LevelMeter) -> f64;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "getInputLevelMeter"]
+        fn get_input_level_meter(
+            device_manager: Pin<&mut AudioDeviceManager>,
+        ) -> SharedPtr<LevelMeter>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "getOutputLevelMeter"]
+        fn get_output_level_meter(
+            device_manager: Pin<&mut AudioDeviceManager>,
+        ) -> SharedPtr<LevelMeter>;
+
+        #[cxx_name = "getCpuUsage"]
+        fn get_cpu_usage(self: &AudioDeviceManager) -> f64;
+
+        #[cxx_name = "getXRunCount"]
+        fn get_xrun_count(self: &AudioDeviceManager) -> i32;
+
         #[rust_name = "play_test_sound"]
         pub fn playTestSound(self: Pin<&mut AudioDeviceManager>);
 
@@ -384,6 +962,81 @@ mod juce {
             treat_as_chosen_device: bool,
         );
 
+        type ChangeListener;
+
+        #[namespace = "cxx_juce"]
+        type BoxDynAudioDeviceSetupCallback = Box<dyn super::AudioDeviceSetupCallback>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "makeAudioDeviceSetupListener"]
+        fn make_audio_device_setup_listener(
+            device_manager: Pin<&mut AudioDeviceManager>,
+            callback: BoxDynAudioDeviceSetupCallback,
+        ) -> UniquePtr<ChangeListener>;
+
+        #[namespace = "cxx_juce"]
+        type AudioDeviceListListener;
+
+        #[namespace = "cxx_juce"]
+        type BoxDynAudioDeviceListCallback = Box<dyn super::AudioDeviceListCallback>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "makeAudioDeviceListListener"]
+        fn make_audio_device_list_listener(
+            device_manager: Pin<&mut AudioDeviceManager>,
+            callback: BoxDynAudioDeviceListCallback,
+        ) -> UniquePtr<AudioDeviceListListener>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "attachToDeviceTypes"]
+        fn attach_to_device_types(
+            self: Pin<&mut AudioDeviceListListener>,
+            device_manager: Pin<&mut AudioDeviceManager>,
+        );
+
+        type MidiInputCallback = crate::juce_audio_devices::midi_input::JuceMidiInputCallback;
+        type MidiOutput = crate::juce_audio_devices::MidiOutput;
+
+        #[cxx_name = "setMidiInputDeviceEnabled"]
+        fn set_midi_input_device_enabled(
+            self: Pin<&mut AudioDeviceManager>,
+            identifier: &JuceString,
+            enabled: bool,
+        );
+
+        #[cxx_name = "isMidiInputDeviceEnabled"]
+        fn is_midi_input_device_enabled(self: &AudioDeviceManager, identifier: &JuceString)
+            -> bool;
+
+        #[cxx_name = "addMidiInputDeviceCallback"]
+        unsafe fn add_midi_input_device_callback(
+            self: Pin<&mut AudioDeviceManager>,
+            identifier: &JuceString,
+            callback: *mut MidiInputCallback,
+        );
+
+        #[cxx_name = "removeMidiInputDeviceCallback"]
+        unsafe fn remove_midi_input_device_callback(
+            self: Pin<&mut AudioDeviceManager>,
+            identifier: &JuceString,
+            callback: *mut MidiInputCallback,
+        );
+
+        #[cxx_name = "setDefaultMidiOutputDevice"]
+        fn set_default_midi_output_device(
+            self: Pin<&mut AudioDeviceManager>,
+            identifier: &JuceString,
+        );
+
+        #[cxx_name = "getDefaultMidiOutputIdentifier"]
+        fn get_default_midi_output_identifier(self: &AudioDeviceManager) -> &JuceString;
+
+        #[cxx_name = "getDefaultMidiOutput"]
+        fn get_default_midi_output(self: &AudioDeviceManager) -> *mut MidiOutput;
+
+        #[cxx_name = "dispatchPendingMessages"]
+        fn dispatch_pending_messages(self: Pin<&mut AudioDeviceManager>);
+
         pub type AudioIODeviceTypeArray;
 
         pub fn size(self: &AudioIODeviceTypeArray) -> i32;
@@ -393,4 +1046,41 @@ mod juce {
 
         type AudioIODevice = crate::juce_audio_devices::AudioIODevice;
     }
+
+    #[namespace = "cxx_juce"]
+    extern "Rust" {
+        type AudioDeviceSetupCallbackImpl;
+
+        #[Self = "AudioDeviceSetupCallbackImpl"]
+        unsafe fn drop(callback: *mut BoxDynAudioDeviceSetupCallback);
+
+        #[Self = "AudioDeviceSetupCallbackImpl"]
+        fn call(
+            callback: &mut BoxDynAudioDeviceSetupCallback,
+            previous: &AudioDeviceSetup,
+            current: &AudioDeviceSetup,
+        );
+
+        type AudioDeviceListCallbackImpl;
+
+        #[Self = "AudioDeviceListCallbackImpl"]
+        unsafe fn drop(callback: *mut BoxDynAudioDeviceListCallback);
+
+        #[Self = "AudioDeviceListCallbackImpl"]
+        fn call(callback: &mut BoxDynAudioDeviceListCallback);
+    }
+}
+
+define_trait! {
+    AudioDeviceSetupCallback: Send,
+    AudioDeviceSetupCallbackImpl,
+    "cxx_juce::BoxDynAudioDeviceSetupCallback",
+    fn call(&mut self, previous: &AudioDeviceSetup, current: &AudioDeviceSetup);
+}
+
+define_trait! {
+    AudioDeviceListCallback: Send,
+    AudioDeviceListCallbackImpl,
+    "cxx_juce::BoxDynAudioDeviceListCallback",
+    fn call(&mut self);
 }
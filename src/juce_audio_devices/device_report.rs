@@ -0,0 +1,181 @@
+use crate::{
+    juce_audio_devices::{AudioIODevice, AudioIODeviceType},
+    juce_core::{BigInteger, StringArray},
+};
+use cxx::UniquePtr;
+use std::pin::Pin;
+
+/// The capabilities of an audio device.
+///
+/// Created with [`AudioIODeviceType::describe_device`] or
+/// [`AudioDeviceManager::probe_devices`](crate::juce_audio_devices::AudioDeviceManager::probe_devices).
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioDeviceReport {
+    /// The name of the device.
+    pub name: String,
+    /// The name of the device's type.
+    pub type_name: String,
+    /// The names of the device's input channels.
+    pub input_channel_names: Vec<String>,
+    /// The names of the device's output channels.
+    pub output_channel_names: Vec<String>,
+    /// The sample rates the device supports.
+    pub sample_rates: Vec<f64>,
+    /// The buffer sizes the device supports, in samples.
+    pub buffer_sizes: Vec<i32>,
+    /// The buffer size the device uses by default, in samples.
+    pub default_buffer_size: i32,
+    /// The input latency in samples, or [`None`] if the device couldn't be opened to find out.
+    pub input_latency: Option<i32>,
+    /// The output latency in samples, or [`None`] if the device couldn't be opened to find out.
+    pub output_latency: Option<i32>,
+    /// The bit depth of the device's samples, or [`None`] if the device couldn't be opened to
+    /// find out.
+    pub bit_depth: Option<i32>,
+    /// The error the device reported when it was opened, if it couldn't be opened.
+    pub open_error: Option<String>,
+}
+
+/// The capabilities of every device of an audio device type.
+///
+/// Created with
+/// [`AudioDeviceManager::probe_devices`](crate::juce_audio_devices::AudioDeviceManager::probe_devices).
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioDeviceTypeReport {
+    /// The name of the device type.
+    pub name: String,
+    /// The devices of this type.
+    pub devices: Vec<AudioDeviceReport>,
+}
+
+impl AudioIODeviceType {
+    /// Describes the capabilities of the device called `name`.
+    ///
+    /// The device is opened with all of its channels at its default settings to find its
+    /// latency and bit depth, and closed again. Returns [`None`] if the type has no device with
+    /// that name, or the device couldn't be created. The type must have scanned for devices
+    /// with [`AudioIODeviceType::scan_for_devices`].
+    pub fn describe_device(
+        self: Pin<&mut Self>,
+        name: impl AsRef<str>,
+    ) -> Option<AudioDeviceReport> {
+        let mut device = self.create_named_device(name.as_ref())?;
+        Some(describe_closed_device(device.pin_mut()))
+    }
+
+    /// Describes the device called `name` without opening it, so its latency and bit depth are
+    /// left unknown.
+    pub(crate) fn describe_device_without_opening(
+        self: Pin<&mut Self>,
+        name: &str,
+    ) -> Option<AudioDeviceReport> {
+        let mut device = self.create_named_device(name)?;
+        Some(describe_channels_and_settings(device.pin_mut()))
+    }
+
+    fn create_named_device(
+        mut self: Pin<&mut Self>,
+        name: &str,
+    ) -> Option<UniquePtr<AudioIODevice>> {
+        let has_device = |names: StringArray| names.as_ref().iter().any(|device| device == name);
+        let input = has_device(self.get_input_device_names()).then_some(name);
+        let output = has_device(self.get_output_device_names()).then_some(name);
+
+        if input.is_none() && output.is_none() {
+            return None;
+        }
+
+        self.as_mut()
+            .create_device(input.unwrap_or_default(), output.unwrap_or_default())
+    }
+
+    pub(crate) fn device_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+
+        for name in self
+            .get_input_device_names()
+            .as_ref()
+            .iter()
+            .chain(self.get_output_device_names().as_ref())
+        {
+            if !names.iter().any(|existing| name == existing.as_str()) {
+                names.push(name.to_string());
+            }
+        }
+
+        names
+    }
+}
+
+/// Describes a device that isn't open, opening it briefly to find its latency and bit depth.
+pub(crate) fn describe_closed_device(mut device: Pin<&mut AudioIODevice>) -> AudioDeviceReport {
+    let mut report = describe_channels_and_settings(device.as_mut());
+
+    let channels = |count: usize| {
+        let mut channels = BigInteger::default();
+        if count > 0 {
+            channels.set_range(0, count as i32, true);
+        }
+        channels
+    };
+
+    let inputs = channels(report.input_channel_names.len());
+    let outputs = channels(report.output_channel_names.len());
+    let sample_rate = match device.as_mut().get_current_sample_rate() {
+        rate if rate > 0.0 => rate,
+        _ => report.sample_rates.first().copied().unwrap_or_default(),
+    };
+    let error = device
+        .as_mut()
+        .open(&inputs, &outputs, sample_rate, report.default_buffer_size);
+
+    if error.as_ref().is_empty() {
+        describe_latency_and_bit_depth(device.as_mut(), &mut report);
+        device.close();
+    } else {
+        report.open_error = Some(error.to_string());
+    }
+
+    report
+}
+
+/// Describes a device that is already open, without disturbing it.
+pub(crate) fn describe_open_device(mut device: Pin<&mut AudioIODevice>) -> AudioDeviceReport {
+    let mut report = describe_channels_and_settings(device.as_mut());
+    describe_latency_and_bit_depth(device, &mut report);
+    report
+}
+
+fn describe_channels_and_settings(mut device: Pin<&mut AudioIODevice>) -> AudioDeviceReport {
+    let strings = |names: StringArray| names.as_ref().iter().map(ToString::to_string).collect();
+
+    AudioDeviceReport {
+        name: device.get_name().to_string(),
+        type_name: device.get_type_name().to_string(),
+        input_channel_names: strings(device.as_mut().get_input_channel_names()),
+        output_channel_names: strings(device.as_mut().get_output_channel_names()),
+        sample_rates: device
+            .as_mut()
+            .get_available_sample_rates()
+            .as_ref()
+            .to_vec(),
+        buffer_sizes: device
+            .as_mut()
+            .get_available_buffer_sizes()
+            .as_ref()
+            .to_vec(),
+        default_buffer_size: device.as_mut().get_default_buffer_size(),
+        ..AudioDeviceReport::default()
+    }
+}
+
+fn describe_latency_and_bit_depth(
+    mut device: Pin<&mut AudioIODevice>,
+    report: &mut AudioDeviceReport,
+) {
+    report.input_latency = Some(device.as_mut().get_input_latency_in_samples());
+    report.output_latency = Some(device.as_mut().get_output_latency_in_samples());
+    report.bit_depth = Some(device.as_mut().get_current_bit_depth());
+}
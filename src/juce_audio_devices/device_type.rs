@@ -38,6 +38,36 @@ impl AudioIODeviceType {
     }
 }
 
+/// Tells the listeners of an [`AudioDeviceType`] that its list of devices has changed.
+///
+/// A notifier is passed to [`AudioDeviceType::set_device_list_notifier`] when the device type is
+/// registered. Listeners are called on the message thread, and calls made after the device type
+/// has been destroyed do nothing.
+pub struct AudioDeviceListNotifier {
+    notifier: UniquePtr<juce::AudioDeviceListNotifier>,
+}
+
+// The notifier only holds a shared pointer to the device type, guarded by a lock, and posts the
+// notification to the message thread when called from any other thread.
+unsafe impl Send for AudioDeviceListNotifier {}
+unsafe impl Sync for AudioDeviceListNotifier {}
+
+impl AudioDeviceListNotifier {
+    /// Tells listeners that devices have been added or removed.
+    pub fn notify(&self) {
+        self.notifier.notify();
+    }
+}
+
+impl AudioDeviceTypeImpl {
+    fn set_device_list_notifier(
+        device_type: &mut Box<dyn AudioDeviceType>,
+        notifier: UniquePtr<juce::AudioDeviceListNotifier>,
+    ) {
+        device_type.set_device_list_notifier(AudioDeviceListNotifier { notifier });
+    }
+}
+
 #[cxx::bridge(namespace = "juce")]
 mod juce {
     unsafe extern "C++" {
@@ -54,6 +84,12 @@ mod juce {
         /// A boxed [`AudioDeviceType`] trait object.
         type BoxDynAudioDeviceType = Box<dyn super::AudioDeviceType>;
 
+        #[namespace = "cxx_juce"]
+        type AudioDeviceListNotifier;
+
+        #[namespace = "cxx_juce"]
+        fn notify(self: &AudioDeviceListNotifier);
+
         #[namespace = "cxx_juce"]
         #[cxx_name = "wrap"]
         fn wrap_audio_device_type(
@@ -112,6 +148,12 @@ mod juce {
 
         #[Self = "AudioDeviceTypeImpl"]
         fn has_separate_inputs_and_outputs(device_type: &BoxDynAudioDeviceType) -> bool;
+
+        #[Self = "AudioDeviceTypeImpl"]
+        fn set_device_list_notifier(
+            device_type: &mut BoxDynAudioDeviceType,
+            notifier: UniquePtr<AudioDeviceListNotifier>,
+        );
     }
 }
 
@@ -145,6 +187,10 @@ define_trait! {
 
     /// Returns true if the device type has separate inputs and outputs.
     fn has_separate_inputs_and_outputs(&self) -> bool;
+
+    /// Called when the device type is registered, with a notifier to call whenever devices are
+    /// added or removed.
+    fn set_device_list_notifier(&mut self, _notifier: AudioDeviceListNotifier), @nobind {}
 }
 
 impl From<Box<dyn AudioDeviceType>> for UniquePtr<AudioIODeviceType> {
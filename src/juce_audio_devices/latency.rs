@@ -0,0 +1,335 @@
+use crate::{
+    juce_audio_basics::AudioSampleBuffer,
+    juce_audio_devices::{AudioDeviceCallback, AudioIODevice},
+    utils::{error, lock},
+    JuceError,
+};
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, TryLockError,
+    },
+    time::{Duration, Instant},
+};
+
+/// The number of samples in the test signal.
+const TEST_SIGNAL_LENGTH: usize = 2048;
+
+/// How closely the input has to match the test signal for it to count as detected, as a
+/// normalised cross-correlation.
+const DETECTION_THRESHOLD: f32 = 0.5;
+
+/// The result of a round-trip latency measurement.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LatencyMeasurement {
+    /// The number of samples between the test signal being output and it arriving at the input.
+    pub measured_latency: i32,
+    /// The sum of the input and output latencies reported by the device.
+    pub reported_latency: i32,
+    /// The sample rate the device was running at.
+    pub sample_rate: f64,
+}
+
+impl LatencyMeasurement {
+    /// Returns how many samples more latency was measured than the device reported.
+    pub fn difference(&self) -> i32 {
+        self.measured_latency - self.reported_latency
+    }
+
+    /// Returns the measured latency as a duration.
+    pub fn measured_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.measured_latency.max(0) as f64 / self.sample_rate)
+    }
+}
+
+struct State {
+    signal: Vec<f32>,
+    recording: Vec<f32>,
+    position: Option<usize>,
+    finished: bool,
+    reported_latency: i32,
+    sample_rate: f64,
+}
+
+struct Shared {
+    max_latency: Duration,
+    state: Mutex<State>,
+    measuring: AtomicBool,
+}
+
+/// Measures the round-trip latency of an audio device.
+///
+/// The tester plays a burst of noise through every output channel and listens for it on the
+/// first input channel, so the device's output has to be connected back to its input, either
+/// with a cable, a microphone near a speaker, or a loopback device. The delay it finds is
+/// compared with the latencies that the device reports, in the same way as JUCE's
+/// `AudioLatencyDemo`.
+///
+/// The tester is an [`AudioDeviceCallback`]: register a clone of it with a running device and
+/// call [`LatencyTester::measure`], or [`LatencyTester::start`] and [`LatencyTester::result`],
+/// on the original. The tester is silent while it isn't measuring.
+#[derive(Clone)]
+pub struct LatencyTester {
+    shared: Arc<Shared>,
+}
+
+impl Default for LatencyTester {
+    fn default() -> Self {
+        Self::with_max_latency(Duration::from_millis(500))
+    }
+}
+
+impl LatencyTester {
+    /// Creates a tester that can detect up to half a second of latency.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a tester that can detect up to `max_latency` of latency.
+    ///
+    /// Longer maximum latencies make each measurement take longer to run and to analyse.
+    pub fn with_max_latency(max_latency: Duration) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                max_latency,
+                state: Mutex::new(State {
+                    signal: Vec::new(),
+                    recording: Vec::new(),
+                    position: None,
+                    finished: false,
+                    reported_latency: 0,
+                    sample_rate: 0.0,
+                }),
+                measuring: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Starts a measurement, abandoning any measurement in progress.
+    ///
+    /// The tester must have been started by an audio device.
+    pub fn start(&self) -> Result<(), JuceError> {
+        let mut state = lock(&self.shared.state);
+
+        if state.signal.is_empty() {
+            return Err(error(
+                "The latency tester hasn't been started by an audio device",
+            ));
+        }
+
+        state.recording.fill(0.0);
+        state.position = Some(0);
+        state.finished = false;
+        self.shared.measuring.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Returns `true` while a measurement is being played and recorded.
+    pub fn is_measuring(&self) -> bool {
+        self.shared.measuring.load(Ordering::Acquire)
+    }
+
+    /// Returns the result of the last measurement, or [`None`] if a measurement hasn't finished
+    /// since the tester was started.
+    ///
+    /// Returns an error if the test signal couldn't be found in the input.
+    pub fn result(&self) -> Option<Result<LatencyMeasurement, JuceError>> {
+        if self.is_measuring() {
+            return None;
+        }
+
+        let (signal, recording, reported_latency, sample_rate) = {
+            let mut state = lock(&self.shared.state);
+
+            if !state.finished {
+                return None;
+            }
+
+            (
+                std::mem::take(&mut state.signal),
+                std::mem::take(&mut state.recording),
+                state.reported_latency,
+                state.sample_rate,
+            )
+        };
+
+        // The buffers are analysed outside the lock so that the audio thread isn't held up.
+        let measured_latency = find_signal(&signal, &recording);
+
+        let mut state = lock(&self.shared.state);
+        if state.signal.is_empty() {
+            state.signal = signal;
+            state.recording = recording;
+        }
+
+        Some(
+            measured_latency
+                .map(|measured_latency| LatencyMeasurement {
+                    measured_latency: measured_latency as i32,
+                    reported_latency,
+                    sample_rate,
+                })
+                .ok_or_else(|| error("The test signal wasn't detected in the input")),
+        )
+    }
+
+    /// Runs a measurement and waits for its result.
+    ///
+    /// Returns an error if the tester hasn't been started by a device, if the measurement
+    /// doesn't finish within `timeout`, or if the test signal couldn't be found in the input.
+    pub fn measure(&self, timeout: Duration) -> Result<LatencyMeasurement, JuceError> {
+        self.start()?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(result) = self.result() {
+                return result;
+            }
+
+            if Instant::now() >= deadline {
+                return Err(error("Timed out waiting for the latency measurement"));
+            }
+
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+impl AudioDeviceCallback for LatencyTester {
+    fn about_to_start(&mut self, mut device: Pin<&mut AudioIODevice>) {
+        let sample_rate = device.as_mut().get_current_sample_rate();
+        let reported_latency = device.as_mut().get_input_latency_in_samples()
+            + device.as_mut().get_output_latency_in_samples();
+        let max_latency = (self.shared.max_latency.as_secs_f64() * sample_rate).ceil() as usize;
+
+        let mut state = lock(&self.shared.state);
+        state.signal = test_signal();
+        state.recording = vec![0.0; TEST_SIGNAL_LENGTH + max_latency];
+        state.position = None;
+        state.finished = false;
+        state.reported_latency = reported_latency;
+        state.sample_rate = sample_rate;
+        self.shared.measuring.store(false, Ordering::Release);
+    }
+
+    fn process_block(
+        &mut self,
+        input: &AudioSampleBuffer,
+        mut output: Pin<&mut AudioSampleBuffer>,
+    ) {
+        output.as_mut().clear();
+
+        if !self.shared.measuring.load(Ordering::Acquire) {
+            return;
+        }
+
+        let mut state = match self.shared.state.try_lock() {
+            Ok(state) => state,
+            Err(TryLockError::Poisoned(error)) => error.into_inner(),
+            Err(TryLockError::WouldBlock) => return,
+        };
+
+        let state = &mut *state;
+        let Some(position) = state.position else {
+            return;
+        };
+
+        let num_samples = output.get_num_samples().max(0) as usize;
+
+        if position < state.signal.len() {
+            let count = num_samples.min(state.signal.len() - position);
+            let signal = &state.signal[position..position + count];
+
+            for channel in 0..output.get_num_channels() {
+                output.as_mut().get_write_slice(channel)[..count].copy_from_slice(signal);
+            }
+        }
+
+        let count = num_samples.min(state.recording.len() - position);
+
+        if input.get_num_channels() > 0 {
+            state.recording[position..position + count]
+                .copy_from_slice(&input.get_read_slice(0)[..count]);
+        }
+
+        if position + count < state.recording.len() {
+            state.position = Some(position + count);
+        } else {
+            state.position = None;
+            state.finished = true;
+            self.shared.measuring.store(false, Ordering::Release);
+        }
+    }
+
+    fn stopped(&mut self) {}
+}
+
+/// Creates a burst of white noise, which correlates strongly with itself at only one offset.
+fn test_signal() -> Vec<f32> {
+    let mut seed = 0x2545_f491_u32;
+
+    (0..TEST_SIGNAL_LENGTH)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            (seed as f32 / u32::MAX as f32 - 0.5) * 0.5
+        })
+        .collect()
+}
+
+/// Returns the offset into `recording` at which `signal` best matches, if it matches well enough
+/// to have been detected.
+fn find_signal(signal: &[f32], recording: &[f32]) -> Option<usize> {
+    if signal.is_empty() || recording.len() < signal.len() {
+        return None;
+    }
+
+    let signal_energy: f32 = signal.iter().map(|sample| sample * sample).sum();
+    let mut best: Option<(usize, f32)> = None;
+
+    for offset in 0..=recording.len() - signal.len() {
+        let correlation: f32 = signal
+            .iter()
+            .zip(&recording[offset..])
+            .map(|(a, b)| a * b)
+            .sum();
+
+        if best.is_none_or(|(_, best)| correlation > best) {
+            best = Some((offset, correlation));
+        }
+    }
+
+    let (offset, correlation) = best?;
+    let window = &recording[offset..offset + signal.len()];
+    let window_energy: f32 = window.iter().map(|sample| sample * sample).sum();
+    let normalised = correlation / (signal_energy * window_energy).sqrt();
+
+    (normalised >= DETECTION_THRESHOLD).then_some(offset)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_the_test_signal_at_its_offset() {
+        let signal = test_signal();
+        let mut recording = vec![0.0; 10_000];
+        recording[1234..1234 + signal.len()]
+            .iter_mut()
+            .zip(&signal)
+            .for_each(|(recorded, sample)| *recorded = sample * 0.25);
+
+        assert_eq!(find_signal(&signal, &recording), Some(1234));
+    }
+
+    #[test]
+    fn silence_does_not_match_the_test_signal() {
+        let signal = test_signal();
+        let recording = vec![0.0; 10_000];
+
+        assert_eq!(find_signal(&signal, &recording), None);
+    }
+}
@@ -11,10 +11,12 @@ define_juce_type! {
         /// The name of the device.
         pub name: JuceString = {
             offset = juce::MidiDeviceInfoLayout::NameOffset,
+            with = with_name,
         },
         /// The unique identifier of the device.
         pub identifier: JuceString = {
             offset = juce::MidiDeviceInfoLayout::IdentifierOffset,
+            with = with_identifier,
         },
     },
     layout = juce::MidiDeviceInfoLayout,
@@ -0,0 +1,363 @@
+use crate::{
+    define_trait,
+    juce_audio_basics::MidiMessage,
+    juce_audio_devices::{
+        midi_input::MessageCallback, MidiDeviceInfo, MidiDeviceInfoArray, MidiInput,
+        MidiInputCallback, MidiInputWithCallback, MidiOutput,
+    },
+    juce_core::JuceString,
+    utils::lock,
+    JUCE,
+};
+use cxx::UniquePtr;
+use std::sync::{Arc, Mutex};
+
+/// A change to the set of available MIDI devices.
+#[derive(Debug, Clone)]
+pub enum MidiDeviceChange {
+    /// An input device was connected.
+    InputAdded(MidiDeviceInfo),
+    /// An input device was disconnected.
+    InputRemoved(MidiDeviceInfo),
+    /// An output device was connected.
+    OutputAdded(MidiDeviceInfo),
+    /// An output device was disconnected.
+    OutputRemoved(MidiDeviceInfo),
+}
+
+/// A registration for notifications about MIDI devices being connected and disconnected.
+///
+/// Callbacks are made on the message thread, so they will only arrive while the JUCE message
+/// loop is running. Dropping the connection unregisters the callback.
+pub struct MidiDeviceListConnection {
+    _connection: UniquePtr<juce::MidiDeviceListConnection>,
+}
+
+impl MidiDeviceListConnection {
+    /// Registers a callback that is called whenever a MIDI device is connected or disconnected.
+    pub fn new(_: &JUCE, callback: impl FnMut() + Send + 'static) -> Self {
+        struct Wrapper<Callback>(Callback);
+
+        impl<Callback> MidiDeviceListCallback for Wrapper<Callback>
+        where
+            Callback: FnMut() + Send + 'static,
+        {
+            fn call(&mut self) {
+                self.0();
+            }
+        }
+
+        Self {
+            _connection: juce::make_midi_device_list_connection(Box::new(Wrapper(callback))),
+        }
+    }
+
+    /// Registers a callback that is told which devices were connected or disconnected.
+    pub fn with_changes(
+        juce: &JUCE,
+        mut callback: impl FnMut(&MidiDeviceChange) + Send + 'static,
+    ) -> Self {
+        let mut inputs = DeviceSnapshot::of(&MidiInput::get_available_devices());
+        let mut outputs = DeviceSnapshot::of(&MidiOutput::get_available_devices());
+
+        Self::new(juce, move || {
+            let current_inputs = DeviceSnapshot::of(&MidiInput::get_available_devices());
+            let current_outputs = DeviceSnapshot::of(&MidiOutput::get_available_devices());
+
+            let (added, removed) = DeviceSnapshot::diff(&inputs, &current_inputs);
+            let input_changes = removed
+                .map(|device| MidiDeviceChange::InputRemoved(device.info()))
+                .chain(added.map(|device| MidiDeviceChange::InputAdded(device.info())));
+
+            let (added, removed) = DeviceSnapshot::diff(&outputs, &current_outputs);
+            let output_changes = removed
+                .map(|device| MidiDeviceChange::OutputRemoved(device.info()))
+                .chain(added.map(|device| MidiDeviceChange::OutputAdded(device.info())));
+
+            for change in input_changes.chain(output_changes) {
+                callback(&change);
+            }
+
+            inputs = current_inputs;
+            outputs = current_outputs;
+        })
+    }
+}
+
+/// The name and identifier of a device, held as Rust strings so they can be kept by callbacks
+/// that run on other threads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DeviceSnapshot {
+    name: String,
+    identifier: String,
+}
+
+impl DeviceSnapshot {
+    fn of(devices: &MidiDeviceInfoArray) -> Vec<Self> {
+        devices
+            .as_slice()
+            .iter()
+            .map(|device| Self {
+                name: device.name.as_ref().to_owned(),
+                identifier: device.identifier.as_ref().to_owned(),
+            })
+            .collect()
+    }
+
+    /// Returns the devices that were added and removed between `previous` and `current`.
+    fn diff<'a>(
+        previous: &'a [Self],
+        current: &'a [Self],
+    ) -> (
+        impl Iterator<Item = &'a Self>,
+        impl Iterator<Item = &'a Self>,
+    ) {
+        let added = current.iter().filter(|device| !device.is_in(previous));
+        let removed = previous.iter().filter(|device| !device.is_in(current));
+        (added, removed)
+    }
+
+    fn is_in(&self, devices: &[Self]) -> bool {
+        devices
+            .iter()
+            .any(|device| device.identifier == self.identifier)
+    }
+
+    fn info(&self) -> MidiDeviceInfo {
+        MidiDeviceInfo::default()
+            .with_name(JuceString::new(&self.name))
+            .with_identifier(JuceString::new(&self.identifier))
+    }
+}
+
+fn is_available(devices: &MidiDeviceInfoArray, identifier: &str) -> bool {
+    devices
+        .as_slice()
+        .iter()
+        .any(|device| device.identifier == identifier)
+}
+
+type OpenInput = Box<dyn FnMut(&JuceString) -> Option<MidiInputWithCallback> + Send>;
+
+struct InputState {
+    identifier: String,
+    open: OpenInput,
+    input: Option<MidiInputWithCallback>,
+}
+
+impl InputState {
+    fn refresh(&mut self) {
+        if !is_available(&MidiInput::get_available_devices(), &self.identifier) {
+            self.input = None;
+        } else if self.input.is_none() {
+            self.input = (self.open)(&JuceString::new(&self.identifier));
+            if let Some(input) = &mut self.input {
+                input.pin_mut().start();
+            }
+        }
+    }
+}
+
+/// A MIDI input that is reopened whenever its device is reconnected.
+///
+/// The device is identified by its [`MidiDeviceInfo::identifier`]. While the device is
+/// disconnected no messages arrive, and the same callback carries on receiving messages once it
+/// returns.
+pub struct ReconnectingMidiInput {
+    state: Arc<Mutex<InputState>>,
+    _connection: MidiDeviceListConnection,
+}
+
+impl ReconnectingMidiInput {
+    /// Opens the input with the given identifier, if it is connected, and keeps it open across
+    /// reconnections.
+    pub fn new(
+        juce: &JUCE,
+        device_identifier: &JuceString,
+        callback: impl FnMut(&MidiMessage) + Send + 'static,
+    ) -> Self {
+        Self::with_callback(juce, device_identifier, MessageCallback(callback))
+    }
+
+    /// Like [`ReconnectingMidiInput::new`], but with a [`MidiInputCallback`].
+    pub fn with_callback(
+        juce: &JUCE,
+        device_identifier: &JuceString,
+        callback: impl MidiInputCallback + 'static,
+    ) -> Self {
+        let callback = Arc::new(Mutex::new(callback));
+
+        let mut state = InputState {
+            identifier: device_identifier.as_ref().to_owned(),
+            open: Box::new(move |identifier| {
+                MidiInput::open_with_callback(identifier, Arc::clone(&callback))
+            }),
+            input: None,
+        };
+        state.refresh();
+
+        let state = Arc::new(Mutex::new(state));
+        let connection = MidiDeviceListConnection::new(juce, {
+            let state = Arc::clone(&state);
+            move || lock(&state).refresh()
+        });
+
+        Self {
+            state,
+            _connection: connection,
+        }
+    }
+
+    /// Returns `true` if the device is currently connected and open.
+    pub fn is_connected(&self) -> bool {
+        lock(&self.state).input.is_some()
+    }
+
+    /// Returns the identifier of the device.
+    pub fn identifier(&self) -> JuceString {
+        JuceString::new(&lock(&self.state).identifier)
+    }
+}
+
+struct OutputState {
+    identifier: String,
+    output: Option<UniquePtr<MidiOutput>>,
+}
+
+impl OutputState {
+    fn refresh(&mut self) {
+        if !is_available(&MidiOutput::get_available_devices(), &self.identifier) {
+            self.output = None;
+        } else if self.output.is_none() {
+            let output = MidiOutput::open_device(&JuceString::new(&self.identifier));
+            self.output = (!output.is_null()).then_some(output);
+        }
+    }
+}
+
+/// A MIDI output that is reopened whenever its device is reconnected.
+///
+/// The device is identified by its [`MidiDeviceInfo::identifier`]. Messages sent while the
+/// device is disconnected are discarded.
+pub struct ReconnectingMidiOutput {
+    state: Arc<Mutex<OutputState>>,
+    _connection: MidiDeviceListConnection,
+}
+
+impl ReconnectingMidiOutput {
+    /// Opens the output with the given identifier, if it is connected, and keeps it open across
+    /// reconnections.
+    pub fn new(juce: &JUCE, device_identifier: &JuceString) -> Self {
+        let mut state = OutputState {
+            identifier: device_identifier.as_ref().to_owned(),
+            output: None,
+        };
+        state.refresh();
+
+        let state = Arc::new(Mutex::new(state));
+        let connection = MidiDeviceListConnection::new(juce, {
+            let state = Arc::clone(&state);
+            move || lock(&state).refresh()
+        });
+
+        Self {
+            state,
+            _connection: connection,
+        }
+    }
+
+    /// Returns `true` if the device is currently connected and open.
+    pub fn is_connected(&self) -> bool {
+        lock(&self.state).output.is_some()
+    }
+
+    /// Returns the identifier of the device.
+    pub fn identifier(&self) -> JuceString {
+        JuceString::new(&lock(&self.state).identifier)
+    }
+
+    /// Sends a message immediately, returning `false` if the device is disconnected.
+    pub fn send_message_now(&self, message: &MidiMessage) -> bool {
+        match &mut lock(&self.state).output {
+            Some(output) => {
+                output.pin_mut().send_message_now(message);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Calls `f` with the open output, or returns [`None`] if the device is disconnected.
+    pub fn with_output<R>(&self, f: impl FnOnce(&mut UniquePtr<MidiOutput>) -> R) -> Option<R> {
+        lock(&self.state).output.as_mut().map(f)
+    }
+}
+
+#[cxx::bridge(namespace = "juce")]
+mod juce {
+    unsafe extern "C++" {
+        include!("cxx_juce.h");
+
+        type MidiDeviceListConnection;
+
+        #[namespace = "cxx_juce"]
+        type BoxDynMidiDeviceListCallback = Box<dyn super::MidiDeviceListCallback>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "makeMidiDeviceListConnection"]
+        fn make_midi_device_list_connection(
+            callback: BoxDynMidiDeviceListCallback,
+        ) -> UniquePtr<MidiDeviceListConnection>;
+    }
+
+    #[namespace = "cxx_juce"]
+    extern "Rust" {
+        type MidiDeviceListCallbackImpl;
+
+        #[Self = "MidiDeviceListCallbackImpl"]
+        unsafe fn drop(callback: *mut BoxDynMidiDeviceListCallback);
+
+        #[Self = "MidiDeviceListCallbackImpl"]
+        fn call(callback: &mut BoxDynMidiDeviceListCallback);
+    }
+}
+
+define_trait! {
+    MidiDeviceListCallback: Send,
+    MidiDeviceListCallbackImpl,
+    "cxx_juce::BoxDynMidiDeviceListCallback",
+    fn call(&mut self);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn device(identifier: &str) -> DeviceSnapshot {
+        DeviceSnapshot {
+            name: format!("Device {identifier}"),
+            identifier: identifier.to_owned(),
+        }
+    }
+
+    #[test]
+    fn diffing_device_lists() {
+        let previous = [device("a"), device("b")];
+        let current = [device("b"), device("c")];
+
+        let (added, removed) = DeviceSnapshot::diff(&previous, &current);
+        let added: Vec<_> = added.map(|device| device.identifier.as_str()).collect();
+        let removed: Vec<_> = removed.map(|device| device.identifier.as_str()).collect();
+
+        assert_eq!(added, ["c"]);
+        assert_eq!(removed, ["a"]);
+    }
+
+    #[test]
+    fn snapshots_convert_back_to_device_info() {
+        let info = device("a").info();
+
+        assert_eq!(info.name, "Device a");
+        assert_eq!(info.identifier, "a");
+    }
+}
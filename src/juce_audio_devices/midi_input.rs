@@ -1,5 +1,6 @@
 use crate::define_trait;
 use cxx::UniquePtr;
+use std::sync::{Arc, Mutex};
 
 pub use juce::MidiInput;
 
@@ -9,6 +10,11 @@ pub struct MidiInputWithCallback {
     _callback: UniquePtr<juce::MidiInputCallback>,
 }
 
+// An open `MidiInput` isn't tied to the thread that opened it, and the callback it owns is
+// required to be `Send`, so both can be handed to another thread.
+unsafe impl Send for MidiInput {}
+unsafe impl Send for MidiInputWithCallback {}
+
 impl std::ops::Deref for MidiInputWithCallback {
     type Target = UniquePtr<MidiInput>;
     fn deref(&self) -> &Self::Target {
@@ -28,8 +34,50 @@ impl MidiInput {
         device: &juce::JuceString,
         callback: impl FnMut(&juce::MidiMessage) + Send + 'static,
     ) -> Option<MidiInputWithCallback> {
-        let callback = wrap_midi_input_callback(callback);
-        let device = unsafe { Self::open_device(device, callback.as_mut_ptr()) };
+        Self::open_with_callback(device, MessageCallback(callback))
+    }
+
+    /// Opens a MIDI input device and registers a [`MidiInputCallback`] for incoming messages.
+    ///
+    /// To share a single callback between several inputs, wrap it in an `Arc<Mutex<_>>` and
+    /// pass a clone to each input. The callback can tell the inputs apart using
+    /// [`MidiInput::get_device_info`].
+    pub fn open_with_callback(
+        device: &juce::JuceString,
+        callback: impl MidiInputCallback + 'static,
+    ) -> Option<MidiInputWithCallback> {
+        Self::with_wrapped_callback(callback, |callback| unsafe {
+            Self::open_device(device, callback)
+        })
+    }
+
+    /// Creates a new virtual MIDI input device that other applications can send messages to.
+    ///
+    /// Returns [`None`] on platforms that don't support virtual devices (Windows and Android)
+    /// or if the device could not be created.
+    pub fn create_new_device(
+        device_name: &juce::JuceString,
+        callback: impl FnMut(&juce::MidiMessage) + Send + 'static,
+    ) -> Option<MidiInputWithCallback> {
+        Self::create_new_device_with_callback(device_name, MessageCallback(callback))
+    }
+
+    /// Creates a new virtual MIDI input device with a [`MidiInputCallback`].
+    pub fn create_new_device_with_callback(
+        device_name: &juce::JuceString,
+        callback: impl MidiInputCallback + 'static,
+    ) -> Option<MidiInputWithCallback> {
+        Self::with_wrapped_callback(callback, |callback| unsafe {
+            juce::create_new_midi_input_device(device_name, callback)
+        })
+    }
+
+    fn with_wrapped_callback(
+        callback: impl MidiInputCallback + 'static,
+        open: impl FnOnce(*mut juce::MidiInputCallback) -> UniquePtr<MidiInput>,
+    ) -> Option<MidiInputWithCallback> {
+        let callback = wrap_callback(callback);
+        let device = open(callback.as_mut_ptr());
         (!device.is_null()).then(|| MidiInputWithCallback {
             device,
             _callback: callback,
@@ -37,6 +85,103 @@ impl MidiInput {
     }
 }
 
+/// The receiving side of a MIDI input.
+///
+/// This is implemented for open [`MidiInput`] devices and for
+/// [`MidiLoopbackInput`](crate::juce_audio_devices::MidiLoopbackInput), so code that receives
+/// MIDI can be tested without any devices.
+pub trait MidiInputPort: Send {
+    /// Starts listening for MIDI input.
+    fn start(&mut self);
+
+    /// Stops listening for MIDI input.
+    fn stop(&mut self);
+
+    /// Returns the device info for the input.
+    fn get_device_info(&self) -> juce::MidiDeviceInfo;
+}
+
+impl MidiInputPort for MidiInputWithCallback {
+    fn start(&mut self) {
+        self.pin_mut().start();
+    }
+
+    fn stop(&mut self) {
+        self.pin_mut().stop();
+    }
+
+    fn get_device_info(&self) -> juce::MidiDeviceInfo {
+        MidiInput::get_device_info(self)
+    }
+}
+
+/// Somewhere that [`MidiInputPort`]s can be opened from.
+///
+/// This is implemented for device identifiers, which open a [`MidiInput`], and for
+/// [`MidiLoopback`](crate::juce_audio_devices::MidiLoopback), which opens a
+/// [`MidiLoopbackInput`](crate::juce_audio_devices::MidiLoopbackInput).
+pub trait MidiInputSource {
+    /// The type of input that is opened.
+    type Input: MidiInputPort + 'static;
+
+    /// Opens an input that calls `callback` for every message it receives while it is started.
+    ///
+    /// Returns [`None`] if the input could not be opened.
+    fn open_input(
+        &self,
+        callback: impl FnMut(&juce::MidiMessage) + Send + 'static,
+    ) -> Option<Self::Input>;
+}
+
+impl MidiInputSource for juce::JuceString {
+    type Input = MidiInputWithCallback;
+
+    fn open_input(
+        &self,
+        callback: impl FnMut(&juce::MidiMessage) + Send + 'static,
+    ) -> Option<Self::Input> {
+        MidiInput::open(self, callback)
+    }
+}
+
+/// The JUCE object that forwards to a [`MidiInputCallback`].
+pub(crate) type JuceMidiInputCallback = juce::MidiInputCallback;
+
+pub(crate) fn wrap_callback(
+    callback: impl MidiInputCallback + 'static,
+) -> UniquePtr<JuceMidiInputCallback> {
+    juce::wrap_midi_input_callback(Box::new(callback))
+}
+
+/// Adapts a closure that only wants complete messages into a [`MidiInputCallback`].
+pub(crate) struct MessageCallback<Callback>(pub(crate) Callback);
+
+impl<Callback> MidiInputCallback for MessageCallback<Callback>
+where
+    Callback: FnMut(&juce::MidiMessage) + Send + 'static,
+{
+    fn handle_incoming_midi_message(&mut self, _: &MidiInput, message: &juce::MidiMessage) {
+        self.0(message);
+    }
+}
+
+impl<Callback> MidiInputCallback for Arc<Mutex<Callback>>
+where
+    Callback: MidiInputCallback,
+{
+    fn handle_incoming_midi_message(&mut self, source: &MidiInput, message: &juce::MidiMessage) {
+        self.lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .handle_incoming_midi_message(source, message);
+    }
+
+    fn handle_partial_sysex_message(&mut self, source: &MidiInput, data: &[u8], timestamp: f64) {
+        self.lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .handle_partial_sysex_message(source, data, timestamp);
+    }
+}
+
 #[cxx::bridge(namespace = "juce")]
 mod juce {
     unsafe extern "C++" {
@@ -77,6 +222,13 @@ mod juce {
             callback: *mut MidiInputCallback,
         ) -> UniquePtr<MidiInput>;
 
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "createNewMidiInputDevice"]
+        unsafe fn create_new_midi_input_device(
+            device_name: &JuceString,
+            callback: *mut MidiInputCallback,
+        ) -> UniquePtr<MidiInput>;
+
         /// Returns the device info for this input.
         #[cxx_name = "getDeviceInfo"]
         fn get_device_info(self: &MidiInput) -> MidiDeviceInfo;
@@ -100,31 +252,35 @@ mod juce {
         #[Self = "MidiInputCallbackImpl"]
         unsafe fn handle_incoming_midi_message(
             callback: &mut BoxDynMidiInputCallback,
+            source: &MidiInput,
             message: &MidiMessage,
         );
-    }
-}
-
-fn wrap_midi_input_callback(
-    callback: impl FnMut(&juce::MidiMessage) + Send + 'static,
-) -> UniquePtr<juce::MidiInputCallback> {
-    struct Wrapper<Callback>(Callback);
 
-    impl<Callback> MidiInputCallback for Wrapper<Callback>
-    where
-        Callback: FnMut(&juce::MidiMessage) + Send + 'static,
-    {
-        fn handle_incoming_midi_message(&mut self, message: &juce::MidiMessage) {
-            self.0(message);
-        }
+        #[Self = "MidiInputCallbackImpl"]
+        unsafe fn handle_partial_sysex_message(
+            callback: &mut BoxDynMidiInputCallback,
+            source: &MidiInput,
+            data: &[u8],
+            timestamp: f64,
+        );
     }
-
-    juce::wrap_midi_input_callback(Box::new(Wrapper(callback)))
 }
 
 define_trait! {
+    /// A callback that receives the messages arriving at one or more MIDI inputs.
     MidiInputCallback: Send,
     MidiInputCallbackImpl,
     "cxx_juce::BoxDynMidiInputCallback",
-    fn handle_incoming_midi_message(&mut self, message: &juce::MidiMessage);
+
+    /// Called when a complete message arrives at `source`.
+    fn handle_incoming_midi_message(&mut self, source: &MidiInput, message: &juce::MidiMessage);
+
+    /// Called repeatedly while a long system exclusive message is arriving at `source`.
+    ///
+    /// `data` holds all the bytes received so far, starting with the `0xf0` status byte, so its
+    /// length can be used to report progress. Once the message is complete it is delivered to
+    /// [`MidiInputCallback::handle_incoming_midi_message`] as usual.
+    fn handle_partial_sysex_message(&mut self, source: &MidiInput, data: &[u8], timestamp: f64) {
+        let _ = (source, data, timestamp);
+    }
 }
@@ -0,0 +1,306 @@
+use crate::{
+    juce_audio_basics::MidiMessage,
+    juce_audio_devices::{MidiInput, MidiInputWithCallback},
+    juce_core::JuceString,
+};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// What a [`MidiInputStream`] does with a message that arrives while its queue is full.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum MidiOverflowPolicy {
+    /// Discard the message that just arrived.
+    #[default]
+    DropNewest,
+    /// Discard the oldest queued message to make room.
+    DropOldest,
+}
+
+/// Options for [`MidiInput::open_stream`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MidiInputStreamOptions {
+    /// The maximum number of messages to queue.
+    pub capacity: usize,
+    /// What to do when a message arrives while the queue is full.
+    pub overflow_policy: MidiOverflowPolicy,
+}
+
+impl Default for MidiInputStreamOptions {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow_policy: MidiOverflowPolicy::default(),
+        }
+    }
+}
+
+impl MidiInputStreamOptions {
+    /// Returns `self` with the capacity set.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Returns `self` with the overflow policy set.
+    pub fn with_overflow_policy(mut self, overflow_policy: MidiOverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    messages: VecDeque<MidiMessage>,
+    dropped: u64,
+    #[cfg(feature = "futures")]
+    waker: Option<std::task::Waker>,
+}
+
+/// A bounded queue of messages, filled by the MIDI input callback.
+struct Channel {
+    queue: Mutex<Queue>,
+    ready: Condvar,
+    options: MidiInputStreamOptions,
+}
+
+impl Channel {
+    fn new(options: MidiInputStreamOptions) -> Self {
+        let options = options.with_capacity(options.capacity.max(1));
+
+        Self {
+            queue: Mutex::new(Queue {
+                messages: VecDeque::with_capacity(options.capacity),
+                ..Queue::default()
+            }),
+            ready: Condvar::new(),
+            options,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn push(&self, message: &MidiMessage) {
+        let mut queue = self.lock();
+
+        if queue.messages.len() >= self.options.capacity {
+            queue.dropped += 1;
+
+            match self.options.overflow_policy {
+                MidiOverflowPolicy::DropNewest => return,
+                MidiOverflowPolicy::DropOldest => {
+                    queue.messages.pop_front();
+                }
+            }
+        }
+
+        queue.messages.push_back(message.clone());
+
+        #[cfg(feature = "futures")]
+        let waker = queue.waker.take();
+
+        drop(queue);
+        self.ready.notify_one();
+
+        #[cfg(feature = "futures")]
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn try_pop(&self) -> Option<MidiMessage> {
+        self.lock().messages.pop_front()
+    }
+
+    fn pop(&self, timeout: Option<Duration>) -> Option<MidiMessage> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut queue = self.lock();
+
+        loop {
+            if let Some(message) = queue.messages.pop_front() {
+                return Some(message);
+            }
+
+            queue = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.checked_duration_since(Instant::now())?;
+                    self.ready
+                        .wait_timeout(queue, remaining)
+                        .unwrap_or_else(|error| error.into_inner())
+                        .0
+                }
+                None => self
+                    .ready
+                    .wait(queue)
+                    .unwrap_or_else(|error| error.into_inner()),
+            };
+        }
+    }
+}
+
+/// A MIDI input whose messages are read from a queue rather than handled in a callback.
+///
+/// Messages can be read with blocking calls in the style of [`std::sync::mpsc::Receiver`] or,
+/// with the `futures` feature enabled, by polling the stream as a `futures::Stream`.
+///
+/// The input is started when the stream is opened, and closed when the stream is dropped.
+pub struct MidiInputStream {
+    input: MidiInputWithCallback,
+    channel: Arc<Channel>,
+}
+
+impl MidiInput {
+    /// Opens a MIDI input device and returns a stream of the messages that arrive at it.
+    ///
+    /// Returns [`None`] if the device could not be opened.
+    pub fn open_stream(
+        device: &JuceString,
+        options: MidiInputStreamOptions,
+    ) -> Option<MidiInputStream> {
+        let channel = Arc::new(Channel::new(options));
+
+        let mut input = Self::open(device, {
+            let channel = Arc::clone(&channel);
+            move |message| channel.push(message)
+        })?;
+        input.pin_mut().start();
+
+        Some(MidiInputStream { input, channel })
+    }
+}
+
+impl MidiInputStream {
+    /// Returns the input being read.
+    pub fn input(&self) -> &MidiInput {
+        &self.input
+    }
+
+    /// Blocks until a message arrives.
+    pub fn recv(&self) -> MidiMessage {
+        loop {
+            if let Some(message) = self.channel.pop(None) {
+                return message;
+            }
+        }
+    }
+
+    /// Blocks until a message arrives or the timeout elapses.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<MidiMessage> {
+        self.channel.pop(Some(timeout))
+    }
+
+    /// Returns a queued message without blocking, or [`None`] if the queue is empty.
+    pub fn try_recv(&self) -> Option<MidiMessage> {
+        self.channel.try_pop()
+    }
+
+    /// Returns an iterator that blocks waiting for each message.
+    pub fn iter(&self) -> impl Iterator<Item = MidiMessage> + '_ {
+        std::iter::repeat_with(|| self.recv())
+    }
+
+    /// Returns an iterator over the messages that are already queued.
+    pub fn try_iter(&self) -> impl Iterator<Item = MidiMessage> + '_ {
+        std::iter::from_fn(|| self.try_recv())
+    }
+
+    /// Returns the number of messages that are queued.
+    pub fn len(&self) -> usize {
+        self.channel.lock().messages.len()
+    }
+
+    /// Returns `true` if no messages are queued.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of messages discarded because the queue was full.
+    pub fn dropped_count(&self) -> u64 {
+        self.channel.lock().dropped
+    }
+
+    /// Returns the options the stream was opened with.
+    pub fn options(&self) -> MidiInputStreamOptions {
+        self.channel.options
+    }
+}
+
+#[cfg(feature = "futures")]
+impl futures_core::Stream for MidiInputStream {
+    type Item = MidiMessage;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let mut queue = self.channel.lock();
+
+        match queue.messages.pop_front() {
+            Some(message) => std::task::Poll::Ready(Some(message)),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                std::task::Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn notes(channel: &Channel) -> Vec<i32> {
+        std::iter::from_fn(|| channel.try_pop())
+            .map(|message| message.get_note_number())
+            .collect()
+    }
+
+    fn fill(channel: &Channel) {
+        for note in 0..4 {
+            channel.push(&MidiMessage::note_on(1, note, 0.5));
+        }
+    }
+
+    #[test]
+    fn full_queues_drop_the_newest_messages() {
+        let channel = Channel::new(MidiInputStreamOptions::default().with_capacity(2));
+        fill(&channel);
+
+        assert_eq!(notes(&channel), [0, 1]);
+        assert_eq!(channel.lock().dropped, 2);
+    }
+
+    #[test]
+    fn full_queues_can_drop_the_oldest_messages() {
+        let channel = Channel::new(
+            MidiInputStreamOptions::default()
+                .with_capacity(2)
+                .with_overflow_policy(MidiOverflowPolicy::DropOldest),
+        );
+        fill(&channel);
+
+        assert_eq!(notes(&channel), [2, 3]);
+        assert_eq!(channel.lock().dropped, 2);
+    }
+
+    #[test]
+    fn receiving_blocks_until_a_message_arrives() {
+        let channel = Arc::new(Channel::new(MidiInputStreamOptions::default()));
+
+        assert!(channel.pop(Some(Duration::from_millis(1))).is_none());
+
+        let sender = std::thread::spawn({
+            let channel = Arc::clone(&channel);
+            move || channel.push(&MidiMessage::note_on(1, 64, 0.5))
+        });
+
+        let message = channel.pop(None).unwrap();
+        assert_eq!(message.get_note_number(), 64);
+
+        sender.join().unwrap();
+    }
+}
@@ -0,0 +1,520 @@
+use crate::{
+    juce_audio_basics::{MidiBuffer, MidiMessage},
+    juce_audio_devices::{MidiDeviceInfo, MidiInputPort, MidiInputSource, MidiOutputPort},
+    juce_core::{JuceString, Time},
+    utils::lock,
+};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+static NEXT_KEY: AtomicU64 = AtomicU64::new(0);
+
+fn next_key() -> u64 {
+    NEXT_KEY.fetch_add(1, Ordering::Relaxed)
+}
+
+type BoxedCallback = Box<dyn FnMut(&MidiMessage) + Send>;
+
+/// The callback of an input, along with the messages that arrived while it was running.
+///
+/// The callback is taken out while it runs, so that it can send to the port it is listening to
+/// without deadlocking. Messages that arrive in the meantime are queued and passed to it by the
+/// thread that is running it, once it returns.
+struct CallbackSlot {
+    callback: Option<BoxedCallback>,
+    queued: VecDeque<MidiMessage>,
+}
+
+type Callback = Arc<Mutex<CallbackSlot>>;
+
+fn call(slot: &Mutex<CallbackSlot>, message: &MidiMessage) {
+    let mut callback = {
+        let mut slot = lock(slot);
+        match slot.callback.take() {
+            Some(callback) => callback,
+            None => {
+                slot.queued.push_back(message.clone());
+                return;
+            }
+        }
+    };
+
+    callback(message);
+
+    loop {
+        let message = {
+            let mut slot = lock(slot);
+            match slot.queued.pop_front() {
+                Some(message) => message,
+                None => {
+                    slot.callback = Some(callback);
+                    return;
+                }
+            }
+        };
+
+        callback(&message);
+    }
+}
+
+struct Receiver {
+    key: u64,
+    started: Arc<AtomicBool>,
+    callback: Callback,
+}
+
+struct Port {
+    name: String,
+    identifier: String,
+    receivers: Mutex<Vec<Receiver>>,
+}
+
+impl Port {
+    /// Delivers a message to every started input, timestamped with the current time in seconds
+    /// as a real input would.
+    fn deliver(&self, message: &MidiMessage) {
+        let callbacks: Vec<_> = lock(&self.receivers)
+            .iter()
+            .filter(|receiver| receiver.started.load(Ordering::Acquire))
+            .map(|receiver| Arc::clone(&receiver.callback))
+            .collect();
+
+        if callbacks.is_empty() {
+            return;
+        }
+
+        let mut message = message.clone();
+        message.set_time_stamp(Time::get_millisecond_counter_hi_res() * 0.001);
+
+        for callback in callbacks {
+            call(&callback, &message);
+        }
+    }
+}
+
+/// An in-process MIDI port that connects loopback outputs to loopback inputs.
+///
+/// Messages sent to any [`MidiLoopbackOutput`] opened from the port arrive at the callback of
+/// every started [`MidiLoopbackInput`] opened from it, in the same way that they would if the
+/// output and input were connected to a real device. This makes it possible to test MIDI code
+/// on machines without any MIDI devices.
+///
+/// The port is a [`MidiInputSource`] and its outputs are [`MidiOutputPort`]s, so it can stand in
+/// for devices wherever the MIDI tools in this module accept them.
+///
+/// Cloning a `MidiLoopback` gives another handle to the same port.
+#[derive(Clone)]
+pub struct MidiLoopback {
+    port: Arc<Port>,
+}
+
+impl MidiLoopback {
+    /// Creates a new port with the given name.
+    pub fn new(name: impl AsRef<str>) -> Self {
+        Self {
+            port: Arc::new(Port {
+                name: name.as_ref().to_owned(),
+                identifier: format!("loopback-{}", next_key()),
+                receivers: Mutex::default(),
+            }),
+        }
+    }
+
+    /// Returns the device info for the port.
+    ///
+    /// The identifier is unique to this port, but isn't known to
+    /// [`MidiInput::open`](crate::juce_audio_devices::MidiInput::open).
+    pub fn get_device_info(&self) -> MidiDeviceInfo {
+        MidiDeviceInfo::default()
+            .with_name(JuceString::new(&self.port.name))
+            .with_identifier(JuceString::new(&self.port.identifier))
+    }
+
+    /// Opens an input that calls `callback` for every message sent to the port while it is
+    /// started.
+    pub fn open_input(
+        &self,
+        callback: impl FnMut(&MidiMessage) + Send + 'static,
+    ) -> MidiLoopbackInput {
+        let key = next_key();
+        let started = Arc::new(AtomicBool::new(false));
+
+        lock(&self.port.receivers).push(Receiver {
+            key,
+            started: Arc::clone(&started),
+            callback: Arc::new(Mutex::new(CallbackSlot {
+                callback: Some(Box::new(callback)),
+                queued: VecDeque::new(),
+            })),
+        });
+
+        MidiLoopbackInput {
+            loopback: self.clone(),
+            key,
+            started,
+        }
+    }
+
+    /// Opens an output that sends messages to the port.
+    pub fn open_output(&self) -> MidiLoopbackOutput {
+        MidiLoopbackOutput {
+            loopback: self.clone(),
+            schedule: Arc::default(),
+            background_thread: None,
+        }
+    }
+}
+
+/// The receiving end of a [`MidiLoopback`].
+///
+/// Like a [`MidiInput`](crate::juce_audio_devices::MidiInput), it only receives messages
+/// between calls to [`MidiLoopbackInput::start`] and [`MidiLoopbackInput::stop`]. Messages are
+/// delivered on the thread that sends them, unless the callback is already running, in which
+/// case they are delivered in order once it returns.
+pub struct MidiLoopbackInput {
+    loopback: MidiLoopback,
+    key: u64,
+    started: Arc<AtomicBool>,
+}
+
+impl MidiLoopbackInput {
+    /// Returns the device info for the port.
+    pub fn get_device_info(&self) -> MidiDeviceInfo {
+        self.loopback.get_device_info()
+    }
+
+    /// Starts listening for MIDI input.
+    pub fn start(&mut self) {
+        self.started.store(true, Ordering::Release);
+    }
+
+    /// Stops listening for MIDI input.
+    pub fn stop(&mut self) {
+        self.started.store(false, Ordering::Release);
+    }
+}
+
+impl MidiInputPort for MidiLoopbackInput {
+    fn start(&mut self) {
+        MidiLoopbackInput::start(self);
+    }
+
+    fn stop(&mut self) {
+        MidiLoopbackInput::stop(self);
+    }
+
+    fn get_device_info(&self) -> MidiDeviceInfo {
+        MidiLoopbackInput::get_device_info(self)
+    }
+}
+
+impl MidiInputSource for MidiLoopback {
+    type Input = MidiLoopbackInput;
+
+    fn open_input(
+        &self,
+        callback: impl FnMut(&MidiMessage) + Send + 'static,
+    ) -> Option<Self::Input> {
+        Some(MidiLoopback::open_input(self, callback))
+    }
+}
+
+impl Drop for MidiLoopbackInput {
+    fn drop(&mut self) {
+        lock(&self.loopback.port.receivers).retain(|receiver| receiver.key != self.key);
+    }
+}
+
+struct ScheduledMessage {
+    due: f64,
+    message: MidiMessage,
+}
+
+#[derive(Default)]
+struct Queue {
+    messages: VecDeque<ScheduledMessage>,
+    running: bool,
+}
+
+/// Messages waiting to be sent by an output's background thread, ordered by the millisecond
+/// counter value at which they are due.
+#[derive(Default)]
+struct Schedule {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+}
+
+impl Schedule {
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        lock(&self.queue)
+    }
+
+    fn push(&self, due: f64, message: MidiMessage) {
+        let mut queue = self.lock();
+        let index = queue
+            .messages
+            .partition_point(|scheduled| scheduled.due <= due);
+        queue
+            .messages
+            .insert(index, ScheduledMessage { due, message });
+
+        drop(queue);
+        self.changed.notify_all();
+    }
+
+    fn set_running(&self, running: bool) {
+        self.lock().running = running;
+        self.changed.notify_all();
+    }
+
+    fn run(&self, port: &Port) {
+        let mut queue = self.lock();
+
+        while queue.running {
+            let now = Time::get_millisecond_counter_hi_res();
+
+            queue = match queue.messages.front().map(|scheduled| scheduled.due) {
+                Some(due) if due <= now => {
+                    let scheduled = queue.messages.pop_front();
+                    drop(queue);
+
+                    if let Some(scheduled) = scheduled {
+                        port.deliver(&scheduled.message);
+                    }
+
+                    self.lock()
+                }
+                Some(due) => {
+                    let timeout = Duration::from_secs_f64((due - now) * 0.001);
+                    self.changed
+                        .wait_timeout(queue, timeout)
+                        .unwrap_or_else(|error| error.into_inner())
+                        .0
+                }
+                None => self
+                    .changed
+                    .wait(queue)
+                    .unwrap_or_else(|error| error.into_inner()),
+            };
+        }
+    }
+}
+
+/// The sending end of a [`MidiLoopback`].
+///
+/// Messages sent with [`MidiOutputPort::send_message_now`] are delivered before the call
+/// returns. Blocks sent with [`MidiOutputPort::send_block_of_messages`] are queued and delivered
+/// at their due times while the background thread is running, as with a
+/// [`MidiOutput`](crate::juce_audio_devices::MidiOutput).
+pub struct MidiLoopbackOutput {
+    loopback: MidiLoopback,
+    schedule: Arc<Schedule>,
+    background_thread: Option<JoinHandle<()>>,
+}
+
+impl MidiLoopbackOutput {
+    /// Returns the device info for the port.
+    pub fn get_device_info(&self) -> MidiDeviceInfo {
+        self.loopback.get_device_info()
+    }
+
+    /// Returns the number of messages waiting to be sent by the background thread.
+    pub fn pending_message_count(&self) -> usize {
+        self.schedule.lock().messages.len()
+    }
+}
+
+impl MidiOutputPort for MidiLoopbackOutput {
+    fn send_message_now(&mut self, message: &MidiMessage) {
+        self.loopback.port.deliver(message);
+    }
+
+    fn send_block_of_messages_now(&mut self, buffer: &MidiBuffer) {
+        for message in buffer.iter() {
+            self.loopback.port.deliver(&message);
+        }
+    }
+
+    fn send_block_of_messages(
+        &mut self,
+        buffer: &MidiBuffer,
+        millisecond_counter_to_start_at: f64,
+        samples_per_second_for_buffer: f64,
+    ) {
+        let milliseconds_per_sample = 1000.0 / samples_per_second_for_buffer;
+
+        for message in buffer.iter() {
+            let due = millisecond_counter_to_start_at
+                + message.get_time_stamp() * milliseconds_per_sample;
+            self.schedule.push(due, message);
+        }
+    }
+
+    fn clear_all_pending_messages(&mut self) {
+        self.schedule.lock().messages.clear();
+    }
+
+    fn start_background_thread(&mut self) {
+        if self.background_thread.is_some() {
+            return;
+        }
+
+        self.schedule.set_running(true);
+        self.background_thread = Some(std::thread::spawn({
+            let schedule = Arc::clone(&self.schedule);
+            let port = Arc::clone(&self.loopback.port);
+            move || schedule.run(&port)
+        }));
+    }
+
+    fn stop_background_thread(&mut self) {
+        if let Some(thread) = self.background_thread.take() {
+            self.schedule.set_running(false);
+            let _ = thread.join();
+        }
+    }
+
+    fn is_background_thread_running(&self) -> bool {
+        self.background_thread.is_some()
+    }
+}
+
+impl Drop for MidiLoopbackOutput {
+    fn drop(&mut self) {
+        self.stop_background_thread();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn open_input(loopback: &MidiLoopback) -> (MidiLoopbackInput, mpsc::Receiver<MidiMessage>) {
+        let (sender, receiver) = mpsc::channel();
+        let input = loopback.open_input(move |message| {
+            let _ = sender.send(message.clone());
+        });
+        (input, receiver)
+    }
+
+    #[test]
+    fn messages_sent_now_arrive_with_the_current_time() {
+        let loopback = MidiLoopback::new("Loopback");
+        let (mut input, received) = open_input(&loopback);
+        let mut output = loopback.open_output();
+        input.start();
+
+        let before = Time::get_millisecond_counter_hi_res() * 0.001;
+        output.send_message_now(&MidiMessage::note_on(1, 60, 0.5));
+        let after = Time::get_millisecond_counter_hi_res() * 0.001;
+
+        let message = received.try_recv().unwrap();
+        assert_eq!(message.get_note_number(), 60);
+        assert!((before..=after).contains(&message.get_time_stamp()));
+    }
+
+    #[test]
+    fn only_started_inputs_receive_messages() {
+        let loopback = MidiLoopback::new("Loopback");
+        let (mut started, started_received) = open_input(&loopback);
+        let (_stopped, stopped_received) = open_input(&loopback);
+        let (mut dropped, dropped_received) = open_input(&loopback);
+        started.start();
+        dropped.start();
+        drop(dropped);
+
+        loopback
+            .open_output()
+            .send_message_now(&MidiMessage::note_on(1, 60, 0.5));
+
+        assert!(started_received.try_recv().is_ok());
+        assert!(stopped_received.try_recv().is_err());
+        assert!(dropped_received.try_recv().is_err());
+    }
+
+    #[test]
+    fn inputs_can_send_back_to_their_own_port() {
+        let loopback = MidiLoopback::new("Loopback");
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let mut input = loopback.open_input({
+            let mut echo = loopback.open_output();
+            let received = Arc::clone(&received);
+
+            move |message| {
+                received.lock().unwrap().push(message.get_channel());
+                if message.get_channel() == 1 {
+                    let mut echoed = message.clone();
+                    echoed.set_channel(2);
+                    echo.send_message_now(&echoed);
+                }
+            }
+        });
+        input.start();
+
+        loopback
+            .open_output()
+            .send_message_now(&MidiMessage::note_on(1, 60, 0.5));
+
+        assert_eq!(*received.lock().unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn scheduled_blocks_arrive_in_order_at_their_due_times() {
+        let loopback = MidiLoopback::new("Loopback");
+        let (mut input, received) = open_input(&loopback);
+        let mut output = loopback.open_output();
+        input.start();
+
+        let mut buffer = MidiBuffer::default();
+        buffer.add_event(&MidiMessage::note_on(1, 62, 0.5), 882);
+        buffer.add_event(&MidiMessage::note_on(1, 60, 0.5), 0);
+
+        let start = Time::get_millisecond_counter_hi_res() + 10.0;
+        output.send_block_of_messages(&buffer, start, 44100.0);
+        assert_eq!(output.pending_message_count(), 2);
+        output.start_background_thread();
+
+        let timeout = Duration::from_secs(1);
+        let first = received.recv_timeout(timeout).unwrap();
+        let second = received.recv_timeout(timeout).unwrap();
+
+        assert_eq!(first.get_note_number(), 60);
+        assert_eq!(second.get_note_number(), 62);
+        assert!(first.get_time_stamp() * 1000.0 >= start);
+        assert!(second.get_time_stamp() * 1000.0 >= start + 20.0);
+        assert_eq!(output.pending_message_count(), 0);
+    }
+
+    #[test]
+    fn cleared_messages_are_not_sent() {
+        let loopback = MidiLoopback::new("Loopback");
+        let (mut input, received) = open_input(&loopback);
+        let mut output = loopback.open_output();
+        input.start();
+
+        let mut buffer = MidiBuffer::default();
+        buffer.add_event(&MidiMessage::note_on(1, 60, 0.5), 0);
+
+        output.start_background_thread();
+        output.send_block_of_messages(
+            &buffer,
+            Time::get_millisecond_counter_hi_res() + 50.0,
+            44100.0,
+        );
+        output.clear_all_pending_messages();
+
+        assert!(received.recv_timeout(Duration::from_millis(100)).is_err());
+
+        output.stop_background_thread();
+        assert!(!output.is_background_thread_running());
+    }
+}
@@ -1,5 +1,106 @@
+use crate::juce_audio_devices::MidiScheduler;
+use cxx::UniquePtr;
+
 pub use juce::MidiOutput;
 
+// JUCE guards the pending message queue of a `MidiOutput` with a lock, so an open output can be
+// handed to another thread.
+unsafe impl Send for MidiOutput {}
+
+impl MidiOutput {
+    /// Creates a new virtual MIDI output device that other applications can receive messages
+    /// from.
+    ///
+    /// Returns a null pointer on platforms that don't support virtual devices (Windows and
+    /// Android) or if the device could not be created.
+    pub fn create_new_device(device_name: &juce::JuceString) -> UniquePtr<MidiOutput> {
+        juce::create_new_midi_output_device(device_name)
+    }
+}
+
+/// The sending side of a MIDI output.
+///
+/// This is implemented for open [`MidiOutput`] devices and for
+/// [`MidiLoopbackOutput`](crate::juce_audio_devices::MidiLoopbackOutput), so code that sends
+/// MIDI can be tested without any devices.
+pub trait MidiOutputPort: Send {
+    /// Sends a MIDI message immediately.
+    fn send_message_now(&mut self, message: &juce::MidiMessage);
+
+    /// Sends a block of MIDI messages immediately.
+    fn send_block_of_messages_now(&mut self, buffer: &juce::MidiBuffer);
+
+    /// Queues a block of MIDI messages to be sent by the background thread.
+    ///
+    /// Sample positions in the buffer are converted to times using `samples_per_second_for_buffer`,
+    /// relative to `millisecond_counter_to_start_at`.
+    fn send_block_of_messages(
+        &mut self,
+        buffer: &juce::MidiBuffer,
+        millisecond_counter_to_start_at: f64,
+        samples_per_second_for_buffer: f64,
+    );
+
+    /// Clears all pending messages from the output queue.
+    fn clear_all_pending_messages(&mut self);
+
+    /// Starts the background thread for timed message delivery.
+    fn start_background_thread(&mut self);
+
+    /// Stops the background thread.
+    fn stop_background_thread(&mut self);
+
+    /// Returns `true` if the background thread is running.
+    fn is_background_thread_running(&self) -> bool;
+
+    /// Hands the output to a [`MidiScheduler`] that sends events at scheduled times.
+    fn into_scheduler(self) -> MidiScheduler<Self>
+    where
+        Self: Sized + 'static,
+    {
+        MidiScheduler::new(self)
+    }
+}
+
+impl MidiOutputPort for UniquePtr<MidiOutput> {
+    fn send_message_now(&mut self, message: &juce::MidiMessage) {
+        self.pin_mut().send_message_now(message);
+    }
+
+    fn send_block_of_messages_now(&mut self, buffer: &juce::MidiBuffer) {
+        self.pin_mut().send_block_of_messages_now(buffer);
+    }
+
+    fn send_block_of_messages(
+        &mut self,
+        buffer: &juce::MidiBuffer,
+        millisecond_counter_to_start_at: f64,
+        samples_per_second_for_buffer: f64,
+    ) {
+        self.pin_mut().send_block_of_messages(
+            buffer,
+            millisecond_counter_to_start_at,
+            samples_per_second_for_buffer,
+        );
+    }
+
+    fn clear_all_pending_messages(&mut self) {
+        self.pin_mut().clear_all_pending_messages();
+    }
+
+    fn start_background_thread(&mut self) {
+        self.pin_mut().start_background_thread();
+    }
+
+    fn stop_background_thread(&mut self) {
+        self.pin_mut().stop_background_thread();
+    }
+
+    fn is_background_thread_running(&self) -> bool {
+        MidiOutput::is_background_thread_running(self)
+    }
+}
+
 #[cxx::bridge(namespace = "juce")]
 mod juce {
     unsafe extern "C++" {
@@ -28,6 +129,10 @@ mod juce {
         #[cxx_name = "openDevice"]
         fn open_device(device_identifier: &JuceString) -> UniquePtr<MidiOutput>;
 
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "createNewMidiOutputDevice"]
+        fn create_new_midi_output_device(device_name: &JuceString) -> UniquePtr<MidiOutput>;
+
         /// Returns the device info for this output.
         #[cxx_name = "getDeviceInfo"]
         fn get_device_info(self: &MidiOutput) -> MidiDeviceInfo;
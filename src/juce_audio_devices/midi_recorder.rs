@@ -0,0 +1,383 @@
+use crate::{
+    juce_audio_basics::{MidiFile, MidiMessage, MidiMessageSequence},
+    juce_audio_devices::{MidiInputPort, MidiInputSource, MidiInputWithCallback},
+    juce_core::Time,
+};
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+/// The messages captured by a [`MidiRecorder`], timestamped in seconds since recording started.
+struct Recording {
+    is_recording: bool,
+    start_time_ms: f64,
+    end_time: Option<f64>,
+    punch: Option<Range<f64>>,
+    messages: Vec<MidiMessage>,
+    held_notes: [[bool; 128]; 16],
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self {
+            is_recording: false,
+            start_time_ms: 0.0,
+            end_time: None,
+            punch: None,
+            messages: Vec::new(),
+            held_notes: [[false; 128]; 16],
+        }
+    }
+}
+
+impl Recording {
+    fn start(&mut self, now_ms: f64) {
+        self.is_recording = true;
+        self.start_time_ms = now_ms;
+        self.end_time = None;
+        self.messages.clear();
+        self.held_notes = [[false; 128]; 16];
+    }
+
+    fn stop(&mut self, now_ms: f64) {
+        if self.is_recording {
+            self.is_recording = false;
+            self.end_time = Some(self.elapsed(now_ms));
+        }
+    }
+
+    fn elapsed(&self, now_ms: f64) -> f64 {
+        ((now_ms - self.start_time_ms) * 0.001).max(0.0)
+    }
+
+    fn record(&mut self, message: &MidiMessage, now_ms: f64) {
+        if !self.is_recording || message.is_active_sense() {
+            return;
+        }
+
+        let time = self.elapsed(now_ms);
+        if let Some(punch) = &self.punch {
+            if !punch.contains(&time) {
+                return;
+            }
+        }
+
+        if message.is_note_on_or_off() {
+            let held = &mut self.held_notes[(message.get_channel() - 1) as usize]
+                [message.get_note_number() as usize];
+
+            if message.is_note_on(false) {
+                *held = true;
+            } else if std::mem::take(held) {
+                // Keep the note-off.
+            } else {
+                // The note started before punch-in, so there is no note-on to pair it with.
+                return;
+            }
+        }
+
+        let mut message = message.clone();
+        message.set_time_stamp(time);
+        self.messages.push(message);
+    }
+
+    /// The time at which notes that are still held get closed off.
+    fn closing_time(&self, now_ms: f64) -> f64 {
+        let end = self.end_time.unwrap_or_else(|| self.elapsed(now_ms));
+
+        match &self.punch {
+            Some(punch) => end.min(punch.end),
+            None => end,
+        }
+    }
+
+    fn to_sequence(&self, now_ms: f64, ticks_per_second: f64) -> MidiMessageSequence {
+        let mut sequence = MidiMessageSequence::default();
+        for message in &self.messages {
+            sequence.add_event(message, 0.0);
+        }
+
+        let closing_time = self.closing_time(now_ms);
+        for (channel, notes) in (1..).zip(&self.held_notes) {
+            for (note, _) in (0..).zip(notes).filter(|(_, &held)| held) {
+                let mut note_off = MidiMessage::note_off(channel, note, 0.0);
+                note_off.set_time_stamp(closing_time);
+                sequence.add_event(&note_off, 0.0);
+            }
+        }
+
+        for index in 0..sequence.len() {
+            if let Some(message) = sequence.get_mut(index) {
+                message.set_time_stamp((message.get_time_stamp() * ticks_per_second).round());
+            }
+        }
+
+        sequence.update_matched_pairs();
+        sequence
+    }
+}
+
+/// Records the messages arriving at a [`MidiInputPort`].
+///
+/// Messages are timestamped on arrival using [`Time::get_millisecond_counter_hi_res`] and can be
+/// converted into a [`MidiMessageSequence`] or [`MidiFile`] at any tempo. Active-sensing messages
+/// are discarded, and any notes still held when recording stops (or at the punch-out point) are
+/// closed with a note-off.
+pub struct MidiRecorder<Input: MidiInputPort = MidiInputWithCallback> {
+    input: Input,
+    recording: Arc<Mutex<Recording>>,
+}
+
+impl<Input: MidiInputPort> MidiRecorder<Input> {
+    /// Opens an input from `source`, such as a device identifier, for recording.
+    ///
+    /// Returns [`None`] if the input could not be opened.
+    pub fn open(source: &impl MidiInputSource<Input = Input>) -> Option<Self> {
+        let recording = Arc::new(Mutex::new(Recording::default()));
+
+        let input = source.open_input({
+            let recording = Arc::clone(&recording);
+            move |message| {
+                let now_ms = Time::get_millisecond_counter_hi_res();
+                lock(&recording).record(message, now_ms);
+            }
+        })?;
+
+        Some(Self { input, recording })
+    }
+
+    /// Returns the input being recorded.
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Starts a new recording, discarding anything recorded previously.
+    pub fn start(&mut self) {
+        lock(&self.recording).start(Time::get_millisecond_counter_hi_res());
+        self.input.start();
+    }
+
+    /// Stops recording.
+    pub fn stop(&mut self) {
+        self.input.stop();
+        lock(&self.recording).stop(Time::get_millisecond_counter_hi_res());
+    }
+
+    /// Returns `true` if the recorder is currently recording.
+    pub fn is_recording(&self) -> bool {
+        lock(&self.recording).is_recording
+    }
+
+    /// Restricts recording to a range of times, in seconds since recording started.
+    ///
+    /// Messages outside the range are ignored, as are note-offs for notes that started before
+    /// punch-in. Notes still held at punch-out are closed at the punch-out time.
+    pub fn set_punch(&self, punch: Option<Range<f64>>) {
+        lock(&self.recording).punch = punch;
+    }
+
+    /// Returns the current punch-in/out range.
+    pub fn punch(&self) -> Option<Range<f64>> {
+        lock(&self.recording).punch.clone()
+    }
+
+    /// Returns the number of messages recorded so far.
+    pub fn len(&self) -> usize {
+        lock(&self.recording).messages.len()
+    }
+
+    /// Returns `true` if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the recorded messages with timestamps in seconds since recording started.
+    pub fn to_sequence_in_seconds(&self) -> MidiMessageSequence {
+        lock(&self.recording).to_sequence(Time::get_millisecond_counter_hi_res(), 1.0)
+    }
+
+    /// Returns the recorded messages with timestamps in ticks at the given tempo.
+    pub fn to_sequence(&self, bpm: f64, ticks_per_quarter_note: i32) -> MidiMessageSequence {
+        lock(&self.recording).to_sequence(
+            Time::get_millisecond_counter_hi_res(),
+            ticks_per_second(bpm, ticks_per_quarter_note),
+        )
+    }
+
+    /// Returns the recording as a single-track MIDI file at the given tempo.
+    ///
+    /// The track starts with a tempo meta-event so that the file plays back at the recorded speed.
+    pub fn to_midi_file(&self, bpm: f64, ticks_per_quarter_note: i32) -> MidiFile {
+        recording_to_midi_file(
+            &self.to_sequence(bpm, ticks_per_quarter_note),
+            bpm,
+            ticks_per_quarter_note,
+        )
+    }
+}
+
+fn ticks_per_second(bpm: f64, ticks_per_quarter_note: i32) -> f64 {
+    bpm.max(f64::EPSILON) / 60.0 * f64::from(ticks_per_quarter_note.max(1))
+}
+
+fn recording_to_midi_file(
+    sequence: &MidiMessageSequence,
+    bpm: f64,
+    ticks_per_quarter_note: i32,
+) -> MidiFile {
+    let microseconds_per_quarter_note = (60_000_000.0 / bpm.max(f64::EPSILON)).round() as i32;
+
+    let mut track = MidiMessageSequence::default();
+    track.add_event(
+        &MidiMessage::tempo_meta_event(microseconds_per_quarter_note),
+        0.0,
+    );
+    track.add_sequence(sequence, 0.0);
+    track.update_matched_pairs();
+
+    let mut file = MidiFile::default();
+    file.set_ticks_per_quarter_note(ticks_per_quarter_note.max(1));
+    file.add_track(&track);
+    file
+}
+
+fn lock(recording: &Mutex<Recording>) -> MutexGuard<'_, Recording> {
+    recording.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::juce_audio_devices::{MidiLoopback, MidiOutputPort};
+
+    fn record(recording: &mut Recording, message: MidiMessage, time: f64) {
+        recording.record(&message, recording.start_time_ms + time * 1000.0);
+    }
+
+    fn times(sequence: &MidiMessageSequence) -> Vec<f64> {
+        sequence.iter().map(MidiMessage::get_time_stamp).collect()
+    }
+
+    #[test]
+    fn recorded_messages_are_converted_to_ticks() {
+        let mut recording = Recording::default();
+        recording.start(1000.0);
+
+        record(&mut recording, MidiMessage::note_on(1, 60, 0.5), 0.5);
+        record(&mut recording, MidiMessage::note_off(1, 60, 0.0), 1.0);
+        recording.stop(3000.0);
+
+        let sequence = recording.to_sequence(0.0, ticks_per_second(120.0, 480));
+
+        assert_eq!(times(&sequence), [480.0, 960.0]);
+        assert_eq!(sequence.get_index_of_matching_key_up(0), 1);
+    }
+
+    #[test]
+    fn active_sensing_is_discarded() {
+        let mut recording = Recording::default();
+        recording.start(0.0);
+
+        record(
+            &mut recording,
+            MidiMessage::from_bytes(&[0xfe]).unwrap(),
+            0.25,
+        );
+
+        assert!(recording.messages.is_empty());
+    }
+
+    #[test]
+    fn held_notes_are_closed_when_recording_stops() {
+        let mut recording = Recording::default();
+        recording.start(0.0);
+
+        record(&mut recording, MidiMessage::note_on(2, 64, 0.5), 0.25);
+        recording.stop(1500.0);
+
+        let sequence = recording.to_sequence(0.0, 1.0);
+
+        assert_eq!(times(&sequence), [0.25, 1.5]);
+        assert!(sequence.get(1).unwrap().is_note_off(true));
+        assert_eq!(sequence.get(1).unwrap().get_channel(), 2);
+    }
+
+    #[test]
+    fn punching_in_and_out() {
+        let mut recording = Recording {
+            punch: Some(1.0..2.0),
+            ..Recording::default()
+        };
+        recording.start(0.0);
+
+        record(&mut recording, MidiMessage::note_on(1, 60, 0.5), 0.5);
+        record(&mut recording, MidiMessage::note_off(1, 60, 0.0), 1.25);
+        record(&mut recording, MidiMessage::note_on(1, 62, 0.5), 1.5);
+        record(&mut recording, MidiMessage::note_off(1, 62, 0.0), 2.5);
+        recording.stop(3000.0);
+
+        let sequence = recording.to_sequence(0.0, 1.0);
+
+        assert_eq!(times(&sequence), [1.5, 2.0]);
+        assert!(sequence
+            .iter()
+            .all(|message| message.get_note_number() == 62));
+    }
+
+    #[test]
+    fn recordings_can_be_saved_as_midi_files() {
+        let mut recording = Recording::default();
+        recording.start(0.0);
+
+        record(&mut recording, MidiMessage::note_on(1, 60, 0.5), 0.0);
+        record(&mut recording, MidiMessage::note_off(1, 60, 0.0), 0.5);
+        recording.stop(1000.0);
+
+        let sequence = recording.to_sequence(0.0, ticks_per_second(60.0, 96));
+        let file = recording_to_midi_file(&sequence, 60.0, 96);
+
+        let file = MidiFile::from_bytes(&file.to_bytes(1).unwrap()).unwrap();
+        assert_eq!(file.get_time_format(), 96);
+
+        let mut tempo_events = MidiMessageSequence::default();
+        file.find_all_tempo_events(&mut tempo_events);
+        assert_eq!(
+            tempo_events
+                .get(0)
+                .unwrap()
+                .get_tempo_seconds_per_quarter_note(),
+            1.0
+        );
+
+        let track = file.get_track(0).unwrap();
+        let notes: Vec<_> = track
+            .iter()
+            .filter(|message| message.is_note_on_or_off())
+            .map(MidiMessage::get_time_stamp)
+            .collect();
+        assert_eq!(notes, [0.0, 48.0]);
+    }
+
+    #[test]
+    fn recording_from_a_loopback_input() {
+        let loopback = MidiLoopback::new("Loopback");
+        let mut output = loopback.open_output();
+        let mut recorder = MidiRecorder::open(&loopback).unwrap();
+
+        output.send_message_now(&MidiMessage::note_on(1, 60, 0.5));
+        recorder.start();
+        output.send_message_now(&MidiMessage::note_on(1, 62, 0.5));
+        output.send_message_now(&MidiMessage::note_off(1, 62, 0.0));
+        recorder.stop();
+        output.send_message_now(&MidiMessage::note_on(1, 64, 0.5));
+
+        let notes: Vec<_> = recorder
+            .to_sequence_in_seconds()
+            .iter()
+            .map(|message| (message.is_note_on(false), message.get_note_number()))
+            .collect();
+        assert_eq!(notes, [(true, 62), (false, 62)]);
+        assert!(!recorder.is_recording());
+    }
+}
@@ -0,0 +1,747 @@
+use crate::{
+    juce_audio_basics::MidiMessage,
+    juce_audio_devices::{MidiInputPort, MidiInputSource, MidiOutputPort},
+    utils::lock,
+};
+use std::{
+    collections::HashMap,
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+fn next_key() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A handle to a source of messages added to a [`MidiRouter`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MidiSourceHandle {
+    key: u64,
+}
+
+/// A handle to a destination added to a [`MidiRouter`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MidiDestinationHandle {
+    key: u64,
+}
+
+/// A handle to a route added to a [`MidiRouter`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MidiRouteHandle {
+    key: u64,
+}
+
+/// The kinds of message a [`MidiRoute`] can filter on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MidiMessageKind {
+    /// Note-on and note-off messages.
+    Note,
+    /// Polyphonic aftertouch messages.
+    Aftertouch,
+    /// Controller messages.
+    Controller,
+    /// Program change messages.
+    ProgramChange,
+    /// Channel-pressure messages.
+    ChannelPressure,
+    /// Pitch-wheel messages.
+    PitchWheel,
+    /// System messages, such as clock, transport, time code and system exclusive.
+    System,
+}
+
+impl MidiMessageKind {
+    /// Returns the kind of the given message.
+    pub fn of(message: &MidiMessage) -> Self {
+        if message.is_note_on_or_off() {
+            Self::Note
+        } else if message.is_aftertouch() {
+            Self::Aftertouch
+        } else if message.is_controller() {
+            Self::Controller
+        } else if message.is_program_change() {
+            Self::ProgramChange
+        } else if message.is_channel_pressure() {
+            Self::ChannelPressure
+        } else if message.is_pitch_wheel() {
+            Self::PitchWheel
+        } else {
+            Self::System
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Maps the velocity of note-on messages passing through a [`MidiRoute`].
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum VelocityCurve {
+    /// Leaves velocities unchanged.
+    #[default]
+    Linear,
+    /// Replaces every velocity with the given value (1-127).
+    Fixed(u8),
+    /// Raises the normalised velocity to the given power. Values below `1.0` make the response
+    /// more sensitive to soft playing, values above `1.0` less so.
+    Exponent(f32),
+    /// Scales velocities linearly into the given range.
+    Range(u8, u8),
+}
+
+impl VelocityCurve {
+    /// Applies the curve to a velocity in the range 1-127.
+    pub fn apply(&self, velocity: u8) -> u8 {
+        let velocity = velocity.clamp(1, 127);
+
+        let mapped = match *self {
+            Self::Linear => f32::from(velocity),
+            Self::Fixed(value) => f32::from(value),
+            Self::Exponent(exponent) => {
+                127.0 * (f32::from(velocity) / 127.0).powf(exponent.max(f32::EPSILON))
+            }
+            Self::Range(low, high) => {
+                let position = f32::from(velocity - 1) / 126.0;
+                f32::from(low) + position * (f32::from(high) - f32::from(low))
+            }
+        };
+
+        mapped.round().clamp(1.0, 127.0) as u8
+    }
+}
+
+/// A connection from a source to a destination in a [`MidiRouter`].
+///
+/// Messages are first checked against the route's filters (channel, message kind and note
+/// range), then transformed in the order: channel map, controller map, transpose and velocity
+/// curve.
+#[derive(Debug, Clone)]
+pub struct MidiRoute {
+    source: MidiSourceHandle,
+    destination: MidiDestinationHandle,
+    channels: u16,
+    kinds: u8,
+    note_range: RangeInclusive<i32>,
+    channel_map: [i32; 16],
+    controller_map: HashMap<i32, i32>,
+    transpose: i32,
+    velocity_curve: VelocityCurve,
+}
+
+impl MidiRoute {
+    /// Creates a route that passes every message from `source` to `destination` unchanged.
+    pub fn new(source: MidiSourceHandle, destination: MidiDestinationHandle) -> Self {
+        Self {
+            source,
+            destination,
+            channels: u16::MAX,
+            kinds: u8::MAX,
+            note_range: 0..=127,
+            channel_map: std::array::from_fn(|index| index as i32 + 1),
+            controller_map: HashMap::new(),
+            transpose: 0,
+            velocity_curve: VelocityCurve::Linear,
+        }
+    }
+
+    /// Returns the source of the route.
+    pub fn source(&self) -> MidiSourceHandle {
+        self.source
+    }
+
+    /// Returns the destination of the route.
+    pub fn destination(&self) -> MidiDestinationHandle {
+        self.destination
+    }
+
+    /// Returns `self` with only the given channels (1-16) passed through.
+    ///
+    /// System messages are not affected by the channel filter.
+    pub fn with_channels(mut self, channels: impl IntoIterator<Item = i32>) -> Self {
+        self.channels = channels
+            .into_iter()
+            .filter(|channel| (1..=16).contains(channel))
+            .fold(0, |mask, channel| mask | 1 << (channel - 1));
+        self
+    }
+
+    /// Returns `self` with only the given kinds of message passed through.
+    pub fn with_message_kinds(mut self, kinds: impl IntoIterator<Item = MidiMessageKind>) -> Self {
+        self.kinds = kinds.into_iter().fold(0, |mask, kind| mask | kind.bit());
+        self
+    }
+
+    /// Returns `self` with only notes in the given range passed through.
+    ///
+    /// The range also applies to polyphonic aftertouch.
+    pub fn with_note_range(mut self, range: RangeInclusive<i32>) -> Self {
+        self.note_range = range;
+        self
+    }
+
+    /// Returns `self` with messages on channel `from` sent out on channel `to`.
+    pub fn with_channel_map(mut self, from: i32, to: i32) -> Self {
+        if (1..=16).contains(&from) {
+            self.channel_map[(from - 1) as usize] = to.clamp(1, 16);
+        }
+        self
+    }
+
+    /// Returns `self` with every channel message sent out on the given channel.
+    pub fn with_output_channel(mut self, channel: i32) -> Self {
+        self.channel_map = [channel.clamp(1, 16); 16];
+        self
+    }
+
+    /// Returns `self` with controller number `from` sent out as controller `to`.
+    pub fn with_controller_map(mut self, from: i32, to: i32) -> Self {
+        self.controller_map.insert(from, to.clamp(0, 127));
+        self
+    }
+
+    /// Returns `self` with notes transposed by the given number of semitones.
+    ///
+    /// Notes that would be transposed out of the range 0-127 are dropped.
+    pub fn with_transpose(mut self, semitones: i32) -> Self {
+        self.transpose = semitones;
+        self
+    }
+
+    /// Returns `self` with the velocity of note-ons mapped through the given curve.
+    pub fn with_velocity_curve(mut self, curve: VelocityCurve) -> Self {
+        self.velocity_curve = curve;
+        self
+    }
+
+    fn accepts(&self, message: &MidiMessage) -> bool {
+        let kind = MidiMessageKind::of(message);
+        if self.kinds & kind.bit() == 0 {
+            return false;
+        }
+
+        let channel = message.get_channel();
+        if channel > 0 && self.channels & 1 << (channel - 1) == 0 {
+            return false;
+        }
+
+        !matches!(kind, MidiMessageKind::Note | MidiMessageKind::Aftertouch)
+            || self.note_range.contains(&message.get_note_number())
+    }
+
+    fn transform(&self, message: &MidiMessage) -> Option<MidiMessage> {
+        let mut message = message.clone();
+
+        let channel = message.get_channel();
+        if channel > 0 {
+            message.set_channel(self.channel_map[(channel - 1) as usize]);
+        }
+
+        if message.is_controller() {
+            if let Some(&controller) = self.controller_map.get(&message.get_controller_number()) {
+                let mut remapped = MidiMessage::controller_event(
+                    message.get_channel(),
+                    controller,
+                    message.get_controller_value(),
+                );
+                remapped.set_time_stamp(message.get_time_stamp());
+                message = remapped;
+            }
+        }
+
+        if message.is_note_on_or_off() || message.is_aftertouch() {
+            let note = message.get_note_number() + self.transpose;
+            if !(0..=127).contains(&note) {
+                return None;
+            }
+            message.set_note_number(note);
+        }
+
+        if message.is_note_on(false) {
+            let velocity = self.velocity_curve.apply(message.get_velocity());
+            message.set_velocity(f32::from(velocity) / 127.0);
+        }
+
+        Some(message)
+    }
+}
+
+/// A route along with the notes it is currently holding on.
+struct ActiveRoute {
+    route: MidiRoute,
+    held_notes: HashMap<(i32, i32), MidiMessage>,
+}
+
+impl ActiveRoute {
+    fn new(route: MidiRoute) -> Self {
+        Self {
+            route,
+            held_notes: HashMap::new(),
+        }
+    }
+
+    /// Routes a message, making sure each note-off goes out the same way as its note-on even
+    /// if the route has changed in between.
+    fn process(&mut self, message: &MidiMessage) -> Option<MidiMessage> {
+        if message.is_note_off(true) {
+            let key = (message.get_channel(), message.get_note_number());
+            let note_on = self.held_notes.remove(&key)?;
+            let mut note_off = MidiMessage::note_off(
+                note_on.get_channel(),
+                note_on.get_note_number(),
+                message.get_float_velocity(),
+            );
+            note_off.set_time_stamp(message.get_time_stamp());
+            return Some(note_off);
+        }
+
+        if !self.route.accepts(message) {
+            return None;
+        }
+
+        let transformed = self.route.transform(message)?;
+        if message.is_note_on(false) {
+            let key = (message.get_channel(), message.get_note_number());
+            self.held_notes.insert(key, transformed.clone());
+        }
+
+        Some(transformed)
+    }
+
+    /// Returns note-offs for every note the route is holding on.
+    fn release(&mut self) -> Vec<MidiMessage> {
+        self.held_notes
+            .drain()
+            .map(|(_, note_on)| {
+                MidiMessage::note_off(note_on.get_channel(), note_on.get_note_number(), 0.0)
+            })
+            .collect()
+    }
+}
+
+enum Destination {
+    Output(Box<dyn MidiOutputPort>),
+    Sink(Box<dyn FnMut(&MidiMessage) + Send>),
+}
+
+impl Destination {
+    fn send(&mut self, message: &MidiMessage) {
+        match self {
+            Self::Output(output) => output.send_message_now(message),
+            Self::Sink(sink) => sink(message),
+        }
+    }
+}
+
+/// Messages to send once the routing table has been unlocked, so that destinations can call
+/// back into the router and slow outputs don't hold up other threads.
+#[derive(Default)]
+#[must_use]
+struct Deliveries(Vec<(Arc<Mutex<Destination>>, MidiMessage)>);
+
+impl Deliveries {
+    fn send(self) {
+        for (destination, message) in self.0 {
+            lock(&destination).send(&message);
+        }
+    }
+}
+
+#[derive(Default)]
+struct RoutingTable {
+    routes: Vec<(MidiRouteHandle, ActiveRoute)>,
+    destinations: HashMap<MidiDestinationHandle, Arc<Mutex<Destination>>>,
+}
+
+impl RoutingTable {
+    fn dispatch(&mut self, source: MidiSourceHandle, message: &MidiMessage) -> Deliveries {
+        let Self {
+            routes,
+            destinations,
+        } = self;
+
+        let mut deliveries = Deliveries::default();
+
+        for (_, active) in routes.iter_mut() {
+            if active.route.source != source {
+                continue;
+            }
+
+            if let Some(message) = active.process(message) {
+                if let Some(destination) = destinations.get(&active.route.destination) {
+                    deliveries.0.push((Arc::clone(destination), message));
+                }
+            }
+        }
+
+        deliveries
+    }
+
+    fn release(&mut self, mut active: ActiveRoute, deliveries: &mut Deliveries) {
+        let notes = active.release();
+        if let Some(destination) = self.destinations.get(&active.route.destination) {
+            deliveries.0.extend(
+                notes
+                    .into_iter()
+                    .map(|note_off| (Arc::clone(destination), note_off)),
+            );
+        }
+    }
+
+    fn remove_routes(&mut self, mut predicate: impl FnMut(&MidiRoute) -> bool) -> Deliveries {
+        let (removed, kept) = std::mem::take(&mut self.routes)
+            .into_iter()
+            .partition(|(_, active)| predicate(&active.route));
+        self.routes = kept;
+
+        let mut deliveries = Deliveries::default();
+        for (_, active) in removed {
+            self.release(active, &mut deliveries);
+        }
+        deliveries
+    }
+}
+
+/// Connects MIDI inputs and in-process sources to MIDI outputs and in-process sinks.
+///
+/// Each [`MidiRoute`] carries its own filters and transforms. Routes can be added, replaced and
+/// removed while messages are flowing, without reopening any devices. When a route is replaced or
+/// removed, note-offs are sent for any notes it was holding on, and note-offs that arrive later
+/// for notes that started under an older configuration still follow their note-on.
+pub struct MidiRouter {
+    inputs: HashMap<MidiSourceHandle, Box<dyn MidiInputPort>>,
+    table: Arc<Mutex<RoutingTable>>,
+}
+
+impl Default for MidiRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MidiRouter {
+    /// Creates a router with no sources, destinations or routes.
+    pub fn new() -> Self {
+        Self {
+            inputs: HashMap::new(),
+            table: Arc::default(),
+        }
+    }
+
+    /// Opens an input from `input_source`, such as a device identifier, and adds it as a
+    /// source.
+    ///
+    /// Returns [`None`] if the input could not be opened.
+    pub fn add_input(&mut self, input_source: &impl MidiInputSource) -> Option<MidiSourceHandle> {
+        let source = MidiSourceHandle { key: next_key() };
+
+        let mut input = input_source.open_input({
+            let table = Arc::clone(&self.table);
+            move |message| {
+                let deliveries = lock(&table).dispatch(source, message);
+                deliveries.send();
+            }
+        })?;
+        input.start();
+
+        self.inputs.insert(source, Box::new(input));
+        Some(source)
+    }
+
+    /// Adds a source whose messages are supplied by calling [`MidiRouter::send`].
+    pub fn add_source(&self) -> MidiSourceHandle {
+        MidiSourceHandle { key: next_key() }
+    }
+
+    /// Removes a source, closing its device if it has one, along with every route from it.
+    pub fn remove_source(&mut self, source: MidiSourceHandle) {
+        // The input is closed before the table is locked, as its callback may be waiting on it.
+        drop(self.inputs.remove(&source));
+        let deliveries = lock(&self.table).remove_routes(|route| route.source == source);
+        deliveries.send();
+    }
+
+    /// Passes a message into the router as if it had arrived from `source`.
+    pub fn send(&self, source: MidiSourceHandle, message: &MidiMessage) {
+        let deliveries = lock(&self.table).dispatch(source, message);
+        deliveries.send();
+    }
+
+    /// Adds a MIDI output as a destination.
+    pub fn add_output(&self, output: impl MidiOutputPort + 'static) -> MidiDestinationHandle {
+        self.add_destination(Destination::Output(Box::new(output)))
+    }
+
+    /// Adds an in-process sink as a destination.
+    ///
+    /// The sink is called on the thread that delivered the message, which for device inputs is
+    /// the MIDI driver's thread.
+    pub fn add_sink(
+        &self,
+        sink: impl FnMut(&MidiMessage) + Send + 'static,
+    ) -> MidiDestinationHandle {
+        self.add_destination(Destination::Sink(Box::new(sink)))
+    }
+
+    fn add_destination(&self, destination: Destination) -> MidiDestinationHandle {
+        let handle = MidiDestinationHandle { key: next_key() };
+        lock(&self.table)
+            .destinations
+            .insert(handle, Arc::new(Mutex::new(destination)));
+        handle
+    }
+
+    /// Removes a destination along with every route to it.
+    pub fn remove_destination(&self, destination: MidiDestinationHandle) {
+        let deliveries = {
+            let mut table = lock(&self.table);
+            let deliveries = table.remove_routes(|route| route.destination == destination);
+            table.destinations.remove(&destination);
+            deliveries
+        };
+        deliveries.send();
+    }
+
+    /// Adds a route.
+    pub fn add_route(&self, route: MidiRoute) -> MidiRouteHandle {
+        let handle = MidiRouteHandle { key: next_key() };
+        lock(&self.table)
+            .routes
+            .push((handle, ActiveRoute::new(route)));
+        handle
+    }
+
+    /// Replaces the configuration of an existing route.
+    ///
+    /// Returns `false` if the route doesn't exist.
+    pub fn update_route(&self, handle: MidiRouteHandle, route: MidiRoute) -> bool {
+        let mut deliveries = Deliveries::default();
+
+        {
+            let mut table = lock(&self.table);
+
+            let Some((_, active)) = table.routes.iter_mut().find(|(key, _)| *key == handle) else {
+                return false;
+            };
+
+            if active.route.source == route.source && active.route.destination == route.destination
+            {
+                active.route = route;
+            } else {
+                let previous = std::mem::replace(active, ActiveRoute::new(route));
+                table.release(previous, &mut deliveries);
+            }
+        }
+
+        deliveries.send();
+        true
+    }
+
+    /// Removes a route, sending note-offs for any notes it was holding on.
+    pub fn remove_route(&self, handle: MidiRouteHandle) {
+        let mut deliveries = Deliveries::default();
+
+        {
+            let mut table = lock(&self.table);
+            if let Some(index) = table.routes.iter().position(|(key, _)| *key == handle) {
+                let (_, active) = table.routes.remove(index);
+                table.release(active, &mut deliveries);
+            }
+        }
+
+        deliveries.send();
+    }
+
+    /// Returns the configuration of a route, or [`None`] if it doesn't exist.
+    pub fn route(&self, handle: MidiRouteHandle) -> Option<MidiRoute> {
+        lock(&self.table)
+            .routes
+            .iter()
+            .find(|(key, _)| *key == handle)
+            .map(|(_, active)| active.route.clone())
+    }
+
+    /// Returns the handles of every route, in the order they were added.
+    pub fn routes(&self) -> Vec<MidiRouteHandle> {
+        lock(&self.table)
+            .routes
+            .iter()
+            .map(|(key, _)| *key)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::juce_audio_devices::MidiLoopback;
+
+    fn collect(router: &MidiRouter) -> (MidiDestinationHandle, Arc<Mutex<Vec<MidiMessage>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = router.add_sink({
+            let received = Arc::clone(&received);
+            move |message| received.lock().unwrap().push(message.clone())
+        });
+        (sink, received)
+    }
+
+    fn take(received: &Mutex<Vec<MidiMessage>>) -> Vec<MidiMessage> {
+        std::mem::take(&mut *received.lock().unwrap())
+    }
+
+    #[test]
+    fn routes_filter_messages() {
+        let router = MidiRouter::new();
+        let source = router.add_source();
+        let (sink, received) = collect(&router);
+
+        router.add_route(
+            MidiRoute::new(source, sink)
+                .with_channels([1])
+                .with_message_kinds([MidiMessageKind::Note])
+                .with_note_range(36..=48),
+        );
+
+        router.send(source, &MidiMessage::note_on(1, 40, 0.5));
+        router.send(source, &MidiMessage::note_on(1, 60, 0.5));
+        router.send(source, &MidiMessage::note_on(2, 40, 0.5));
+        router.send(source, &MidiMessage::controller_event(1, 7, 100));
+
+        let received = take(&received);
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].get_note_number(), 40);
+    }
+
+    #[test]
+    fn routes_transform_messages() {
+        let router = MidiRouter::new();
+        let source = router.add_source();
+        let (sink, received) = collect(&router);
+
+        router.add_route(
+            MidiRoute::new(source, sink)
+                .with_transpose(12)
+                .with_channel_map(1, 10)
+                .with_controller_map(1, 74)
+                .with_velocity_curve(VelocityCurve::Fixed(100)),
+        );
+
+        router.send(source, &MidiMessage::note_on(1, 60, 0.5));
+        router.send(source, &MidiMessage::note_on(2, 120, 0.5));
+        router.send(source, &MidiMessage::controller_event(1, 1, 64));
+
+        let received = take(&received);
+        assert_eq!(received.len(), 2);
+
+        assert_eq!(received[0].get_channel(), 10);
+        assert_eq!(received[0].get_note_number(), 72);
+        assert_eq!(received[0].get_velocity(), 100);
+
+        assert!(received[1].is_controller());
+        assert_eq!(received[1].get_channel(), 10);
+        assert_eq!(received[1].get_controller_number(), 74);
+        assert_eq!(received[1].get_controller_value(), 64);
+    }
+
+    #[test]
+    fn note_offs_follow_their_note_on_when_routes_change() {
+        let router = MidiRouter::new();
+        let source = router.add_source();
+        let (sink, received) = collect(&router);
+
+        let route = router.add_route(MidiRoute::new(source, sink).with_transpose(1));
+        router.send(source, &MidiMessage::note_on(1, 60, 0.5));
+
+        router.update_route(route, MidiRoute::new(source, sink).with_transpose(2));
+        router.send(source, &MidiMessage::note_off(1, 60, 0.0));
+
+        let notes: Vec<_> = take(&received)
+            .iter()
+            .map(MidiMessage::get_note_number)
+            .collect();
+        assert_eq!(notes, [61, 61]);
+    }
+
+    #[test]
+    fn removing_a_route_releases_held_notes() {
+        let router = MidiRouter::new();
+        let source = router.add_source();
+        let (sink, received) = collect(&router);
+
+        let route = router.add_route(MidiRoute::new(source, sink));
+        router.send(source, &MidiMessage::note_on(1, 60, 0.5));
+        router.remove_route(route);
+
+        let released = take(&received);
+        assert_eq!(released.len(), 2);
+        assert!(released[1].is_note_off(true));
+        assert!(router.routes().is_empty());
+
+        router.send(source, &MidiMessage::note_off(1, 60, 0.0));
+        assert!(take(&received).is_empty());
+    }
+
+    #[test]
+    fn velocity_curves() {
+        assert_eq!(VelocityCurve::Linear.apply(64), 64);
+        assert_eq!(VelocityCurve::Fixed(0).apply(64), 1);
+        assert_eq!(VelocityCurve::Exponent(2.0).apply(127), 127);
+        assert!(VelocityCurve::Exponent(2.0).apply(64) < 64);
+        assert_eq!(VelocityCurve::Range(40, 80).apply(1), 40);
+        assert_eq!(VelocityCurve::Range(40, 80).apply(127), 80);
+    }
+
+    #[test]
+    fn sinks_are_called_with_the_routing_table_unlocked() {
+        let router = MidiRouter::new();
+        let source = router.add_source();
+
+        let unlocked = Arc::new(Mutex::new(Vec::new()));
+        let sink = router.add_sink({
+            let table = Arc::clone(&router.table);
+            let unlocked = Arc::clone(&unlocked);
+            move |_| unlocked.lock().unwrap().push(table.try_lock().is_ok())
+        });
+
+        let route = router.add_route(MidiRoute::new(source, sink));
+        router.send(source, &MidiMessage::note_on(1, 60, 0.5));
+        router.remove_route(route);
+
+        assert_eq!(*unlocked.lock().unwrap(), [true, true]);
+    }
+
+    #[test]
+    fn routing_between_loopback_ports() {
+        let from = MidiLoopback::new("From");
+        let to = MidiLoopback::new("To");
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut receiver = to.open_input({
+            let received = Arc::clone(&received);
+            move |message| received.lock().unwrap().push(message.clone())
+        });
+        receiver.start();
+
+        let mut router = MidiRouter::new();
+        let source = router.add_input(&from).unwrap();
+        let destination = router.add_output(to.open_output());
+        router.add_route(MidiRoute::new(source, destination).with_transpose(12));
+
+        let mut sender = from.open_output();
+        sender.send_message_now(&MidiMessage::note_on(1, 60, 0.5));
+
+        let notes: Vec<_> = take(&received)
+            .iter()
+            .map(MidiMessage::get_note_number)
+            .collect();
+        assert_eq!(notes, [72]);
+
+        router.remove_source(source);
+        sender.send_message_now(&MidiMessage::note_on(1, 62, 0.5));
+        assert!(take(&received).is_empty());
+    }
+}
@@ -0,0 +1,527 @@
+use crate::{
+    juce_audio_basics::{MidiBuffer, MidiMessage},
+    juce_audio_devices::{MidiOutput, MidiOutputPort},
+    juce_core::Time,
+};
+use cxx::UniquePtr;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// How far ahead of their due time events are handed to the output's own queue.
+const LOOKAHEAD_MILLISECONDS: f64 = 50.0;
+
+/// The longest the scheduling thread sleeps between checks.
+const MAX_WAIT_MILLISECONDS: f64 = 100.0;
+
+/// The sample rate used to hand single events to the output, so that sample positions are
+/// milliseconds.
+const MILLISECONDS_PER_SECOND: f64 = 1000.0;
+
+static NEXT_TAG: AtomicU64 = AtomicU64::new(0);
+
+/// When a scheduled MIDI event should be sent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MidiEventTime {
+    /// At the given instant.
+    At(Instant),
+    /// After the given delay, measured from when the event is scheduled.
+    After(Duration),
+}
+
+impl From<Instant> for MidiEventTime {
+    fn from(instant: Instant) -> Self {
+        Self::At(instant)
+    }
+}
+
+impl From<Duration> for MidiEventTime {
+    fn from(offset: Duration) -> Self {
+        Self::After(offset)
+    }
+}
+
+impl MidiEventTime {
+    /// Converts the time to a value of [`Time::get_millisecond_counter_hi_res`], given the
+    /// instant and counter value for now.
+    fn to_millisecond_counter(self, now: Instant, now_milliseconds: f64) -> f64 {
+        match self {
+            Self::At(instant) if instant >= now => {
+                now_milliseconds + instant.duration_since(now).as_secs_f64() * 1000.0
+            }
+            Self::At(instant) => {
+                now_milliseconds - now.duration_since(instant).as_secs_f64() * 1000.0
+            }
+            Self::After(offset) => now_milliseconds + offset.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Identifies a group of events scheduled on a [`MidiScheduler`], so they can be cancelled
+/// together.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MidiEventTag(u64);
+
+impl MidiEventTag {
+    fn next() -> Self {
+        Self(NEXT_TAG.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+struct ScheduledEvent {
+    due: f64,
+    tag: MidiEventTag,
+    message: MidiMessage,
+}
+
+impl ScheduledEvent {
+    fn send_to(&self, output: &mut impl MidiOutputPort) {
+        let mut buffer = MidiBuffer::default();
+        buffer.add_event(&self.message, 0);
+        output.send_block_of_messages(&buffer, self.due, MILLISECONDS_PER_SECOND);
+    }
+}
+
+struct State<Output> {
+    output: Output,
+    /// Events that haven't been handed to the output yet, ordered by due time.
+    pending: Vec<ScheduledEvent>,
+    /// Events that have been handed to the output but aren't due yet.
+    queued: Vec<ScheduledEvent>,
+    running: bool,
+}
+
+impl<Output: MidiOutputPort> State<Output> {
+    fn insert(&mut self, event: ScheduledEvent) {
+        let index = self
+            .pending
+            .partition_point(|pending| pending.due <= event.due);
+        self.pending.insert(index, event);
+    }
+
+    /// Hands the events that fall due before `horizon` to the output, and returns when the next
+    /// one falls due.
+    fn queue_until(&mut self, now: f64, horizon: f64) -> Option<f64> {
+        self.queued.retain(|event| event.due > now);
+
+        let count = self.pending.partition_point(|event| event.due <= horizon);
+        for event in self.pending.drain(..count) {
+            event.send_to(&mut self.output);
+            self.queued.push(event);
+        }
+
+        self.pending.first().map(|event| event.due)
+    }
+
+    /// Removes the events matching `cancel`, returning how many were removed.
+    fn cancel(&mut self, now: f64, cancel: impl Fn(&ScheduledEvent) -> bool) -> usize {
+        let count = self.pending.len();
+        self.pending.retain(|event| !cancel(event));
+        let mut cancelled = count - self.pending.len();
+
+        self.queued.retain(|event| event.due > now);
+        if self.queued.iter().any(&cancel) {
+            let count = self.queued.len();
+            self.queued.retain(|event| !cancel(event));
+            cancelled += count - self.queued.len();
+
+            self.output.clear_all_pending_messages();
+            for event in &self.queued {
+                event.send_to(&mut self.output);
+            }
+        }
+
+        cancelled
+    }
+}
+
+struct Shared<Output> {
+    state: Mutex<State<Output>>,
+    changed: Condvar,
+}
+
+impl<Output> Shared<Output> {
+    fn lock(&self) -> MutexGuard<'_, State<Output>> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// Sends MIDI events to an output at scheduled times.
+///
+/// Events are scheduled from iterators of `(time, message)` pairs, where each time is either an
+/// [`Instant`] or a [`Duration`] from when the events are scheduled. Every call to
+/// [`MidiScheduler::schedule`] returns a [`MidiEventTag`] that can be used to cancel the events
+/// that haven't been sent yet.
+///
+/// The scheduler starts the output's background thread and hands it each event shortly before
+/// it is due, so the timing is as accurate as [`MidiOutput::send_block_of_messages`], while
+/// events further in the future can still be cancelled individually.
+pub struct MidiScheduler<Output: MidiOutputPort + 'static = UniquePtr<MidiOutput>> {
+    shared: Arc<Shared<Output>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<Output: MidiOutputPort + 'static> MidiScheduler<Output> {
+    /// Starts scheduling events on `output`.
+    pub fn new(mut output: Output) -> Self {
+        output.start_background_thread();
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                output,
+                pending: Vec::new(),
+                queued: Vec::new(),
+                running: true,
+            }),
+            changed: Condvar::new(),
+        });
+
+        let thread = std::thread::spawn({
+            let shared = Arc::clone(&shared);
+
+            move || {
+                let mut state = shared.lock();
+
+                while state.running {
+                    let now = Time::get_millisecond_counter_hi_res();
+                    let wait = state.queue_until(now, now + LOOKAHEAD_MILLISECONDS).map_or(
+                        MAX_WAIT_MILLISECONDS,
+                        |due| {
+                            (due - now - LOOKAHEAD_MILLISECONDS).clamp(1.0, MAX_WAIT_MILLISECONDS)
+                        },
+                    );
+
+                    state = shared
+                        .changed
+                        .wait_timeout(state, Duration::from_secs_f64(wait * 0.001))
+                        .unwrap_or_else(|error| error.into_inner())
+                        .0;
+                }
+            }
+        });
+
+        Self {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Schedules a group of events and returns a tag that identifies them.
+    pub fn schedule<When>(
+        &self,
+        events: impl IntoIterator<Item = (When, MidiMessage)>,
+    ) -> MidiEventTag
+    where
+        When: Into<MidiEventTime>,
+    {
+        let tag = MidiEventTag::next();
+        self.schedule_with_tag(tag, events);
+        tag
+    }
+
+    /// Schedules more events with the tag of an earlier group, so they are cancelled together.
+    pub fn schedule_with_tag<When>(
+        &self,
+        tag: MidiEventTag,
+        events: impl IntoIterator<Item = (When, MidiMessage)>,
+    ) where
+        When: Into<MidiEventTime>,
+    {
+        let now = Instant::now();
+        let now_milliseconds = Time::get_millisecond_counter_hi_res();
+
+        let mut state = self.shared.lock();
+        for (time, message) in events {
+            state.insert(ScheduledEvent {
+                due: time.into().to_millisecond_counter(now, now_milliseconds),
+                tag,
+                message,
+            });
+        }
+
+        drop(state);
+        self.shared.changed.notify_all();
+    }
+
+    /// Schedules the events in a buffer, with each event's sample position converted to a time
+    /// after `start` using the given sample rate.
+    ///
+    /// If the sample rate isn't positive, such as that of a device that isn't open yet, nothing
+    /// is scheduled and the returned tag has no events.
+    pub fn schedule_block(
+        &self,
+        start: impl Into<MidiEventTime>,
+        buffer: &MidiBuffer,
+        sample_rate: f64,
+    ) -> MidiEventTag {
+        if sample_rate <= 0.0 || sample_rate.is_nan() {
+            return MidiEventTag::next();
+        }
+
+        let start = start.into();
+        let offset = |samples: f64| Duration::from_secs_f64(samples.max(0.0) / sample_rate);
+
+        self.schedule(buffer.iter().map(|message| {
+            let time = match start {
+                MidiEventTime::At(instant) => {
+                    MidiEventTime::At(instant + offset(message.get_time_stamp()))
+                }
+                MidiEventTime::After(delay) => {
+                    MidiEventTime::After(delay + offset(message.get_time_stamp()))
+                }
+            };
+            (time, message)
+        }))
+    }
+
+    /// Cancels the events with the given tag that haven't been sent yet, returning how many
+    /// were cancelled.
+    ///
+    /// Events that are due within a few milliseconds may already be on their way and will
+    /// still be sent.
+    pub fn cancel(&self, tag: MidiEventTag) -> usize {
+        let now = Time::get_millisecond_counter_hi_res();
+        self.shared.lock().cancel(now, |event| event.tag == tag)
+    }
+
+    /// Cancels all events that haven't been sent yet.
+    pub fn cancel_all(&self) {
+        let mut state = self.shared.lock();
+        state.pending.clear();
+        state.queued.clear();
+        state.output.clear_all_pending_messages();
+    }
+
+    /// Returns the number of events that haven't been handed to the output yet.
+    pub fn pending_count(&self) -> usize {
+        self.shared.lock().pending.len()
+    }
+
+    /// Returns the number of events with the given tag that haven't been handed to the output
+    /// yet.
+    pub fn pending_count_for(&self, tag: MidiEventTag) -> usize {
+        let state = self.shared.lock();
+        state
+            .pending
+            .iter()
+            .filter(|event| event.tag == tag)
+            .count()
+    }
+
+    /// Sends a message immediately, bypassing the schedule.
+    pub fn send_now(&self, message: &MidiMessage) {
+        self.shared.lock().output.send_message_now(message);
+    }
+
+    /// Calls `f` with the output.
+    pub fn with_output<R>(&self, f: impl FnOnce(&mut Output) -> R) -> R {
+        f(&mut self.shared.lock().output)
+    }
+}
+
+impl<Output: MidiOutputPort + 'static> Drop for MidiScheduler<Output> {
+    fn drop(&mut self) {
+        self.shared.lock().running = false;
+        self.shared.changed.notify_all();
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        let mut state = self.shared.lock();
+        state.output.clear_all_pending_messages();
+        state.output.stop_background_thread();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::juce_audio_devices::{MidiLoopback, MidiLoopbackOutput};
+    use std::sync::mpsc;
+
+    /// Records the events handed to it along with their due times, so scheduling can be tested
+    /// without waiting on the clock.
+    #[derive(Default)]
+    struct RecordingOutput {
+        queued: Vec<(f64, i32)>,
+    }
+
+    impl MidiOutputPort for RecordingOutput {
+        fn send_message_now(&mut self, _message: &MidiMessage) {}
+
+        fn send_block_of_messages_now(&mut self, _buffer: &MidiBuffer) {}
+
+        fn send_block_of_messages(
+            &mut self,
+            buffer: &MidiBuffer,
+            millisecond_counter_to_start_at: f64,
+            _samples_per_second_for_buffer: f64,
+        ) {
+            for message in buffer.iter() {
+                self.queued
+                    .push((millisecond_counter_to_start_at, message.get_note_number()));
+            }
+        }
+
+        fn clear_all_pending_messages(&mut self) {
+            self.queued.clear();
+        }
+
+        fn start_background_thread(&mut self) {}
+
+        fn stop_background_thread(&mut self) {}
+
+        fn is_background_thread_running(&self) -> bool {
+            false
+        }
+    }
+
+    fn state(events: &[(f64, MidiEventTag, i32)]) -> State<RecordingOutput> {
+        let mut state = State {
+            output: RecordingOutput::default(),
+            pending: Vec::new(),
+            queued: Vec::new(),
+            running: true,
+        };
+
+        for &(due, tag, note) in events {
+            state.insert(ScheduledEvent {
+                due,
+                tag,
+                message: MidiMessage::note_on(1, note, 0.5),
+            });
+        }
+
+        state
+    }
+
+    #[test]
+    fn event_times_convert_to_the_millisecond_counter() {
+        let now = Instant::now();
+        let ms = Duration::from_millis;
+
+        let convert = |time: MidiEventTime| time.to_millisecond_counter(now, 1000.0);
+
+        assert_eq!(convert(ms(250).into()), 1250.0);
+        assert_eq!(convert((now + ms(100)).into()), 1100.0);
+        assert_eq!(convert((now - ms(100)).into()), 900.0);
+    }
+
+    #[test]
+    fn events_are_handed_to_the_output_in_time_order() {
+        let tag = MidiEventTag::next();
+        let mut state = state(&[
+            (20.0, tag, 62),
+            (0.0, tag, 60),
+            (80.0, tag, 63),
+            (10.0, tag, 61),
+        ]);
+
+        assert_eq!(state.queue_until(0.0, 50.0), Some(80.0));
+        assert_eq!(state.output.queued, [(0.0, 60), (10.0, 61), (20.0, 62)]);
+
+        assert_eq!(state.queue_until(30.0, 80.0), None);
+        assert_eq!(state.output.queued.last(), Some(&(80.0, 63)));
+    }
+
+    #[test]
+    fn cancelling_a_tag_only_removes_its_events() {
+        let kept = MidiEventTag::next();
+        let cancelled = MidiEventTag::next();
+        let mut state = state(&[
+            (200.0, kept, 60),
+            (200.0, cancelled, 61),
+            (400.0, cancelled, 62),
+        ]);
+
+        assert_eq!(state.cancel(0.0, |event| event.tag == cancelled), 2);
+
+        let notes: Vec<_> = state
+            .pending
+            .iter()
+            .map(|event| event.message.get_note_number())
+            .collect();
+        assert_eq!(notes, [60]);
+    }
+
+    #[test]
+    fn cancelling_removes_events_already_handed_to_the_output() {
+        let kept = MidiEventTag::next();
+        let cancelled = MidiEventTag::next();
+        let mut state = state(&[(5.0, kept, 59), (40.0, kept, 60), (40.0, cancelled, 61)]);
+
+        state.queue_until(0.0, 50.0);
+        assert!(state.pending.is_empty());
+
+        // The event due at 5ms has already been sent, so it isn't queued again.
+        assert_eq!(state.cancel(10.0, |event| event.tag == cancelled), 1);
+        assert_eq!(state.output.queued, [(40.0, 60)]);
+    }
+
+    #[test]
+    fn blocks_are_scheduled_by_sample_position() {
+        let scheduler = MidiScheduler::new(RecordingOutput::default());
+
+        let mut buffer = MidiBuffer::default();
+        buffer.add_event(&MidiMessage::note_on(1, 61, 0.5), 441);
+        buffer.add_event(&MidiMessage::note_on(1, 60, 0.5), 0);
+
+        // Far enough ahead that nothing is handed to the output while the test runs.
+        scheduler.schedule_block(Duration::from_secs(3600), &buffer, 44100.0);
+
+        let state = scheduler.shared.lock();
+        let events: Vec<_> = state
+            .pending
+            .iter()
+            .map(|event| (event.due, event.message.get_note_number()))
+            .collect();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].1, 60);
+        assert_eq!(events[1].1, 61);
+        assert!((events[1].0 - events[0].0 - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn blocks_without_a_sample_rate_are_ignored() {
+        let scheduler = MidiScheduler::new(RecordingOutput::default());
+
+        let mut buffer = MidiBuffer::default();
+        buffer.add_event(&MidiMessage::note_on(1, 60, 0.5), 441);
+
+        for sample_rate in [0.0, -44100.0, f64::NAN] {
+            let tag = scheduler.schedule_block(Duration::ZERO, &buffer, sample_rate);
+            assert_eq!(scheduler.pending_count_for(tag), 0);
+        }
+        assert_eq!(scheduler.pending_count(), 0);
+    }
+
+    #[test]
+    fn events_reach_a_loopback_output() {
+        let loopback = MidiLoopback::new("Loopback");
+        let (sender, received) = mpsc::channel();
+        let mut input = loopback.open_input(move |message| {
+            let _ = sender.send(message.get_note_number());
+        });
+        input.start();
+
+        let scheduler: MidiScheduler<MidiLoopbackOutput> =
+            MidiScheduler::new(loopback.open_output());
+        scheduler.schedule([
+            (Duration::from_millis(2), MidiMessage::note_on(1, 62, 0.5)),
+            (Duration::ZERO, MidiMessage::note_on(1, 60, 0.5)),
+            (Duration::from_millis(1), MidiMessage::note_on(1, 61, 0.5)),
+        ]);
+
+        let notes: Vec<_> = (0..3)
+            .map(|_| received.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        assert_eq!(notes, [60, 61, 62]);
+    }
+}
@@ -0,0 +1,683 @@
+use crate::{
+    juce_audio_basics::{MidiBuffer, MidiFile, MidiMessage, MidiMessageSequence},
+    juce_audio_devices::{MidiOutput, MidiOutputPort},
+    juce_core::Time,
+};
+use cxx::UniquePtr;
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+const DEFAULT_SECONDS_PER_QUARTER_NOTE: f64 = 0.5;
+
+#[derive(Debug, Copy, Clone)]
+struct TempoChange {
+    tick: f64,
+    seconds: f64,
+    seconds_per_tick: f64,
+}
+
+/// Converts between ticks and seconds using the tempo changes in a [`MidiFile`].
+#[derive(Debug, Clone)]
+struct TempoMap {
+    ticks_per_quarter_note: Option<f64>,
+    changes: Vec<TempoChange>,
+}
+
+impl TempoMap {
+    fn new(file: &MidiFile) -> Self {
+        let time_format = file.get_time_format();
+
+        if time_format < 0 {
+            let frames_per_second = f64::from(-(time_format >> 8));
+            let ticks_per_frame = f64::from(time_format & 0xff);
+            let ticks_per_second = (frames_per_second * ticks_per_frame).max(1.0);
+
+            return Self {
+                ticks_per_quarter_note: None,
+                changes: vec![TempoChange {
+                    tick: 0.0,
+                    seconds: 0.0,
+                    seconds_per_tick: 1.0 / ticks_per_second,
+                }],
+            };
+        }
+
+        let ticks_per_quarter_note = f64::from(time_format.max(1));
+        let mut tempo_events = MidiMessageSequence::default();
+        file.find_all_tempo_events(&mut tempo_events);
+
+        let mut changes = vec![TempoChange {
+            tick: 0.0,
+            seconds: 0.0,
+            seconds_per_tick: DEFAULT_SECONDS_PER_QUARTER_NOTE / ticks_per_quarter_note,
+        }];
+
+        for event in &tempo_events {
+            let previous = changes[changes.len() - 1];
+            let tick = event.get_time_stamp().max(previous.tick);
+            let change = TempoChange {
+                tick,
+                seconds: previous.seconds + (tick - previous.tick) * previous.seconds_per_tick,
+                seconds_per_tick: event.get_tempo_seconds_per_quarter_note()
+                    / ticks_per_quarter_note,
+            };
+
+            if tick == previous.tick {
+                changes.pop();
+            }
+            changes.push(change);
+        }
+
+        Self {
+            ticks_per_quarter_note: Some(ticks_per_quarter_note),
+            changes,
+        }
+    }
+
+    fn fixed_tempo(&self, bpm: Option<f64>) -> Option<f64> {
+        let ticks_per_quarter_note = self.ticks_per_quarter_note?;
+        bpm.filter(|bpm| *bpm > 0.0)
+            .map(|bpm| 60.0 / bpm / ticks_per_quarter_note)
+    }
+
+    fn ticks_to_seconds(&self, tick: f64, tempo_override: Option<f64>) -> f64 {
+        if let Some(seconds_per_tick) = self.fixed_tempo(tempo_override) {
+            return tick * seconds_per_tick;
+        }
+
+        let change = self
+            .changes
+            .iter()
+            .rev()
+            .find(|change| change.tick <= tick)
+            .unwrap_or(&self.changes[0]);
+
+        change.seconds + (tick - change.tick) * change.seconds_per_tick
+    }
+
+    fn seconds_to_ticks(&self, seconds: f64, tempo_override: Option<f64>) -> f64 {
+        if let Some(seconds_per_tick) = self.fixed_tempo(tempo_override) {
+            return seconds / seconds_per_tick;
+        }
+
+        let change = self
+            .changes
+            .iter()
+            .rev()
+            .find(|change| change.seconds <= seconds)
+            .unwrap_or(&self.changes[0]);
+
+        change.tick + (seconds - change.seconds) / change.seconds_per_tick
+    }
+}
+
+/// Plays back the contents of a [`MidiFile`] in real time.
+///
+/// The sequencer does not keep time by itself. It is driven by calls to
+/// [`MidiSequencer::advance`] or [`MidiSequencer::render_block`], which makes it suitable for
+/// feeding a synthesiser from an audio callback. Use [`MidiFilePlayer`] to play a file to a
+/// [`MidiOutputPort`].
+///
+/// The playhead is stored as a musical position, so changing the tempo override while playing
+/// does not make it jump.
+pub struct MidiSequencer {
+    events: MidiMessageSequence,
+    tempo_map: TempoMap,
+    tempo_override: Option<f64>,
+    end_tick: f64,
+    position: f64,
+    loop_range: Option<Range<f64>>,
+    playing: bool,
+    active_notes: [[bool; 128]; 16],
+    pending: Vec<MidiMessage>,
+}
+
+impl MidiSequencer {
+    /// Creates a sequencer that plays all the tracks of the given file.
+    pub fn new(file: &MidiFile) -> Self {
+        let events = MidiMessageSequence::merge(file.tracks());
+        let end_tick = events.get_end_time().max(0.0);
+
+        Self {
+            events,
+            tempo_map: TempoMap::new(file),
+            tempo_override: None,
+            end_tick,
+            position: 0.0,
+            loop_range: None,
+            playing: false,
+            active_notes: [[false; 128]; 16],
+            pending: Vec::new(),
+        }
+    }
+
+    /// Starts playback from the current position.
+    pub fn play(&mut self) {
+        if self.position >= self.end_tick && self.loop_range.is_none() {
+            self.position = 0.0;
+        }
+
+        self.playing = true;
+    }
+
+    /// Stops playback, leaving the playhead where it is.
+    ///
+    /// Note-offs for any sounding notes are delivered by the next call to
+    /// [`MidiSequencer::advance`].
+    pub fn stop(&mut self) {
+        self.playing = false;
+        self.release_active_notes();
+    }
+
+    /// Returns `true` if the sequencer is playing.
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Moves the playhead to the given position in seconds.
+    ///
+    /// While looping, positions past the end of the loop are wrapped back into it.
+    pub fn seek(&mut self, seconds: f64) {
+        self.position = self.seconds_to_ticks(seconds.max(0.0));
+        self.wrap_into_loop();
+        self.release_active_notes();
+    }
+
+    /// Returns the position of the playhead in seconds.
+    pub fn position(&self) -> f64 {
+        self.ticks_to_seconds(self.position)
+    }
+
+    /// Returns the length of the sequence in seconds.
+    pub fn length(&self) -> f64 {
+        self.ticks_to_seconds(self.end_tick)
+    }
+
+    /// Sets a range, in seconds, to play repeatedly, or [`None`] to stop looping.
+    ///
+    /// If the playhead is past the end of the range, it is wrapped back into it.
+    pub fn set_loop(&mut self, range: Option<Range<f64>>) {
+        self.loop_range = range
+            .map(|range| self.seconds_to_ticks(range.start)..self.seconds_to_ticks(range.end))
+            .filter(|range| range.start < range.end);
+        self.wrap_into_loop();
+    }
+
+    /// Plays the sequence at a fixed tempo in beats per minute, or [`None`] to follow the tempo
+    /// map of the file.
+    ///
+    /// The override has no effect on files that use SMPTE timing.
+    pub fn set_tempo_override(&mut self, bpm: Option<f64>) {
+        self.tempo_override = bpm;
+    }
+
+    /// Returns the current tempo override.
+    pub fn tempo_override(&self) -> Option<f64> {
+        self.tempo_override
+    }
+
+    /// Advances the playhead by the given number of seconds.
+    ///
+    /// The callback is called for every message that falls within that time, along with its
+    /// offset in seconds from the start of the call.
+    pub fn advance(&mut self, seconds: f64, mut callback: impl FnMut(&MidiMessage, f64)) {
+        for message in std::mem::take(&mut self.pending) {
+            callback(&message, 0.0);
+        }
+
+        let mut elapsed = 0.0;
+        while self.playing && elapsed < seconds {
+            let start_tick = self.position;
+            let start_time = self.ticks_to_seconds(start_tick);
+            let mut end_tick = self.seconds_to_ticks(start_time + seconds - elapsed);
+
+            // Rounding can leave a remainder too small to move the playhead.
+            if end_tick <= start_tick {
+                break;
+            }
+
+            let loop_end = self
+                .loop_range
+                .as_ref()
+                .filter(|range| start_tick < range.end && end_tick >= range.end)
+                .map(|range| range.end);
+
+            if let Some(loop_end) = loop_end {
+                end_tick = loop_end;
+            }
+
+            let mut index = self.events.get_next_index_at_time(start_tick);
+            while let Some(message) = self.events.get(index) {
+                let tick = message.get_time_stamp();
+                if tick >= end_tick {
+                    break;
+                }
+
+                if !message.is_meta_event() {
+                    let offset = elapsed + self.ticks_to_seconds(tick) - start_time;
+                    Self::track_note(&mut self.active_notes, message);
+                    callback(message, offset);
+                }
+
+                index += 1;
+            }
+
+            elapsed += self.ticks_to_seconds(end_tick) - start_time;
+            self.position = end_tick;
+
+            if let Some(range) = loop_end.and(self.loop_range.clone()) {
+                self.release_active_notes();
+                self.position = range.start;
+            } else if self.position >= self.end_tick && self.loop_range.is_none() {
+                self.playing = false;
+                self.release_active_notes();
+            }
+
+            for message in std::mem::take(&mut self.pending) {
+                callback(&message, elapsed);
+            }
+        }
+    }
+
+    /// Advances the playhead by one audio block, adding the messages to a [`MidiBuffer`] at
+    /// their sample positions within the block.
+    pub fn render_block(&mut self, buffer: &mut MidiBuffer, num_samples: i32, sample_rate: f64) {
+        if num_samples <= 0 || sample_rate <= 0.0 {
+            return;
+        }
+
+        let last_sample = num_samples - 1;
+        self.advance(f64::from(num_samples) / sample_rate, |message, offset| {
+            let sample = ((offset * sample_rate) as i32).clamp(0, last_sample);
+            buffer.add_event(message, sample);
+        });
+    }
+
+    fn wrap_into_loop(&mut self) {
+        if let Some(range) = &self.loop_range {
+            if self.position >= range.end {
+                self.position =
+                    range.start + (self.position - range.start) % (range.end - range.start);
+            }
+        }
+    }
+
+    fn rewind(&mut self, seconds: f64) {
+        let position = (self.position() - seconds).max(0.0);
+        self.position = self.seconds_to_ticks(position);
+    }
+
+    fn ticks_to_seconds(&self, tick: f64) -> f64 {
+        self.tempo_map.ticks_to_seconds(tick, self.tempo_override)
+    }
+
+    fn seconds_to_ticks(&self, seconds: f64) -> f64 {
+        self.tempo_map
+            .seconds_to_ticks(seconds, self.tempo_override)
+    }
+
+    fn track_note(active_notes: &mut [[bool; 128]; 16], message: &MidiMessage) {
+        let channel = (message.get_channel() - 1) as usize;
+        let note = message.get_note_number() as usize;
+
+        if message.is_note_on(false) {
+            active_notes[channel][note] = true;
+        } else if message.is_note_off(true) {
+            active_notes[channel][note] = false;
+        }
+    }
+
+    fn release_active_notes(&mut self) {
+        for (channel, notes) in self.active_notes.iter_mut().enumerate() {
+            for (note, active) in notes.iter_mut().enumerate() {
+                if std::mem::take(active) {
+                    self.pending
+                        .push(MidiMessage::note_off(channel as i32 + 1, note as i32, 0.0));
+                }
+            }
+        }
+    }
+}
+
+struct PlayerState<Output> {
+    sequencer: MidiSequencer,
+    output: Output,
+    next_block: f64,
+}
+
+impl<Output: MidiOutputPort> PlayerState<Output> {
+    /// Removes anything scheduled on the output that has not been sent yet, moving the playhead
+    /// back to match.
+    fn discard_scheduled(&mut self) {
+        let now = Time::get_millisecond_counter_hi_res();
+        let unsent = (self.next_block - now).max(0.0);
+
+        self.output.clear_all_pending_messages();
+
+        if self.sequencer.is_playing() {
+            self.sequencer.rewind(unsent / 1000.0);
+        }
+
+        self.next_block = now;
+    }
+
+    /// Sends any note-offs owed by the sequencer straight away.
+    fn flush(&mut self) {
+        let output = &mut self.output;
+        self.sequencer.advance(0.0, |message, _| {
+            output.send_message_now(message);
+        });
+    }
+
+    fn schedule(&mut self) {
+        const BLOCK_MILLISECONDS: f64 = 5.0;
+        const LOOKAHEAD_MILLISECONDS: f64 = 20.0;
+        const SAMPLES_PER_SECOND: f64 = 48000.0;
+
+        let now = Time::get_millisecond_counter_hi_res();
+
+        if !self.sequencer.is_playing() {
+            self.flush();
+            self.next_block = now;
+            return;
+        }
+
+        self.next_block = self.next_block.max(now);
+
+        let mut buffer = MidiBuffer::default();
+        while self.next_block < now + LOOKAHEAD_MILLISECONDS {
+            buffer.clear();
+            self.sequencer.render_block(
+                &mut buffer,
+                (BLOCK_MILLISECONDS * SAMPLES_PER_SECOND / 1000.0) as i32,
+                SAMPLES_PER_SECOND,
+            );
+
+            if !buffer.is_empty() {
+                self.output
+                    .send_block_of_messages(&buffer, self.next_block, SAMPLES_PER_SECOND);
+            }
+
+            self.next_block += BLOCK_MILLISECONDS;
+        }
+    }
+}
+
+/// Plays a [`MidiFile`] to a [`MidiOutputPort`] from a background thread.
+///
+/// Messages are scheduled slightly ahead of time using the output's background thread, so
+/// that playback timing does not depend on how promptly the player's own thread is woken.
+pub struct MidiFilePlayer<Output: MidiOutputPort + 'static = UniquePtr<MidiOutput>> {
+    state: Arc<Mutex<PlayerState<Output>>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<Output: MidiOutputPort + 'static> MidiFilePlayer<Output> {
+    /// Creates a player for the given file that sends its messages to `output`.
+    ///
+    /// Playback does not begin until [`MidiFilePlayer::play`] is called.
+    pub fn new(file: &MidiFile, mut output: Output) -> Self {
+        if !output.is_background_thread_running() {
+            output.start_background_thread();
+        }
+
+        let state = Arc::new(Mutex::new(PlayerState {
+            sequencer: MidiSequencer::new(file),
+            output,
+            next_block: Time::get_millisecond_counter_hi_res(),
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread = std::thread::spawn({
+            let state = Arc::clone(&state);
+            let running = Arc::clone(&running);
+
+            move || {
+                while running.load(Ordering::Acquire) {
+                    if let Ok(mut state) = state.lock() {
+                        state.schedule();
+                    }
+
+                    std::thread::sleep(Duration::from_millis(2));
+                }
+            }
+        });
+
+        Self {
+            state,
+            running,
+            thread: Some(thread),
+        }
+    }
+
+    /// Starts playback from the current position.
+    pub fn play(&self) {
+        let mut state = self.lock();
+        state.next_block = Time::get_millisecond_counter_hi_res();
+        state.sequencer.play();
+    }
+
+    /// Stops playback, silencing any sounding notes.
+    pub fn stop(&self) {
+        let mut state = self.lock();
+        state.discard_scheduled();
+        state.sequencer.stop();
+        state.flush();
+    }
+
+    /// Returns `true` if the player is playing.
+    pub fn is_playing(&self) -> bool {
+        self.lock().sequencer.is_playing()
+    }
+
+    /// Moves the playhead to the given position in seconds.
+    pub fn seek(&self, seconds: f64) {
+        let mut state = self.lock();
+        state.discard_scheduled();
+        state.sequencer.seek(seconds);
+        state.flush();
+    }
+
+    /// Returns the position of the playhead in seconds.
+    pub fn position(&self) -> f64 {
+        self.lock().sequencer.position()
+    }
+
+    /// Returns the length of the file in seconds.
+    pub fn length(&self) -> f64 {
+        self.lock().sequencer.length()
+    }
+
+    /// Sets a range, in seconds, to play repeatedly, or [`None`] to stop looping.
+    pub fn set_loop(&self, range: Option<Range<f64>>) {
+        let mut state = self.lock();
+        state.discard_scheduled();
+        state.sequencer.set_loop(range);
+    }
+
+    /// Plays the file at a fixed tempo in beats per minute, or [`None`] to follow the tempo map
+    /// of the file.
+    pub fn set_tempo_override(&self, bpm: Option<f64>) {
+        let mut state = self.lock();
+        state.discard_scheduled();
+        state.sequencer.set_tempo_override(bpm);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PlayerState<Output>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<Output: MidiOutputPort + 'static> Drop for MidiFilePlayer<Output> {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::juce_audio_devices::MidiLoopback;
+    use std::sync::mpsc;
+
+    fn file_with_notes(notes: &[(i32, f64, f64)]) -> MidiFile {
+        let mut track = MidiMessageSequence::default();
+        for &(note, start, end) in notes {
+            track.add_event(&MidiMessage::note_on(1, note, 0.8), start);
+            track.add_event(&MidiMessage::note_off(1, note, 0.0), end);
+        }
+
+        let mut file = MidiFile::default();
+        file.set_ticks_per_quarter_note(96);
+        file.add_track(&track);
+        file
+    }
+
+    fn collect(sequencer: &mut MidiSequencer, seconds: f64) -> Vec<(bool, i32, f64)> {
+        let mut messages = vec![];
+        sequencer.advance(seconds, |message, offset| {
+            messages.push((message.is_note_on(false), message.get_note_number(), offset));
+        });
+        messages
+    }
+
+    #[test]
+    fn playing_at_the_default_tempo() {
+        let file = file_with_notes(&[(60, 0.0, 96.0), (62, 96.0, 192.0)]);
+        let mut sequencer = MidiSequencer::new(&file);
+        assert_eq!(sequencer.length(), 1.0);
+
+        assert!(collect(&mut sequencer, 1.0).is_empty());
+
+        sequencer.play();
+        assert_eq!(
+            collect(&mut sequencer, 0.75),
+            [(true, 60, 0.0), (false, 60, 0.5), (true, 62, 0.5)]
+        );
+        assert_eq!(collect(&mut sequencer, 0.75), [(false, 62, 0.25)]);
+        assert!(!sequencer.is_playing());
+    }
+
+    #[test]
+    fn overriding_the_tempo() {
+        let file = file_with_notes(&[(60, 0.0, 96.0)]);
+        let mut sequencer = MidiSequencer::new(&file);
+        sequencer.set_tempo_override(Some(60.0));
+        sequencer.play();
+
+        assert_eq!(
+            collect(&mut sequencer, 2.0),
+            [(true, 60, 0.0), (false, 60, 1.0)]
+        );
+    }
+
+    #[test]
+    fn stopping_releases_sounding_notes() {
+        let file = file_with_notes(&[(60, 0.0, 192.0)]);
+        let mut sequencer = MidiSequencer::new(&file);
+        sequencer.play();
+
+        assert_eq!(collect(&mut sequencer, 0.25), [(true, 60, 0.0)]);
+
+        sequencer.stop();
+        assert_eq!(collect(&mut sequencer, 0.25), [(false, 60, 0.0)]);
+        assert_eq!(sequencer.position(), 0.25);
+    }
+
+    #[test]
+    fn looping_a_range() {
+        let file = file_with_notes(&[(60, 0.0, 48.0), (62, 96.0, 144.0)]);
+        let mut sequencer = MidiSequencer::new(&file);
+        sequencer.set_loop(Some(0.0..0.5));
+        sequencer.play();
+
+        assert_eq!(
+            collect(&mut sequencer, 1.0),
+            [
+                (true, 60, 0.0),
+                (false, 60, 0.25),
+                (true, 60, 0.5),
+                (false, 60, 0.75)
+            ]
+        );
+        assert!(sequencer.is_playing());
+    }
+
+    #[test]
+    fn seeking_past_the_loop_wraps_into_it() {
+        let file = file_with_notes(&[(60, 0.0, 48.0), (62, 96.0, 144.0)]);
+        let mut sequencer = MidiSequencer::new(&file);
+        sequencer.set_loop(Some(0.0..0.5));
+        sequencer.seek(1.375);
+        assert_eq!(sequencer.position(), 0.375);
+
+        sequencer.play();
+        assert_eq!(collect(&mut sequencer, 0.25), [(true, 60, 0.125)]);
+        assert_eq!(sequencer.position(), 0.125);
+
+        sequencer.seek(0.375);
+        sequencer.set_loop(Some(0.125..0.25));
+        assert_eq!(sequencer.position(), 0.125);
+    }
+
+    #[test]
+    fn advancing_by_blocks_that_are_not_binary_fractions() {
+        let mut file = file_with_notes(&[(60, 0.0, 240.0), (62, 240.0, 480.0)]);
+        file.set_ticks_per_quarter_note(480);
+        let mut sequencer = MidiSequencer::new(&file);
+        sequencer.play();
+
+        let mut events = 0;
+        for _ in 0..200 {
+            let mut buffer = MidiBuffer::default();
+            sequencer.render_block(&mut buffer, 240, 48000.0);
+            events += buffer.get_num_events();
+        }
+
+        assert_eq!(events, 4);
+        assert!(!sequencer.is_playing());
+    }
+
+    #[test]
+    fn rendering_into_a_midi_buffer() {
+        let file = file_with_notes(&[(60, 0.0, 96.0)]);
+        let mut sequencer = MidiSequencer::new(&file);
+        sequencer.play();
+
+        let mut buffer = MidiBuffer::default();
+        sequencer.render_block(&mut buffer, 48000, 48000.0);
+        assert_eq!(buffer.get_num_events(), 2);
+    }
+
+    #[test]
+    fn playing_a_file_to_a_loopback_output() {
+        let loopback = MidiLoopback::new("Loopback");
+        let (sender, received) = mpsc::channel();
+        let mut input = loopback.open_input(move |message| {
+            let _ = sender.send((message.is_note_on(false), message.get_note_number()));
+        });
+        input.start();
+
+        let file = file_with_notes(&[(60, 0.0, 24.0)]);
+        let player = MidiFilePlayer::new(&file, loopback.open_output());
+        player.play();
+
+        let timeout = Duration::from_secs(5);
+        assert_eq!(received.recv_timeout(timeout), Ok((true, 60)));
+        assert_eq!(received.recv_timeout(timeout), Ok((false, 60)));
+    }
+}
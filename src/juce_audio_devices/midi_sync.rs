@@ -0,0 +1,584 @@
+use crate::{
+    juce_audio_basics::{MidiMessage, MtcDecoder, SmpteFrameRate, SmpteTimecode},
+    juce_audio_devices::{
+        MidiInputPort, MidiInputSource, MidiInputWithCallback, MidiOutput, MidiOutputPort,
+    },
+    juce_core::Time,
+    utils::lock,
+};
+use cxx::UniquePtr;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+/// The number of MIDI clock messages per quarter note.
+const CLOCKS_PER_QUARTER_NOTE: f64 = 24.0;
+
+/// The number of MIDI clock messages per MIDI beat (sixteenth note).
+const CLOCKS_PER_MIDI_BEAT: u64 = 6;
+
+/// How late a clock may fall behind before the schedule gives up catching up.
+const MAX_LATENESS_MILLISECONDS: f64 = 50.0;
+
+/// How close to a deadline the generator stops sleeping and starts spinning.
+const SPIN_MILLISECONDS: f64 = 1.5;
+
+/// The longest the generator sleeps for, so that changes are picked up promptly.
+const MAX_SLEEP_MILLISECONDS: f64 = 5.0;
+
+/// How long the follower waits without a clock before it considers the clock stopped.
+const CLOCK_TIMEOUT_MILLISECONDS: f64 = 250.0;
+
+/// The number of clock intervals averaged by the follower's tempo estimate.
+const TEMPO_WINDOW: usize = 24;
+
+/// Decides when to send clock and time code messages.
+#[derive(Debug)]
+struct ClockSchedule {
+    bpm: f64,
+    playing: bool,
+    clocks: u64,
+    next_clock: f64,
+    frame_rate: Option<SmpteFrameRate>,
+    next_quarter_frame: f64,
+    quarter_frame_piece: i32,
+    quarter_frame_timecode: SmpteTimecode,
+    origin_seconds: f64,
+    origin_time: f64,
+}
+
+impl ClockSchedule {
+    fn new(bpm: f64, now: f64) -> Self {
+        Self {
+            bpm: sanitise_bpm(bpm),
+            playing: false,
+            clocks: 0,
+            next_clock: now,
+            frame_rate: None,
+            next_quarter_frame: now,
+            quarter_frame_piece: 0,
+            quarter_frame_timecode: SmpteTimecode::from_frame_number(0, SmpteFrameRate::Fps25),
+            origin_seconds: 0.0,
+            origin_time: now,
+        }
+    }
+
+    fn clock_interval(&self) -> f64 {
+        60_000.0 / (self.bpm * CLOCKS_PER_QUARTER_NOTE)
+    }
+
+    fn seconds_at(&self, time: f64) -> f64 {
+        if self.playing {
+            self.origin_seconds + (time - self.origin_time) * 0.001
+        } else {
+            self.origin_seconds
+        }
+    }
+
+    fn next_deadline(&self) -> f64 {
+        match self.frame_rate {
+            Some(_) if self.playing => self.next_clock.min(self.next_quarter_frame),
+            _ => self.next_clock,
+        }
+    }
+
+    fn process(&mut self, now: f64, send: &mut dyn FnMut(&MidiMessage)) {
+        if now - self.next_clock > MAX_LATENESS_MILLISECONDS {
+            self.next_clock = now;
+        }
+
+        while self.next_clock <= now {
+            send(&MidiMessage::midi_clock());
+            if self.playing {
+                self.clocks += 1;
+            }
+            self.next_clock += self.clock_interval();
+        }
+
+        let Some(frame_rate) = self.frame_rate.filter(|_| self.playing) else {
+            return;
+        };
+
+        if now - self.next_quarter_frame > MAX_LATENESS_MILLISECONDS {
+            self.next_quarter_frame = now;
+            self.quarter_frame_piece = 0;
+        }
+
+        while self.next_quarter_frame <= now {
+            if self.quarter_frame_piece == 0 {
+                self.quarter_frame_timecode = SmpteTimecode::from_seconds(
+                    self.seconds_at(self.next_quarter_frame),
+                    frame_rate,
+                );
+            }
+
+            let piece = self.quarter_frame_piece;
+            let value = self.quarter_frame_timecode.quarter_frame_value(piece);
+            send(&MidiMessage::quarter_frame(piece, value));
+
+            self.quarter_frame_piece = (piece + 1) % 8;
+            self.next_quarter_frame += 250.0 / frame_rate.frames_per_second();
+        }
+    }
+
+    fn start(&mut self, now: f64, send: &mut dyn FnMut(&MidiMessage)) {
+        self.clocks = 0;
+        self.origin_seconds = 0.0;
+        self.send_full_frame(send);
+        send(&MidiMessage::midi_start());
+        self.resume_at(now);
+    }
+
+    fn resume(&mut self, now: f64, send: &mut dyn FnMut(&MidiMessage)) {
+        if !self.playing {
+            send(&MidiMessage::midi_continue());
+            self.resume_at(now);
+        }
+    }
+
+    fn resume_at(&mut self, now: f64) {
+        self.origin_time = now;
+        self.playing = true;
+        self.next_quarter_frame = now;
+        self.quarter_frame_piece = 0;
+    }
+
+    fn stop(&mut self, now: f64, send: &mut dyn FnMut(&MidiMessage)) {
+        if self.playing {
+            self.origin_seconds = self.seconds_at(now);
+            self.playing = false;
+            send(&MidiMessage::midi_stop());
+        }
+    }
+
+    fn locate(&mut self, midi_beats: i32, now: f64, send: &mut dyn FnMut(&MidiMessage)) {
+        let was_playing = self.playing;
+        self.stop(now, send);
+
+        let midi_beats = midi_beats.clamp(0, 0x3fff);
+        self.clocks = midi_beats as u64 * CLOCKS_PER_MIDI_BEAT;
+        self.origin_seconds = self.clocks as f64 * self.clock_interval() * 0.001;
+
+        send(&MidiMessage::song_position_pointer(midi_beats));
+        self.send_full_frame(send);
+
+        if was_playing {
+            self.resume(now, send);
+        }
+    }
+
+    fn send_full_frame(&self, send: &mut dyn FnMut(&MidiMessage)) {
+        if let Some(frame_rate) = self.frame_rate {
+            let timecode = SmpteTimecode::from_seconds(self.origin_seconds, frame_rate);
+            send(&MidiMessage::full_frame(&timecode));
+        }
+    }
+}
+
+fn sanitise_bpm(bpm: f64) -> f64 {
+    if bpm.is_finite() {
+        bpm.clamp(1.0, 1000.0)
+    } else {
+        120.0
+    }
+}
+
+struct GeneratorState<Output> {
+    schedule: ClockSchedule,
+    output: Output,
+}
+
+impl<Output: MidiOutputPort> GeneratorState<Output> {
+    fn with_output(&mut self, f: impl FnOnce(&mut ClockSchedule, &mut dyn FnMut(&MidiMessage))) {
+        let Self { schedule, output } = self;
+        f(schedule, &mut |message| output.send_message_now(message));
+    }
+}
+
+/// Sends MIDI clock, and optionally MIDI time code, to a [`MidiOutputPort`].
+///
+/// Clock messages are sent continuously at 24 per quarter note so that followers can lock to
+/// the tempo before playback starts. The transport is controlled with start, stop, continue and
+/// song position pointer messages.
+///
+/// Messages are sent from a dedicated thread that sleeps until shortly before each deadline and
+/// then spins, which keeps jitter well below a millisecond on an unloaded system.
+pub struct MidiClockGenerator<Output: MidiOutputPort + 'static = UniquePtr<MidiOutput>> {
+    state: Arc<Mutex<GeneratorState<Output>>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<Output: MidiOutputPort + 'static> MidiClockGenerator<Output> {
+    /// Starts sending clock to `output` at the given tempo, in beats per minute.
+    pub fn new(output: Output, bpm: f64) -> Self {
+        let state = Arc::new(Mutex::new(GeneratorState {
+            schedule: ClockSchedule::new(bpm, Time::get_millisecond_counter_hi_res()),
+            output,
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread = std::thread::spawn({
+            let state = Arc::clone(&state);
+            let running = Arc::clone(&running);
+
+            move || {
+                while running.load(Ordering::Acquire) {
+                    let deadline = {
+                        let mut state = lock(&state);
+                        let now = Time::get_millisecond_counter_hi_res();
+                        state.with_output(|schedule, send| schedule.process(now, send));
+                        state.schedule.next_deadline()
+                    };
+
+                    let wait = deadline - Time::get_millisecond_counter_hi_res();
+                    if wait > SPIN_MILLISECONDS {
+                        let sleep = (wait - SPIN_MILLISECONDS).min(MAX_SLEEP_MILLISECONDS);
+                        std::thread::sleep(Duration::from_secs_f64(sleep * 0.001));
+                    } else {
+                        std::thread::yield_now();
+                    }
+                }
+            }
+        });
+
+        Self {
+            state,
+            running,
+            thread: Some(thread),
+        }
+    }
+
+    /// Sets the tempo in beats per minute.
+    pub fn set_bpm(&self, bpm: f64) {
+        self.lock().schedule.bpm = sanitise_bpm(bpm);
+    }
+
+    /// Returns the tempo in beats per minute.
+    pub fn bpm(&self) -> f64 {
+        self.lock().schedule.bpm
+    }
+
+    /// Also sends MIDI time code at the given frame rate while playing, or [`None`] to send
+    /// clock only.
+    pub fn set_time_code(&self, frame_rate: Option<SmpteFrameRate>) {
+        let mut state = self.lock();
+        state.schedule.frame_rate = frame_rate;
+        state.schedule.quarter_frame_piece = 0;
+    }
+
+    /// Starts playback from the beginning of the song.
+    pub fn start(&self) {
+        let now = Time::get_millisecond_counter_hi_res();
+        self.lock()
+            .with_output(|schedule, send| schedule.start(now, send));
+    }
+
+    /// Stops playback.
+    pub fn stop(&self) {
+        let now = Time::get_millisecond_counter_hi_res();
+        self.lock()
+            .with_output(|schedule, send| schedule.stop(now, send));
+    }
+
+    /// Continues playback from the current position.
+    pub fn resume(&self) {
+        let now = Time::get_millisecond_counter_hi_res();
+        self.lock()
+            .with_output(|schedule, send| schedule.resume(now, send));
+    }
+
+    /// Returns `true` if the transport is playing.
+    pub fn is_playing(&self) -> bool {
+        self.lock().schedule.playing
+    }
+
+    /// Moves the song position to the given number of MIDI beats (sixteenth notes).
+    ///
+    /// Followers only accept song position pointers while stopped, so if the transport is
+    /// playing it is stopped, moved and then continued.
+    pub fn locate(&self, midi_beats: i32) {
+        let now = Time::get_millisecond_counter_hi_res();
+        self.lock()
+            .with_output(|schedule, send| schedule.locate(midi_beats, now, send));
+    }
+
+    /// Returns the song position in quarter notes.
+    pub fn position(&self) -> f64 {
+        self.lock().schedule.clocks as f64 / CLOCKS_PER_QUARTER_NOTE
+    }
+
+    fn lock(&self) -> MutexGuard<'_, GeneratorState<Output>> {
+        lock(&self.state)
+    }
+}
+
+impl<Output: MidiOutputPort + 'static> Drop for MidiClockGenerator<Output> {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Tracks the clock and time code messages received from a master.
+#[derive(Debug, Default)]
+struct FollowerState {
+    playing: bool,
+    clocks: u64,
+    last_clock: Option<f64>,
+    anchor: Option<f64>,
+    intervals: VecDeque<f64>,
+    decoder: MtcDecoder,
+    last_timecode: Option<f64>,
+}
+
+impl FollowerState {
+    fn handle(&mut self, message: &MidiMessage, now: f64) {
+        if message.is_midi_clock() {
+            if let Some(last_clock) = self.last_clock {
+                let interval = now - last_clock;
+                if interval < CLOCK_TIMEOUT_MILLISECONDS {
+                    if self.intervals.len() == TEMPO_WINDOW {
+                        self.intervals.pop_front();
+                    }
+                    self.intervals.push_back(interval);
+                } else {
+                    self.intervals.clear();
+                }
+            }
+
+            self.last_clock = Some(now);
+            self.anchor = Some(now);
+            if self.playing {
+                self.clocks += 1;
+            }
+        } else if message.is_midi_start() {
+            self.playing = true;
+            self.clocks = 0;
+            self.anchor = Some(now);
+        } else if message.is_midi_continue() {
+            self.playing = true;
+            self.anchor = Some(now);
+        } else if message.is_midi_stop() {
+            self.playing = false;
+        } else if message.is_song_position_pointer() {
+            self.clocks =
+                message.get_song_position_pointer_midi_beat() as u64 * CLOCKS_PER_MIDI_BEAT;
+            self.anchor = Some(now);
+        } else if self.decoder.process(message).is_some() {
+            self.last_timecode = Some(now);
+        }
+    }
+
+    fn clock_interval(&self, now: f64) -> Option<f64> {
+        let last_clock = self.last_clock?;
+        if self.intervals.is_empty() || now - last_clock > CLOCK_TIMEOUT_MILLISECONDS {
+            return None;
+        }
+
+        Some(self.intervals.iter().sum::<f64>() / self.intervals.len() as f64)
+    }
+
+    fn tempo(&self, now: f64) -> Option<f64> {
+        self.clock_interval(now)
+            .map(|interval| 60_000.0 / (interval * CLOCKS_PER_QUARTER_NOTE))
+    }
+
+    fn position(&self, now: f64) -> f64 {
+        let clocks = self.clocks as f64;
+
+        let fraction = match (self.playing, self.anchor, self.clock_interval(now)) {
+            (true, Some(anchor), Some(interval)) => ((now - anchor) / interval).min(1.0),
+            _ => 0.0,
+        };
+
+        (clocks + fraction) / CLOCKS_PER_QUARTER_NOTE
+    }
+
+    fn timecode_position(&self, now: f64) -> Option<f64> {
+        let timecode = self.decoder.timecode()?.to_seconds();
+
+        match self.last_timecode {
+            Some(received) if now - received < CLOCK_TIMEOUT_MILLISECONDS => {
+                Some(timecode + (now - received) * 0.001)
+            }
+            _ => Some(timecode),
+        }
+    }
+}
+
+/// Follows the MIDI clock and MIDI time code sent by an external master to a [`MidiInputPort`].
+///
+/// The tempo is estimated by averaging the intervals between recent clock messages, and the
+/// position is interpolated between clocks so that it advances smoothly.
+pub struct MidiClockFollower<Input: MidiInputPort = MidiInputWithCallback> {
+    input: Input,
+    state: Arc<Mutex<FollowerState>>,
+}
+
+impl<Input: MidiInputPort> MidiClockFollower<Input> {
+    /// Opens an input from `source`, such as a device identifier, and starts following it.
+    ///
+    /// Returns [`None`] if the input could not be opened.
+    pub fn open(source: &impl MidiInputSource<Input = Input>) -> Option<Self> {
+        let state = Arc::new(Mutex::new(FollowerState::default()));
+
+        let mut input = source.open_input({
+            let state = Arc::clone(&state);
+            move |message| {
+                let now = Time::get_millisecond_counter_hi_res();
+                lock(&state).handle(message, now);
+            }
+        })?;
+        input.start();
+
+        Some(Self { input, state })
+    }
+
+    /// Returns the input being followed.
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Returns the estimated tempo in beats per minute, or [`None`] if no clock is being
+    /// received.
+    pub fn tempo(&self) -> Option<f64> {
+        self.lock().tempo(Time::get_millisecond_counter_hi_res())
+    }
+
+    /// Returns `true` if the master's transport is playing.
+    pub fn is_playing(&self) -> bool {
+        self.lock().playing
+    }
+
+    /// Returns the song position in quarter notes.
+    pub fn position(&self) -> f64 {
+        self.lock().position(Time::get_millisecond_counter_hi_res())
+    }
+
+    /// Returns the most recently received MIDI time code.
+    pub fn timecode(&self) -> Option<SmpteTimecode> {
+        self.lock().decoder.timecode()
+    }
+
+    /// Returns the time code position in seconds, extrapolated from the most recent time code
+    /// while quarter frames are still arriving.
+    pub fn timecode_position(&self) -> Option<f64> {
+        self.lock()
+            .timecode_position(Time::get_millisecond_counter_hi_res())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FollowerState> {
+        lock(&self.state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::juce_audio_devices::MidiLoopback;
+
+    /// Runs the schedule for `duration` milliseconds, feeding everything it sends to `follower`.
+    fn run(
+        schedule: &mut ClockSchedule,
+        follower: &mut FollowerState,
+        start: f64,
+        duration: f64,
+    ) -> Vec<MidiMessage> {
+        let mut sent = Vec::new();
+
+        loop {
+            let now = schedule.next_deadline().max(start);
+            if now >= start + duration {
+                break;
+            }
+
+            schedule.process(now, &mut |message| {
+                follower.handle(message, now);
+                sent.push(message.clone());
+            });
+        }
+
+        sent
+    }
+
+    #[test]
+    fn clocks_are_sent_at_24_per_quarter_note() {
+        let mut schedule = ClockSchedule::new(120.0, 0.0);
+        let mut follower = FollowerState::default();
+
+        let sent = run(&mut schedule, &mut follower, 0.0, 990.0);
+
+        assert_eq!(sent.len(), 48);
+        assert!(sent.iter().all(MidiMessage::is_midi_clock));
+        assert_eq!(follower.tempo(1000.0).map(f64::round), Some(120.0));
+        assert!(!follower.playing);
+    }
+
+    #[test]
+    fn follower_tracks_transport_and_position() {
+        let mut schedule = ClockSchedule::new(60.0, 0.0);
+        let mut follower = FollowerState::default();
+
+        schedule.start(0.0, &mut |message| follower.handle(message, 0.0));
+        run(&mut schedule, &mut follower, 0.0, 1990.0);
+
+        assert!(follower.playing);
+        assert_eq!(follower.clocks, schedule.clocks);
+        assert_eq!(follower.position(follower.last_clock.unwrap()), 2.0);
+
+        schedule.locate(16, 2000.0, &mut |message| follower.handle(message, 2000.0));
+
+        assert!(follower.playing);
+        assert_eq!(follower.position(2000.0), 4.0);
+    }
+
+    #[test]
+    fn time_code_is_sent_while_playing() {
+        let mut schedule = ClockSchedule::new(120.0, 0.0);
+        schedule.frame_rate = Some(SmpteFrameRate::Fps25);
+        let mut follower = FollowerState::default();
+
+        run(&mut schedule, &mut follower, 0.0, 100.0);
+        assert_eq!(follower.decoder.timecode(), None);
+
+        schedule.start(100.0, &mut |message| follower.handle(message, 100.0));
+        assert_eq!(
+            follower.decoder.timecode(),
+            Some(SmpteTimecode::from_frame_number(0, SmpteFrameRate::Fps25))
+        );
+
+        run(&mut schedule, &mut follower, 100.0, 1000.0);
+
+        let position = follower.timecode_position(1100.0).unwrap();
+        assert!((position - 1.0).abs() < 0.1, "{position}");
+    }
+
+    #[test]
+    fn following_a_generator_through_a_loopback() {
+        let loopback = MidiLoopback::new("Clock");
+        let follower = MidiClockFollower::open(&loopback).unwrap();
+        let generator = MidiClockGenerator::new(loopback.open_output(), 120.0);
+
+        generator.start();
+        assert!(follower.is_playing());
+
+        generator.stop();
+        assert!(!follower.is_playing());
+
+        generator.locate(16);
+        assert_eq!(follower.position(), 4.0);
+
+        generator.resume();
+        assert!(follower.is_playing());
+    }
+}
@@ -1,21 +1,67 @@
 //! Play and record from audio and MIDI I/O devices.
 
+mod callback_timing;
 mod device;
 mod device_callback;
 mod device_manager;
+mod device_report;
 mod device_type;
+mod latency;
 mod midi_device_info;
+mod midi_device_list;
 mod midi_input;
+mod midi_input_stream;
+mod midi_loopback;
 mod midi_output;
+mod midi_recorder;
+mod midi_router;
+mod midi_scheduler;
+mod midi_sequencer;
+mod midi_sync;
+mod offline_device;
+mod source_player;
 
-pub use device::{AudioDevice, AudioIODevice, BoxDynAudioDevice};
-pub use device_callback::{AudioDeviceCallback, AudioIODeviceCallback, BoxDynAudioDeviceCallback};
-pub use device_manager::{AudioDeviceManager, AudioDeviceSetup, ChannelCount};
-pub use device_type::{AudioDeviceType, AudioIODeviceType, BoxDynAudioDeviceType};
+pub use callback_timing::{CallbackTimingHistogram, TimedAudioDeviceCallback, TimingBucket};
+pub use device::{
+    AudioDevice, AudioDeviceCallbackHandle, AudioIODevice, BoxDynAudioDevice, StartedAudioDevice,
+};
+pub use device_callback::{
+    AudioDeviceCallback, AudioDeviceCallbackContext, AudioIODeviceCallback,
+    BoxDynAudioDeviceCallback,
+};
+pub use device_manager::{
+    AudioDeviceListenerHandle, AudioDeviceManager, AudioDeviceManagerOptions, AudioDeviceSetup,
+    ChannelCount, LevelMeter, MidiInputCallbackHandle,
+};
+pub use device_report::{AudioDeviceReport, AudioDeviceTypeReport};
+pub use device_type::{
+    AudioDeviceListNotifier, AudioDeviceType, AudioIODeviceType, BoxDynAudioDeviceType,
+};
 pub use juce::SystemAudioVolume;
+pub use latency::{LatencyMeasurement, LatencyTester};
 pub use midi_device_info::{MidiDeviceInfo, MidiDeviceInfoArray};
-pub use midi_input::{MidiInput, MidiInputWithCallback};
-pub use midi_output::MidiOutput;
+pub use midi_device_list::{
+    MidiDeviceChange, MidiDeviceListConnection, ReconnectingMidiInput, ReconnectingMidiOutput,
+};
+pub use midi_input::{
+    MidiInput, MidiInputCallback, MidiInputPort, MidiInputSource, MidiInputWithCallback,
+};
+pub use midi_input_stream::{MidiInputStream, MidiInputStreamOptions, MidiOverflowPolicy};
+pub use midi_loopback::{MidiLoopback, MidiLoopbackInput, MidiLoopbackOutput};
+pub use midi_output::{MidiOutput, MidiOutputPort};
+pub use midi_recorder::MidiRecorder;
+pub use midi_router::{
+    MidiDestinationHandle, MidiMessageKind, MidiRoute, MidiRouteHandle, MidiRouter,
+    MidiSourceHandle, VelocityCurve,
+};
+pub use midi_scheduler::{MidiEventTag, MidiEventTime, MidiScheduler};
+pub use midi_sequencer::{MidiFilePlayer, MidiSequencer};
+pub use midi_sync::{MidiClockFollower, MidiClockGenerator};
+pub use offline_device::{
+    OfflineAudioDevice, OfflineAudioDeviceType, OfflineDeviceOptions, OfflineGenerator,
+    OfflineInput, OfflineOutput, OfflineRecording, OfflineSpeed,
+};
+pub use source_player::AudioSourcePlayer;
 
 #[cxx::bridge(namespace = "juce")]
 mod juce {
@@ -0,0 +1,682 @@
+use crate::{
+    juce_audio_basics::AudioSampleBuffer,
+    juce_audio_devices::{AudioDevice, AudioDeviceCallbackHandle, AudioDeviceType, AudioIODevice},
+    juce_core::{DoubleArray, IntArray, JuceString, StringArray},
+    utils::lock,
+};
+use cxx::UniquePtr;
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "juce_audio_formats")]
+use {
+    crate::{
+        juce_audio_formats::{AudioFormatReader, AudioFormatWriter, WavAudioFormat},
+        juce_core::File,
+    },
+    std::path::{Path, PathBuf},
+};
+
+const NAME: &str = "Offline";
+const DEFAULT_SAMPLE_RATE: f64 = 44100.0;
+const DEFAULT_BUFFER_SIZE: i32 = 512;
+const SAMPLE_RATES: [f64; 4] = [44100.0, 48000.0, 88200.0, 96000.0];
+const BUFFER_SIZES: [i32; 6] = [64, 128, 256, 512, 1024, 2048];
+
+/// How quickly an offline device delivers blocks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum OfflineSpeed {
+    /// Deliver blocks at the rate a real device would.
+    #[default]
+    RealTime,
+    /// Deliver each block as soon as the previous one has been processed.
+    AsFastAsPossible,
+}
+
+type GeneratorFn = dyn FnMut(Pin<&mut AudioSampleBuffer>) + Send;
+
+/// A function that fills each input block of an offline device.
+///
+/// Created with [`OfflineInput::generator`].
+#[derive(Clone)]
+pub struct OfflineGenerator(Arc<Mutex<GeneratorFn>>);
+
+/// Where the input of an offline device comes from.
+#[derive(Clone, Default)]
+pub enum OfflineInput {
+    /// The input is silent.
+    #[default]
+    Silence,
+    /// The input is filled by a function.
+    Generator(OfflineGenerator),
+    /// The input is the device's own output, fed back after one block plus `delay` samples.
+    ///
+    /// Output channels are fed back to the input channels with the same index. The device
+    /// reports the block as its output latency and `delay` as its input latency, so the round
+    /// trip matches what it reports.
+    Loopback {
+        /// The number of samples to delay the output by, on top of one block.
+        delay: usize,
+    },
+    /// The input is read from a WAV file, and is silent once the file has been read.
+    ///
+    /// The file isn't resampled, so its sample rate should match the device's.
+    #[cfg(feature = "juce_audio_formats")]
+    WavFile(PathBuf),
+}
+
+impl OfflineInput {
+    /// Creates an input that calls `generator` to fill each block.
+    pub fn generator(generator: impl FnMut(Pin<&mut AudioSampleBuffer>) + Send + 'static) -> Self {
+        Self::Generator(OfflineGenerator(Arc::new(Mutex::new(generator))))
+    }
+}
+
+/// Where the output of an offline device goes.
+#[derive(Clone, Default)]
+pub enum OfflineOutput {
+    /// The output is discarded.
+    #[default]
+    Discard,
+    /// The output is appended to an in-memory recording.
+    Memory(OfflineRecording),
+    /// The output is written to a 32-bit floating point WAV file, replacing any existing file.
+    #[cfg(feature = "juce_audio_formats")]
+    WavFile(PathBuf),
+}
+
+/// The output of an offline device, recorded in memory.
+///
+/// Cloning a recording gives another handle to the same samples, so one handle can be given
+/// to the device and another kept to read the samples back.
+#[derive(Clone, Default)]
+pub struct OfflineRecording {
+    channels: Arc<Mutex<Vec<Vec<f32>>>>,
+}
+
+impl OfflineRecording {
+    /// Creates an empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of channels recorded.
+    pub fn num_channels(&self) -> usize {
+        lock(&self.channels).len()
+    }
+
+    /// Returns the number of samples recorded in each channel.
+    pub fn len(&self) -> usize {
+        lock(&self.channels).first().map_or(0, Vec::len)
+    }
+
+    /// Returns `true` if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a copy of the samples recorded in a channel.
+    pub fn channel(&self, channel: usize) -> Vec<f32> {
+        lock(&self.channels)
+            .get(channel)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Discards everything that has been recorded.
+    pub fn clear(&self) {
+        lock(&self.channels).clear();
+    }
+
+    fn append(&self, buffer: &AudioSampleBuffer, num_samples: usize) {
+        let mut channels = lock(&self.channels);
+        let num_channels = buffer.get_num_channels().max(0) as usize;
+        channels.resize_with(num_channels, Vec::new);
+
+        for (index, channel) in channels.iter_mut().enumerate() {
+            let samples = buffer.get_read_slice(index as i32);
+            channel.extend_from_slice(&samples[..num_samples.min(samples.len())]);
+        }
+    }
+}
+
+/// Options for an [`OfflineAudioDevice`].
+#[derive(Clone)]
+pub struct OfflineDeviceOptions {
+    /// Where the input comes from.
+    pub input: OfflineInput,
+    /// Where the output goes.
+    pub output: OfflineOutput,
+    /// How quickly blocks are delivered.
+    pub speed: OfflineSpeed,
+    /// The number of input channels.
+    pub num_input_channels: i32,
+    /// The number of output channels.
+    pub num_output_channels: i32,
+    /// The number of samples to process before the device stops delivering blocks, or [`None`]
+    /// to carry on until the device is stopped.
+    pub length_in_samples: Option<u64>,
+}
+
+impl Default for OfflineDeviceOptions {
+    fn default() -> Self {
+        Self {
+            input: OfflineInput::default(),
+            output: OfflineOutput::default(),
+            speed: OfflineSpeed::default(),
+            num_input_channels: 2,
+            num_output_channels: 2,
+            length_in_samples: None,
+        }
+    }
+}
+
+impl OfflineDeviceOptions {
+    /// Returns `self` with the input set.
+    pub fn with_input(mut self, input: OfflineInput) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Returns `self` with the output set.
+    pub fn with_output(mut self, output: OfflineOutput) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Returns `self` with the speed set.
+    pub fn with_speed(mut self, speed: OfflineSpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Returns `self` with the number of input channels set.
+    pub fn with_input_channels(mut self, num_input_channels: i32) -> Self {
+        self.num_input_channels = num_input_channels.max(0);
+        self
+    }
+
+    /// Returns `self` with the number of output channels set.
+    pub fn with_output_channels(mut self, num_output_channels: i32) -> Self {
+        self.num_output_channels = num_output_channels.max(0);
+        self
+    }
+
+    /// Returns `self` with the length set.
+    pub fn with_length_in_samples(mut self, length_in_samples: impl Into<Option<u64>>) -> Self {
+        self.length_in_samples = length_in_samples.into();
+        self
+    }
+}
+
+/// An [`AudioDeviceType`] with a single device that needs no audio hardware.
+///
+/// The device runs its callback from its own thread, taking its input from silence, a
+/// generator, a WAV file or its own output, and sending its output to a WAV file or an
+/// in-memory recording.
+/// This makes it possible to run the whole [`AudioDeviceManager`] and [`AudioDeviceCallback`]
+/// path on machines without a sound card.
+///
+/// [`AudioDeviceManager`]: crate::juce_audio_devices::AudioDeviceManager
+/// [`AudioDeviceCallback`]: crate::juce_audio_devices::AudioDeviceCallback
+pub struct OfflineAudioDeviceType {
+    options: OfflineDeviceOptions,
+}
+
+impl OfflineAudioDeviceType {
+    /// The name of the device type, and of its device.
+    pub const NAME: &'static str = NAME;
+
+    /// Creates a device type whose device uses the given options.
+    pub fn new(options: OfflineDeviceOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl AudioDeviceType for OfflineAudioDeviceType {
+    fn name(&self) -> String {
+        NAME.to_owned()
+    }
+
+    fn scan_for_devices(&mut self) {}
+
+    fn input_devices(&self) -> StringArray {
+        [NAME].into_iter().collect()
+    }
+
+    fn output_devices(&self) -> StringArray {
+        [NAME].into_iter().collect()
+    }
+
+    fn create_device(
+        &mut self,
+        _input_device_name: &JuceString,
+        _output_device_name: &JuceString,
+    ) -> UniquePtr<AudioIODevice> {
+        let device: Box<dyn AudioDevice> = Box::new(OfflineAudioDevice::new(self.options.clone()));
+        device.into()
+    }
+
+    fn default_device_index(&self, _for_input: bool) -> i32 {
+        0
+    }
+
+    fn has_separate_inputs_and_outputs(&self) -> bool {
+        false
+    }
+}
+
+struct Running {
+    running: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+/// An [`AudioDevice`] that needs no audio hardware.
+///
+/// See [`OfflineAudioDeviceType`].
+pub struct OfflineAudioDevice {
+    options: OfflineDeviceOptions,
+    sample_rate: f64,
+    buffer_size: i32,
+    is_open: bool,
+    last_error: Arc<Mutex<String>>,
+    running: Option<Running>,
+}
+
+impl OfflineAudioDevice {
+    /// Creates a device with the given options.
+    pub fn new(options: OfflineDeviceOptions) -> Self {
+        Self {
+            options,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            is_open: false,
+            last_error: Arc::default(),
+            running: None,
+        }
+    }
+
+    fn set_error(&self, error: impl Into<String>) -> JuceString {
+        let error = error.into();
+        *lock(&self.last_error) = error.clone();
+        JuceString::new(error)
+    }
+}
+
+impl AudioDevice for OfflineAudioDevice {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    fn type_name(&self) -> &str {
+        NAME
+    }
+
+    fn sample_rate(&mut self) -> f64 {
+        self.sample_rate
+    }
+
+    fn buffer_size(&mut self) -> i32 {
+        self.buffer_size
+    }
+
+    fn available_sample_rates(&mut self) -> DoubleArray {
+        SAMPLE_RATES.as_slice().into()
+    }
+
+    fn available_buffer_sizes(&mut self) -> IntArray {
+        BUFFER_SIZES.as_slice().into()
+    }
+
+    fn open(&mut self, sample_rate: f64, buffer_size: i32) -> JuceString {
+        self.stop();
+
+        #[cfg(feature = "juce_audio_formats")]
+        if let OfflineInput::WavFile(path) = &self.options.input {
+            if let Err(error) = open_wav_reader(path) {
+                return self.set_error(error);
+            }
+        }
+
+        self.sample_rate = if sample_rate > 0.0 {
+            sample_rate
+        } else {
+            DEFAULT_SAMPLE_RATE
+        };
+        self.buffer_size = if buffer_size > 0 {
+            buffer_size
+        } else {
+            DEFAULT_BUFFER_SIZE
+        };
+        self.is_open = true;
+
+        self.set_error("")
+    }
+
+    fn close(&mut self) {
+        self.stop();
+        self.is_open = false;
+    }
+
+    fn input_channels(&self) -> i32 {
+        self.options.num_input_channels
+    }
+
+    fn output_channels(&self) -> i32 {
+        self.options.num_output_channels
+    }
+
+    fn output_channel_names(&self) -> StringArray {
+        (1..=self.options.num_output_channels)
+            .map(|channel| JuceString::new(format!("Output {channel}")))
+            .collect()
+    }
+
+    fn input_channel_names(&self) -> StringArray {
+        (1..=self.options.num_input_channels)
+            .map(|channel| JuceString::new(format!("Input {channel}")))
+            .collect()
+    }
+
+    fn default_buffer_size(&self) -> i32 {
+        DEFAULT_BUFFER_SIZE
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    fn is_playing(&self) -> bool {
+        self.running
+            .as_ref()
+            .is_some_and(|running| !running.thread.is_finished())
+    }
+
+    fn last_error(&self) -> String {
+        lock(&self.last_error).clone()
+    }
+
+    fn bit_depth(&self) -> i32 {
+        32
+    }
+
+    fn output_latency(&self) -> i32 {
+        match self.options.input {
+            OfflineInput::Loopback { .. } => self.buffer_size,
+            _ => 0,
+        }
+    }
+
+    fn input_latency(&self) -> i32 {
+        match self.options.input {
+            OfflineInput::Loopback { delay } => delay as i32,
+            _ => 0,
+        }
+    }
+
+    fn has_control_panel(&self) -> bool {
+        false
+    }
+
+    fn show_control_panel(&mut self) -> bool {
+        false
+    }
+
+    fn set_audio_preprocessing_enabled(&mut self, _enabled: bool) -> bool {
+        false
+    }
+
+    fn xrun_count(&self) -> i32 {
+        0
+    }
+
+    fn start(&mut self, mut callback: AudioDeviceCallbackHandle) {
+        self.stop();
+
+        if !self.is_open {
+            return;
+        }
+
+        let render = match Render::new(&self.options, self.sample_rate, self.buffer_size) {
+            Ok(render) => render,
+            Err(error) => {
+                callback.error(&error);
+                *lock(&self.last_error) = error;
+                return;
+            }
+        };
+
+        // Real devices tell their callback that they are about to start before `start` returns,
+        // so only the blocks are delivered from the render thread.
+        callback.about_to_start();
+
+        let running = Arc::new(AtomicBool::new(true));
+        let last_error = Arc::clone(&self.last_error);
+
+        self.running = Some(Running {
+            running: Arc::clone(&running),
+            thread: std::thread::spawn(move || render.run(callback, &running, &last_error)),
+        });
+    }
+
+    fn stop(&mut self) {
+        if let Some(Running { running, thread }) = self.running.take() {
+            running.store(false, Ordering::Release);
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for OfflineAudioDevice {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// The state of the thread that runs an offline device's callback.
+struct Render {
+    source: Source,
+    sink: Sink,
+    input: UniquePtr<AudioSampleBuffer>,
+    output: UniquePtr<AudioSampleBuffer>,
+    speed: OfflineSpeed,
+    block_duration: Duration,
+    buffer_size: i32,
+    remaining: Option<u64>,
+}
+
+// The reader, writer and buffers are only used by the render thread once they have been moved
+// to it.
+unsafe impl Send for Render {}
+
+impl Render {
+    fn new(
+        options: &OfflineDeviceOptions,
+        sample_rate: f64,
+        buffer_size: i32,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            source: Source::open(&options.input, options.num_input_channels, buffer_size)?,
+            sink: Sink::open(&options.output, sample_rate, options.num_output_channels)?,
+            input: AudioSampleBuffer::new(options.num_input_channels, buffer_size),
+            output: AudioSampleBuffer::new(options.num_output_channels, buffer_size),
+            speed: options.speed,
+            block_duration: Duration::from_secs_f64(buffer_size as f64 / sample_rate),
+            buffer_size,
+            remaining: options.length_in_samples,
+        })
+    }
+
+    fn run(
+        mut self,
+        mut callback: AudioDeviceCallbackHandle,
+        running: &AtomicBool,
+        last_error: &Mutex<String>,
+    ) {
+        let mut deadline = Instant::now();
+
+        while running.load(Ordering::Acquire) && self.remaining != Some(0) {
+            self.input.pin_mut().clear();
+            self.source.fill(self.input.pin_mut());
+
+            self.output.pin_mut().clear();
+            callback.process_block(&self.input, self.output.pin_mut());
+            self.source.feed_back(&self.output);
+
+            let block_size = self.buffer_size as u64;
+            let num_samples = self
+                .remaining
+                .map_or(block_size, |remaining| remaining.min(block_size));
+            self.remaining = self.remaining.map(|remaining| remaining - num_samples);
+
+            if let Err(error) = self.sink.write(&self.output, num_samples as usize) {
+                callback.error(&error);
+                *lock(last_error) = error;
+                break;
+            }
+
+            if self.speed == OfflineSpeed::RealTime {
+                deadline += self.block_duration;
+                if let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+                    std::thread::sleep(wait);
+                }
+            }
+        }
+
+        drop(self.sink);
+        callback.stopped();
+    }
+}
+
+enum Source {
+    Silence,
+    Generator(OfflineGenerator),
+    Loopback(Vec<VecDeque<f32>>),
+    #[cfg(feature = "juce_audio_formats")]
+    WavFile {
+        reader: UniquePtr<AudioFormatReader>,
+        position: i64,
+    },
+}
+
+impl Source {
+    fn open(input: &OfflineInput, num_channels: i32, buffer_size: i32) -> Result<Self, String> {
+        Ok(match input {
+            OfflineInput::Silence => Self::Silence,
+            OfflineInput::Generator(generator) => Self::Generator(generator.clone()),
+            OfflineInput::Loopback { delay } => {
+                let length = delay + buffer_size.max(0) as usize;
+                let line = VecDeque::from(vec![0.0; length]);
+                Self::Loopback(vec![line; num_channels.max(0) as usize])
+            }
+            #[cfg(feature = "juce_audio_formats")]
+            OfflineInput::WavFile(path) => Self::WavFile {
+                reader: open_wav_reader(path)?,
+                position: 0,
+            },
+        })
+    }
+
+    fn fill(&mut self, buffer: Pin<&mut AudioSampleBuffer>) {
+        match self {
+            Self::Silence => {}
+            Self::Generator(generator) => (lock(&generator.0))(buffer),
+            Self::Loopback(lines) => {
+                let mut buffer = buffer;
+                for (channel, line) in lines.iter_mut().enumerate() {
+                    for sample in buffer.as_mut().get_write_slice(channel as i32) {
+                        *sample = line.pop_front().unwrap_or_default();
+                    }
+                }
+            }
+            #[cfg(feature = "juce_audio_formats")]
+            Self::WavFile { reader, position } => {
+                let num_samples = buffer.get_num_samples();
+                if buffer.get_num_channels() > 0 && *position < reader.length_in_samples() {
+                    reader.pin_mut().read(buffer, 0, num_samples, *position);
+                }
+                *position += i64::from(num_samples);
+            }
+        }
+    }
+
+    /// Keeps the output of a block, for loopback inputs to deliver later.
+    fn feed_back(&mut self, output: &AudioSampleBuffer) {
+        if let Self::Loopback(lines) = self {
+            let num_channels = lines.len().min(output.get_num_channels().max(0) as usize);
+            for (channel, line) in lines.iter_mut().enumerate().take(num_channels) {
+                line.extend(output.get_read_slice(channel as i32));
+            }
+        }
+    }
+}
+
+enum Sink {
+    Discard,
+    Memory(OfflineRecording),
+    #[cfg(feature = "juce_audio_formats")]
+    WavFile(UniquePtr<AudioFormatWriter>),
+}
+
+impl Sink {
+    #[cfg_attr(not(feature = "juce_audio_formats"), allow(unused_variables))]
+    fn open(output: &OfflineOutput, sample_rate: f64, num_channels: i32) -> Result<Self, String> {
+        Ok(match output {
+            OfflineOutput::Discard => Self::Discard,
+            OfflineOutput::Memory(recording) => Self::Memory(recording.clone()),
+            #[cfg(feature = "juce_audio_formats")]
+            OfflineOutput::WavFile(path) => {
+                let file = juce_file(path)?;
+                let num_channels = num_channels.max(1) as u32;
+                let writer =
+                    WavAudioFormat::create_writer_for(&file, sample_rate, num_channels, 32)
+                        .ok_or_else(|| format!("Couldn't create {}", path.display()))?;
+                Self::WavFile(writer)
+            }
+        })
+    }
+
+    fn write(&mut self, buffer: &AudioSampleBuffer, num_samples: usize) -> Result<(), String> {
+        match self {
+            Self::Discard => Ok(()),
+            Self::Memory(recording) => {
+                recording.append(buffer, num_samples);
+                Ok(())
+            }
+            #[cfg(feature = "juce_audio_formats")]
+            Self::WavFile(writer) => {
+                if buffer.get_num_channels() == 0
+                    || writer.pin_mut().write_from_audio_sample_buffer(
+                        buffer,
+                        0,
+                        num_samples as i32,
+                    )
+                {
+                    Ok(())
+                } else {
+                    Err("Couldn't write to the output file".to_owned())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "juce_audio_formats")]
+fn juce_file(path: &Path) -> Result<File, String> {
+    let path = std::path::absolute(path).map_err(|error| error.to_string())?;
+    Ok(File::from_absolute_path(path.to_string_lossy().as_ref()))
+}
+
+#[cfg(feature = "juce_audio_formats")]
+fn open_wav_reader(path: &Path) -> Result<UniquePtr<AudioFormatReader>, String> {
+    WavAudioFormat::create_reader_for(&juce_file(path)?)
+        .ok_or_else(|| format!("Couldn't open {} as a WAV file", path.display()))
+}
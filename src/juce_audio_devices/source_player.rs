@@ -0,0 +1,145 @@
+use crate::{
+    juce_audio_basics::{wrap_audio_source, AudioSampleBuffer, AudioSource, JuceAudioSource},
+    juce_audio_devices::{AudioDeviceCallback, AudioDeviceCallbackContext, AudioIODevice},
+    juce_core::JuceString,
+};
+use cxx::UniquePtr;
+use std::pin::Pin;
+
+/// Plays an [`AudioSource`] through an audio device.
+///
+/// The player is an [`AudioDeviceCallback`], so it can be registered with
+/// [`AudioDeviceManager::add_audio_callback`](crate::juce_audio_devices::AudioDeviceManager::add_audio_callback)
+/// like any other callback.
+///
+/// Registering the player moves it into the device or device manager, so its source and gain
+/// are configured before it is registered.
+pub struct AudioSourcePlayer {
+    // Declared before the source so that the player lets go of the source before it is dropped.
+    player: UniquePtr<juce::AudioSourcePlayer>,
+    source: Option<UniquePtr<JuceAudioSource>>,
+}
+
+// JUCE's player guards its source with a lock, as it is driven from the audio thread, and
+// sources are required to be `Send`.
+unsafe impl Send for AudioSourcePlayer {}
+
+impl Default for AudioSourcePlayer {
+    fn default() -> Self {
+        Self {
+            player: juce::audio_source_player_new(),
+            source: None,
+        }
+    }
+}
+
+impl AudioSourcePlayer {
+    /// Creates a player with no source, which outputs silence.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a player that plays `source`.
+    pub fn with_source(source: impl AudioSource + 'static) -> Self {
+        let mut player = Self::new();
+        player.set_source(source);
+        player
+    }
+
+    /// Replaces the source being played.
+    pub fn set_source(&mut self, source: impl AudioSource + 'static) {
+        let source = wrap_audio_source(source);
+
+        unsafe {
+            self.player.pin_mut().set_source(source.as_mut_ptr());
+        }
+
+        self.source = Some(source);
+    }
+
+    /// Stops playing the current source, releasing its resources.
+    pub fn clear_source(&mut self) {
+        unsafe {
+            self.player.pin_mut().set_source(std::ptr::null_mut());
+        }
+
+        self.source = None;
+    }
+
+    /// Returns `true` if the player has a source.
+    pub fn has_source(&self) -> bool {
+        self.source.is_some()
+    }
+
+    /// Sets the gain applied to the output of the source.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.player.pin_mut().set_gain(gain);
+    }
+
+    /// Returns the gain applied to the output of the source.
+    pub fn gain(&self) -> f32 {
+        self.player.get_gain()
+    }
+
+    fn callback(&mut self) -> Pin<&mut juce::AudioIODeviceCallback> {
+        juce::audio_source_player_as_callback(self.player.pin_mut())
+    }
+}
+
+impl AudioDeviceCallback for AudioSourcePlayer {
+    fn about_to_start(&mut self, device: Pin<&mut AudioIODevice>) {
+        self.callback().about_to_start(device);
+    }
+
+    fn process_block(&mut self, input: &AudioSampleBuffer, output: Pin<&mut AudioSampleBuffer>) {
+        self.process_block_with_context(input, output, &AudioDeviceCallbackContext::default());
+    }
+
+    fn process_block_with_context(
+        &mut self,
+        input: &AudioSampleBuffer,
+        output: Pin<&mut AudioSampleBuffer>,
+        context: &AudioDeviceCallbackContext,
+    ) {
+        self.callback().process_block(input, output, context);
+    }
+
+    fn stopped(&mut self) {
+        self.callback().stopped();
+    }
+
+    fn device_error(&mut self, message: &str) {
+        self.callback().device_error(&JuceString::new(message));
+    }
+}
+
+#[cxx::bridge(namespace = "juce")]
+mod juce {
+    unsafe extern "C++" {
+        include!("cxx_juce.h");
+
+        type AudioSource = crate::juce_audio_basics::JuceAudioSource;
+        type AudioIODeviceCallback = crate::juce_audio_devices::AudioIODeviceCallback;
+
+        type AudioSourcePlayer;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "makeUnique"]
+        fn audio_source_player_new() -> UniquePtr<AudioSourcePlayer>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "derivedCastMut"]
+        fn audio_source_player_as_callback(
+            player: Pin<&mut AudioSourcePlayer>,
+        ) -> Pin<&mut AudioIODeviceCallback>;
+
+        #[cxx_name = "setSource"]
+        unsafe fn set_source(self: Pin<&mut AudioSourcePlayer>, source: *mut AudioSource);
+
+        #[cxx_name = "setGain"]
+        fn set_gain(self: Pin<&mut AudioSourcePlayer>, gain: f32);
+
+        #[cxx_name = "getGain"]
+        fn get_gain(self: &AudioSourcePlayer) -> f32;
+    }
+}
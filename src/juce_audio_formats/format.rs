@@ -0,0 +1,227 @@
+use crate::{juce_audio_basics::AudioSampleBuffer, juce_core::File};
+use cxx::UniquePtr;
+use std::pin::Pin;
+
+pub use juce::{AudioFormatReader, AudioFormatWriter};
+
+/// Reads and writes WAV files.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct WavAudioFormat;
+
+impl WavAudioFormat {
+    /// Opens a WAV file for reading.
+    ///
+    /// Returns [`None`] if the file can't be opened or isn't a WAV file.
+    pub fn create_reader_for(file: &File) -> Option<UniquePtr<AudioFormatReader>> {
+        let reader = juce::create_wav_reader(file);
+        (!reader.is_null()).then_some(reader)
+    }
+
+    /// Creates a WAV file for writing, replacing any existing file.
+    ///
+    /// Returns [`None`] if the file can't be created or the format isn't supported.
+    pub fn create_writer_for(
+        file: &File,
+        sample_rate: f64,
+        num_channels: u32,
+        bits_per_sample: i32,
+    ) -> Option<UniquePtr<AudioFormatWriter>> {
+        let writer = juce::create_wav_writer(file, sample_rate, num_channels, bits_per_sample);
+        (!writer.is_null()).then_some(writer)
+    }
+}
+
+/// Reads and writes FLAC files.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct FlacAudioFormat;
+
+impl FlacAudioFormat {
+    /// Opens a FLAC file for reading.
+    ///
+    /// Returns [`None`] if the file can't be opened or isn't a FLAC file.
+    pub fn create_reader_for(file: &File) -> Option<UniquePtr<AudioFormatReader>> {
+        let reader = juce::create_flac_reader(file);
+        (!reader.is_null()).then_some(reader)
+    }
+
+    /// Creates a FLAC file for writing, replacing any existing file.
+    ///
+    /// FLAC supports 16 and 24 bits per sample. Returns [`None`] if the file can't be created
+    /// or the format isn't supported.
+    pub fn create_writer_for(
+        file: &File,
+        sample_rate: f64,
+        num_channels: u32,
+        bits_per_sample: i32,
+    ) -> Option<UniquePtr<AudioFormatWriter>> {
+        let writer = juce::create_flac_writer(file, sample_rate, num_channels, bits_per_sample);
+        (!writer.is_null()).then_some(writer)
+    }
+}
+
+impl AudioFormatReader {
+    /// Returns the sample rate of the file.
+    pub fn sample_rate(&self) -> f64 {
+        juce::audio_format_reader_sample_rate(self)
+    }
+
+    /// Returns the length of the file in samples.
+    pub fn length_in_samples(&self) -> i64 {
+        juce::audio_format_reader_length_in_samples(self)
+    }
+
+    /// Returns the number of channels in the file.
+    pub fn num_channels(&self) -> u32 {
+        juce::audio_format_reader_num_channels(self)
+    }
+
+    /// Returns the number of bits per sample in the file.
+    pub fn bits_per_sample(&self) -> u32 {
+        juce::audio_format_reader_bits_per_sample(self)
+    }
+
+    /// Reads `num_samples` samples, starting at `reader_start_sample` in the file, into the
+    /// buffer at `start_sample`.
+    ///
+    /// Samples beyond the end of the file are read as silence. Returns `false` if the read
+    /// failed.
+    pub fn read(
+        self: Pin<&mut Self>,
+        buffer: Pin<&mut AudioSampleBuffer>,
+        start_sample: i32,
+        num_samples: i32,
+        reader_start_sample: i64,
+    ) -> bool {
+        let available = buffer.get_num_samples() - start_sample.max(0);
+        let num_samples = num_samples.min(available);
+
+        if start_sample < 0 || num_samples <= 0 {
+            return false;
+        }
+
+        juce::audio_format_reader_read(self, buffer, start_sample, num_samples, reader_start_sample)
+    }
+}
+
+impl AudioFormatWriter {
+    /// Writes `num_samples` samples from `source`, starting at `start_sample`.
+    ///
+    /// Returns `false` if the range isn't within the buffer, the buffer has no channels, or
+    /// the write failed.
+    pub fn write_from_audio_sample_buffer(
+        self: Pin<&mut Self>,
+        source: &AudioSampleBuffer,
+        start_sample: i32,
+        num_samples: i32,
+    ) -> bool {
+        let in_range = start_sample >= 0
+            && num_samples >= 0
+            && start_sample
+                .checked_add(num_samples)
+                .is_some_and(|end| end <= source.get_num_samples());
+
+        if !in_range || source.get_num_channels() <= 0 {
+            return false;
+        }
+
+        juce::audio_format_writer_write_from_audio_sample_buffer(
+            self,
+            source,
+            start_sample,
+            num_samples,
+        )
+    }
+}
+
+#[cxx::bridge(namespace = "juce")]
+mod juce {
+    unsafe extern "C++" {
+        include!("cxx_juce.h");
+        include!("cxx_juce_audio_formats/cxx_juce_audio_formats.h");
+
+        type File = crate::juce_core::File;
+        type AudioSampleBuffer = crate::juce_audio_basics::AudioSampleBuffer;
+
+        /// Reads samples from an audio file.
+        type AudioFormatReader;
+
+        /// Writes samples to an audio file.
+        type AudioFormatWriter;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "createWavReader"]
+        fn create_wav_reader(file: &File) -> UniquePtr<AudioFormatReader>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "createWavWriter"]
+        fn create_wav_writer(
+            file: &File,
+            sample_rate: f64,
+            num_channels: u32,
+            bits_per_sample: i32,
+        ) -> UniquePtr<AudioFormatWriter>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "createFlacReader"]
+        fn create_flac_reader(file: &File) -> UniquePtr<AudioFormatReader>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "createFlacWriter"]
+        fn create_flac_writer(
+            file: &File,
+            sample_rate: f64,
+            num_channels: u32,
+            bits_per_sample: i32,
+        ) -> UniquePtr<AudioFormatWriter>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "audioFormatReaderSampleRate"]
+        fn audio_format_reader_sample_rate(reader: &AudioFormatReader) -> f64;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "audioFormatReaderLengthInSamples"]
+        fn audio_format_reader_length_in_samples(reader: &AudioFormatReader) -> i64;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "audioFormatReaderNumChannels"]
+        fn audio_format_reader_num_channels(reader: &AudioFormatReader) -> u32;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "audioFormatReaderBitsPerSample"]
+        fn audio_format_reader_bits_per_sample(reader: &AudioFormatReader) -> u32;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "audioFormatReaderRead"]
+        fn audio_format_reader_read(
+            reader: Pin<&mut AudioFormatReader>,
+            buffer: Pin<&mut AudioSampleBuffer>,
+            start_sample: i32,
+            num_samples: i32,
+            reader_start_sample: i64,
+        ) -> bool;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "audioFormatWriterWriteFromAudioSampleBuffer"]
+        fn audio_format_writer_write_from_audio_sample_buffer(
+            writer: Pin<&mut AudioFormatWriter>,
+            source: &AudioSampleBuffer,
+            start_sample: i32,
+            num_samples: i32,
+        ) -> bool;
+
+        /// Writes any buffered samples to the file, returning `false` if that isn't possible.
+        fn flush(self: Pin<&mut AudioFormatWriter>) -> bool;
+
+        /// Returns the sample rate being written.
+        #[cxx_name = "getSampleRate"]
+        fn get_sample_rate(self: &AudioFormatWriter) -> f64;
+
+        /// Returns the number of channels being written.
+        #[cxx_name = "getNumChannels"]
+        fn get_num_channels(self: &AudioFormatWriter) -> i32;
+
+        /// Returns the number of bits per sample being written.
+        #[cxx_name = "getBitsPerSample"]
+        fn get_bits_per_sample(self: &AudioFormatWriter) -> i32;
+    }
+}
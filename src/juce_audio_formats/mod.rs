@@ -0,0 +1,7 @@
+//! Reading and writing audio files.
+
+mod format;
+mod recorder;
+
+pub use format::{AudioFormatReader, AudioFormatWriter, FlacAudioFormat, WavAudioFormat};
+pub use recorder::{AudioRecorder, AudioRecorderOptions, RecordingFormat};
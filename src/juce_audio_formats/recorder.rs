@@ -0,0 +1,479 @@
+use crate::{
+    juce_audio_basics::AudioSampleBuffer,
+    juce_audio_devices::{AudioDeviceCallback, AudioIODevice},
+    juce_audio_formats::{FlacAudioFormat, WavAudioFormat},
+    juce_core::{File, JuceString},
+    utils::{error, lock},
+    JuceError,
+};
+use cxx::UniquePtr;
+use std::{
+    path::Path,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, TryLockError,
+    },
+    time::Duration,
+};
+
+/// The most input channels a recorder will write. Matches `maxThreadedWriterChannels` in the
+/// bridge, which lets blocks be handed to the writer without allocating.
+const MAX_CHANNELS: i32 = 64;
+
+/// The file format written by an [`AudioRecorder`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RecordingFormat {
+    /// A WAV file.
+    #[default]
+    Wav,
+    /// A FLAC file, which supports 16 and 24 bits per sample.
+    Flac,
+}
+
+/// How an [`AudioRecorder`] writes its files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioRecorderOptions {
+    /// The file format to write.
+    pub format: RecordingFormat,
+    /// The number of bits per sample to write.
+    pub bits_per_sample: i32,
+    /// How much audio from before [`AudioRecorder::start`] is called to include at the start of
+    /// each recording.
+    pub pre_roll: Duration,
+    /// How much audio can be waiting to be written to disk before blocks are dropped.
+    pub buffer_length: Duration,
+}
+
+impl Default for AudioRecorderOptions {
+    fn default() -> Self {
+        Self {
+            format: RecordingFormat::Wav,
+            bits_per_sample: 24,
+            pre_roll: Duration::ZERO,
+            buffer_length: Duration::from_secs(1),
+        }
+    }
+}
+
+impl AudioRecorderOptions {
+    /// Returns the options with the file format set.
+    pub fn with_format(mut self, format: RecordingFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Returns the options with the number of bits per sample set.
+    pub fn with_bits_per_sample(mut self, bits_per_sample: i32) -> Self {
+        self.bits_per_sample = bits_per_sample;
+        self
+    }
+
+    /// Returns the options with the pre-roll length set.
+    pub fn with_pre_roll(mut self, pre_roll: Duration) -> Self {
+        self.pre_roll = pre_roll;
+        self
+    }
+
+    /// Returns the options with the disk buffer length set.
+    pub fn with_buffer_length(mut self, buffer_length: Duration) -> Self {
+        self.buffer_length = buffer_length;
+        self
+    }
+}
+
+type LevelFn = dyn FnMut(&[f32]) + Send;
+
+struct Recording {
+    writer: UniquePtr<juce::ThreadedWriter>,
+    num_channels: i32,
+}
+
+/// The most recent input, kept while the recorder isn't recording.
+struct PreRoll {
+    buffer: UniquePtr<AudioSampleBuffer>,
+    position: i32,
+    filled: i32,
+}
+
+impl PreRoll {
+    fn new(num_channels: i32, num_samples: i32) -> Self {
+        Self {
+            buffer: if num_channels > 0 && num_samples > 0 {
+                AudioSampleBuffer::new(num_channels, num_samples)
+            } else {
+                UniquePtr::null()
+            },
+            position: 0,
+            filled: 0,
+        }
+    }
+
+    fn push(&mut self, input: &AudioSampleBuffer) {
+        let Some(mut buffer) = self.buffer.as_mut() else {
+            return;
+        };
+
+        let length = buffer.get_num_samples();
+        let num_channels = buffer.get_num_channels().min(input.get_num_channels());
+        let mut offset = input.get_num_samples().saturating_sub(length).max(0);
+
+        while offset < input.get_num_samples() {
+            let count = (input.get_num_samples() - offset).min(length - self.position);
+            let source = offset as usize..(offset + count) as usize;
+            let destination = self.position as usize..(self.position + count) as usize;
+
+            for channel in 0..num_channels {
+                buffer.as_mut().get_write_slice(channel)[destination.clone()]
+                    .copy_from_slice(&input.get_read_slice(channel)[source.clone()]);
+            }
+
+            offset += count;
+            self.position = (self.position + count) % length;
+            self.filled = (self.filled + count).min(length);
+        }
+    }
+
+    /// Writes the kept input to `writer`, oldest first, and forgets it.
+    fn drain_into(&mut self, writer: Pin<&mut juce::ThreadedWriter>) -> (u64, u64) {
+        let Some(buffer) = self.buffer.as_ref() else {
+            return (0, 0);
+        };
+
+        let length = buffer.get_num_samples();
+        let chunks = if self.filled < length {
+            [(0, self.filled), (0, 0)]
+        } else {
+            [(self.position, length - self.position), (0, self.position)]
+        };
+
+        let mut writer = writer;
+        let (mut written, mut dropped) = (0, 0);
+
+        for (start, count) in chunks.into_iter().filter(|&(_, count)| count > 0) {
+            if juce::threaded_writer_write(writer.as_mut(), buffer, start, count) {
+                written += count as u64;
+            } else {
+                dropped += count as u64;
+            }
+        }
+
+        self.position = 0;
+        self.filled = 0;
+        (written, dropped)
+    }
+}
+
+struct State {
+    recording: Option<Recording>,
+    pre_roll: PreRoll,
+    drain_pre_roll: bool,
+    levels: Vec<f32>,
+    level_callback: Option<Box<LevelFn>>,
+    sample_rate: f64,
+    num_channels: i32,
+}
+
+// The buffers and writer are only touched with the lock held, and the writer is designed to be
+// fed from the audio thread while its background thread writes to disk.
+unsafe impl Send for State {}
+
+// TimeSliceThread guards its list of clients with a lock, so clients can be added and removed
+// from any thread.
+unsafe impl Send for juce::TimeSliceThread {}
+unsafe impl Sync for juce::TimeSliceThread {}
+
+struct Shared {
+    options: AudioRecorderOptions,
+    // Declared before the thread so that any writer is removed from the thread before it stops.
+    state: Mutex<State>,
+    thread: UniquePtr<juce::TimeSliceThread>,
+    recording: AtomicBool,
+    paused: AtomicBool,
+    recorded_samples: AtomicU64,
+    dropped_samples: AtomicU64,
+}
+
+/// Records the input of an audio device to a WAV or FLAC file.
+///
+/// The recorder is an [`AudioDeviceCallback`]: register a clone of it with
+/// [`AudioDeviceManager::add_audio_callback`](crate::juce_audio_devices::AudioDeviceManager::add_audio_callback)
+/// and keep the original to control it. Incoming blocks are queued for a background thread that
+/// writes them to disk, so the audio thread never allocates or waits on the disk or on the
+/// recorder's controls. If the queue fills up, or a block arrives while a recording is being
+/// started or stopped, the block is dropped and counted in
+/// [`AudioRecorder::dropped_samples`].
+///
+/// Up to 64 input channels are recorded.
+#[derive(Clone)]
+pub struct AudioRecorder {
+    shared: Arc<Shared>,
+}
+
+impl AudioRecorder {
+    /// Creates a recorder, starting its background writer thread.
+    pub fn new(options: AudioRecorderOptions) -> Self {
+        let mut thread = juce::time_slice_thread_new(&JuceString::new("Audio Recorder"));
+        thread.pin_mut().start_thread();
+
+        Self {
+            shared: Arc::new(Shared {
+                options,
+                state: Mutex::new(State {
+                    recording: None,
+                    pre_roll: PreRoll::new(0, 0),
+                    drain_pre_roll: false,
+                    levels: Vec::new(),
+                    level_callback: None,
+                    sample_rate: 0.0,
+                    num_channels: 0,
+                }),
+                thread,
+                recording: AtomicBool::new(false),
+                paused: AtomicBool::new(false),
+                recorded_samples: AtomicU64::new(0),
+                dropped_samples: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Starts recording to the file at `path`, replacing any existing file.
+    ///
+    /// The recorder must have been started by an audio device, so that it knows the sample rate
+    /// and number of channels to record. Any recording already in progress is finished first.
+    pub fn start(&self, path: impl AsRef<Path>) -> Result<(), JuceError> {
+        let path = path.as_ref();
+        let options = &self.shared.options;
+
+        let (sample_rate, num_channels) = {
+            let state = lock(&self.shared.state);
+            (state.sample_rate, state.num_channels)
+        };
+
+        if sample_rate <= 0.0 || num_channels <= 0 {
+            return Err(error("The recorder hasn't been started by an audio device"));
+        }
+
+        let file = std::path::absolute(path)
+            .map(|path| File::from_absolute_path(path.to_string_lossy().as_ref()))
+            .map_err(|err| error(err.to_string()))?;
+
+        let create_writer = match options.format {
+            RecordingFormat::Wav => WavAudioFormat::create_writer_for,
+            RecordingFormat::Flac => FlacAudioFormat::create_writer_for,
+        };
+
+        let writer = create_writer(
+            &file,
+            sample_rate,
+            num_channels as u32,
+            options.bits_per_sample,
+        )
+        .ok_or_else(|| error(format!("Couldn't create {}", path.display())))?;
+
+        let buffer_length = options.buffer_length + options.pre_roll;
+        let num_samples_to_buffer = (buffer_length.as_secs_f64() * sample_rate).ceil() as i32;
+        let writer =
+            juce::make_threaded_writer(writer, &self.shared.thread, num_samples_to_buffer.max(1));
+
+        self.shared.recorded_samples.store(0, Ordering::Relaxed);
+        self.shared.dropped_samples.store(0, Ordering::Relaxed);
+        self.shared.paused.store(false, Ordering::Relaxed);
+
+        let previous = {
+            let mut state = lock(&self.shared.state);
+            state.drain_pre_roll = true;
+            state.recording.replace(Recording {
+                writer,
+                num_channels,
+            })
+        };
+
+        self.shared.recording.store(true, Ordering::Relaxed);
+
+        // Any previous writer is destroyed outside the lock, as it waits for its queue to be
+        // written to disk.
+        drop(previous);
+        Ok(())
+    }
+
+    /// Stops recording, returning `false` if the recorder wasn't recording.
+    ///
+    /// This waits for any queued audio to be written to disk before returning.
+    pub fn stop(&self) -> bool {
+        self.shared.recording.store(false, Ordering::Relaxed);
+        let recording = lock(&self.shared.state).recording.take();
+        self.shared.paused.store(false, Ordering::Relaxed);
+        recording.is_some()
+    }
+
+    /// Pauses recording, so that incoming blocks are skipped until the recorder is resumed.
+    pub fn pause(&self) {
+        self.shared.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Resumes a paused recording.
+    pub fn resume(&self) {
+        self.shared.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the recorder is paused.
+    pub fn is_paused(&self) -> bool {
+        self.shared.paused.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the recorder is recording, including while it is paused.
+    pub fn is_recording(&self) -> bool {
+        self.shared.recording.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of samples per channel queued for the current recording, including
+    /// the pre-roll.
+    pub fn recorded_samples(&self) -> u64 {
+        self.shared.recorded_samples.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of samples per channel that couldn't be queued for the current
+    /// recording.
+    pub fn dropped_samples(&self) -> u64 {
+        self.shared.dropped_samples.load(Ordering::Relaxed)
+    }
+
+    /// Sets a function to call with the peak level of each input channel for every block.
+    ///
+    /// The function is called on the audio thread, so it mustn't block or allocate.
+    pub fn on_levels(&self, callback: impl FnMut(&[f32]) + Send + 'static) {
+        lock(&self.shared.state).level_callback = Some(Box::new(callback));
+    }
+
+    /// Removes the function set with [`AudioRecorder::on_levels`].
+    pub fn clear_levels_callback(&self) {
+        lock(&self.shared.state).level_callback = None;
+    }
+}
+
+impl AudioDeviceCallback for AudioRecorder {
+    fn about_to_start(&mut self, mut device: Pin<&mut AudioIODevice>) {
+        let sample_rate = device.as_mut().get_current_sample_rate();
+        let num_channels = device
+            .get_active_input_channels()
+            .count_number_of_set_bits()
+            .min(MAX_CHANNELS);
+        let pre_roll_samples = (self.shared.options.pre_roll.as_secs_f64() * sample_rate) as i32;
+
+        let mut state = lock(&self.shared.state);
+        state.sample_rate = sample_rate;
+        state.num_channels = num_channels;
+        state.pre_roll = PreRoll::new(num_channels, pre_roll_samples);
+        state.levels = vec![0.0; num_channels.max(0) as usize];
+    }
+
+    fn process_block(&mut self, input: &AudioSampleBuffer, output: Pin<&mut AudioSampleBuffer>) {
+        output.clear();
+
+        let num_samples = input.get_num_samples().max(0) as u64;
+        let shared = &self.shared;
+        let writing =
+            shared.recording.load(Ordering::Relaxed) && !shared.paused.load(Ordering::Relaxed);
+
+        let mut state = match shared.state.try_lock() {
+            Ok(state) => state,
+            Err(TryLockError::Poisoned(error)) => error.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                if writing {
+                    shared
+                        .dropped_samples
+                        .fetch_add(num_samples, Ordering::Relaxed);
+                }
+                return;
+            }
+        };
+
+        let state = &mut *state;
+
+        if let Some(callback) = &mut state.level_callback {
+            for (channel, level) in state.levels.iter_mut().enumerate() {
+                *level = input
+                    .get_read_slice(channel as i32)
+                    .iter()
+                    .fold(0.0, |peak: f32, sample| peak.max(sample.abs()));
+            }
+
+            callback(&state.levels);
+        }
+
+        let Some(recording) = &mut state.recording else {
+            state.pre_roll.push(input);
+            return;
+        };
+
+        if shared.paused.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let (mut written, mut dropped) = (0, 0);
+
+        if std::mem::take(&mut state.drain_pre_roll) {
+            (written, dropped) = state.pre_roll.drain_into(recording.writer.pin_mut());
+        }
+
+        if input.get_num_channels() >= recording.num_channels
+            && juce::threaded_writer_write(
+                recording.writer.pin_mut(),
+                input,
+                0,
+                input.get_num_samples(),
+            )
+        {
+            written += num_samples;
+        } else {
+            dropped += num_samples;
+        }
+
+        shared
+            .recorded_samples
+            .fetch_add(written, Ordering::Relaxed);
+        shared.dropped_samples.fetch_add(dropped, Ordering::Relaxed);
+    }
+
+    fn stopped(&mut self) {}
+}
+
+#[cxx::bridge(namespace = "juce")]
+mod juce {
+    unsafe extern "C++" {
+        include!("cxx_juce.h");
+        include!("cxx_juce_audio_formats/cxx_juce_audio_formats.h");
+
+        type JuceString = crate::juce_core::JuceString;
+        type AudioSampleBuffer = crate::juce_audio_basics::AudioSampleBuffer;
+        type AudioFormatWriter = crate::juce_audio_formats::AudioFormatWriter;
+
+        type TimeSliceThread;
+        type ThreadedWriter;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "makeUnique"]
+        fn time_slice_thread_new(name: &JuceString) -> UniquePtr<TimeSliceThread>;
+
+        #[cxx_name = "startThread"]
+        fn start_thread(self: Pin<&mut TimeSliceThread>) -> bool;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "makeThreadedWriter"]
+        fn make_threaded_writer(
+            writer: UniquePtr<AudioFormatWriter>,
+            thread: &TimeSliceThread,
+            num_samples_to_buffer: i32,
+        ) -> UniquePtr<ThreadedWriter>;
+
+        #[namespace = "cxx_juce"]
+        #[cxx_name = "threadedWriterWrite"]
+        fn threaded_writer_write(
+            writer: Pin<&mut ThreadedWriter>,
+            buffer: &AudioSampleBuffer,
+            start_sample: i32,
+            num_samples: i32,
+        ) -> bool;
+    }
+}
@@ -3,7 +3,9 @@
 mod plugin_description;
 mod plugin_formats;
 mod plugin_instance;
+mod processor_player;
 
 pub use plugin_description::{OwnedArrayPluginDescription, PluginDescription};
 pub use plugin_formats::{AudioPluginFormat, AudioPluginFormatManager};
 pub use plugin_instance::{AudioPlugin, AudioPluginInstance, AudioProcessor};
+pub use processor_player::{AudioProcessorPlayer, AudioProcessorPlayerMidiInput};
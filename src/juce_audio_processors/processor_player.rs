@@ -0,0 +1,197 @@
+use crate::{
+    juce_audio_basics::{AudioSampleBuffer, MidiMessage},
+    juce_audio_devices::{
+        AudioDeviceCallback, AudioDeviceCallbackContext, AudioIODevice, MidiInput,
+        MidiInputCallback,
+    },
+    juce_audio_processors::AudioPluginInstance,
+    juce_core::JuceString,
+};
+use cxx::UniquePtr;
+use std::pin::Pin;
+
+/// Plays an [`AudioPluginInstance`] through an audio device.
+///
+/// The player is an [`AudioDeviceCallback`], so it can be registered with
+/// [`AudioDeviceManager::add_audio_callback`](crate::juce_audio_devices::AudioDeviceManager::add_audio_callback)
+/// like any other callback. MIDI reaches the processor through the inputs returned by
+/// [`AudioProcessorPlayer::midi_input`].
+pub struct AudioProcessorPlayer {
+    // Declared before the processor so that the player lets go of the processor before it is
+    // dropped.
+    player: UniquePtr<juce::AudioProcessorPlayer>,
+    processor: Option<UniquePtr<AudioPluginInstance>>,
+}
+
+// JUCE's player guards its processor with a lock, as it is driven from the audio thread, and
+// plugin instances can be processed on any thread.
+unsafe impl Send for AudioProcessorPlayer {}
+
+impl Default for AudioProcessorPlayer {
+    fn default() -> Self {
+        Self {
+            player: juce::audio_processor_player_new(),
+            processor: None,
+        }
+    }
+}
+
+impl AudioProcessorPlayer {
+    /// Creates a player with no processor, which outputs silence.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a player that plays `processor`.
+    pub fn with_processor(processor: UniquePtr<AudioPluginInstance>) -> Self {
+        let mut player = Self::new();
+        player.set_processor(processor);
+        player
+    }
+
+    /// Replaces the processor being played, returning the previous one.
+    ///
+    /// If the player is running, the new processor is prepared before it starts playing.
+    pub fn set_processor(
+        &mut self,
+        mut processor: UniquePtr<AudioPluginInstance>,
+    ) -> Option<UniquePtr<AudioPluginInstance>> {
+        if processor.is_null() {
+            return self.clear_processor();
+        }
+
+        unsafe {
+            let processor = processor.pin_mut().cast_mut();
+            self.player
+                .pin_mut()
+                .set_processor(processor.get_unchecked_mut());
+        }
+
+        self.processor.replace(processor)
+    }
+
+    /// Stops playing the current processor, releasing its resources, and returns it.
+    pub fn clear_processor(&mut self) -> Option<UniquePtr<AudioPluginInstance>> {
+        unsafe {
+            self.player.pin_mut().set_processor(std::ptr::null_mut());
+        }
+
+        self.processor.take()
+    }
+
+    /// Returns the processor being played.
+    pub fn processor(&self) -> Option<&AudioPluginInstance> {
+        self.processor
+            .as_ref()
+            .and_then(|processor| processor.as_ref())
+    }
+
+    /// Returns a MIDI input callback that passes messages on to the processor.
+    ///
+    /// The callback can be registered with
+    /// [`AudioDeviceManager::add_midi_input_device_callback`](crate::juce_audio_devices::AudioDeviceManager::add_midi_input_device_callback)
+    /// to route a device's MIDI into the processor. Messages that arrive after the player has
+    /// been dropped are ignored.
+    pub fn midi_input(&self) -> AudioProcessorPlayerMidiInput {
+        AudioProcessorPlayerMidiInput {
+            input: self.player.make_midi_input(),
+        }
+    }
+
+    fn callback(&mut self) -> Pin<&mut juce::AudioIODeviceCallback> {
+        juce::audio_processor_player_as_callback(self.player.pin_mut())
+    }
+}
+
+impl AudioDeviceCallback for AudioProcessorPlayer {
+    fn about_to_start(&mut self, device: Pin<&mut AudioIODevice>) {
+        self.callback().about_to_start(device);
+    }
+
+    fn process_block(&mut self, input: &AudioSampleBuffer, output: Pin<&mut AudioSampleBuffer>) {
+        self.process_block_with_context(input, output, &AudioDeviceCallbackContext::default());
+    }
+
+    fn process_block_with_context(
+        &mut self,
+        input: &AudioSampleBuffer,
+        output: Pin<&mut AudioSampleBuffer>,
+        context: &AudioDeviceCallbackContext,
+    ) {
+        self.callback().process_block(input, output, context);
+    }
+
+    fn stopped(&mut self) {
+        self.callback().stopped();
+    }
+
+    fn device_error(&mut self, message: &str) {
+        self.callback().device_error(&JuceString::new(message));
+    }
+}
+
+/// A [`MidiInputCallback`] that passes messages on to an [`AudioProcessorPlayer`].
+///
+/// Created with [`AudioProcessorPlayer::midi_input`].
+pub struct AudioProcessorPlayerMidiInput {
+    input: UniquePtr<juce::AudioProcessorPlayerMidiInput>,
+}
+
+// The input forwards messages to the player under a lock, and the player queues them for the
+// audio thread.
+unsafe impl Send for AudioProcessorPlayerMidiInput {}
+
+impl AudioProcessorPlayerMidiInput {
+    /// Passes a message on to the player, to be delivered to the processor in the next block.
+    pub fn add_message(&self, message: &MidiMessage) {
+        self.input.add_message(message);
+    }
+}
+
+impl MidiInputCallback for AudioProcessorPlayerMidiInput {
+    fn handle_incoming_midi_message(&mut self, _source: &MidiInput, message: &MidiMessage) {
+        self.add_message(message);
+    }
+}
+
+#[cxx::bridge(namespace = "cxx_juce")]
+mod juce {
+    unsafe extern "C++" {
+        include!("cxx_juce.h");
+        include!("cxx_juce_audio_processors/cxx_juce_audio_processors.h");
+
+        #[namespace = "juce"]
+        type AudioProcessor = crate::juce_audio_processors::AudioProcessor;
+
+        #[namespace = "juce"]
+        type AudioIODeviceCallback = crate::juce_audio_devices::AudioIODeviceCallback;
+
+        #[namespace = "juce"]
+        type MidiMessage = crate::juce_audio_basics::MidiMessage;
+
+        type AudioProcessorPlayer;
+
+        type AudioProcessorPlayerMidiInput;
+
+        #[cxx_name = "makeUnique"]
+        fn audio_processor_player_new() -> UniquePtr<AudioProcessorPlayer>;
+
+        #[cxx_name = "derivedCastMut"]
+        fn audio_processor_player_as_callback(
+            player: Pin<&mut AudioProcessorPlayer>,
+        ) -> Pin<&mut AudioIODeviceCallback>;
+
+        #[cxx_name = "setProcessor"]
+        unsafe fn set_processor(
+            self: Pin<&mut AudioProcessorPlayer>,
+            processor: *mut AudioProcessor,
+        );
+
+        #[cxx_name = "makeMidiInput"]
+        fn make_midi_input(self: &AudioProcessorPlayer)
+            -> UniquePtr<AudioProcessorPlayerMidiInput>;
+
+        #[cxx_name = "addMessage"]
+        fn add_message(self: &AudioProcessorPlayerMidiInput, message: &MidiMessage);
+    }
+}
@@ -52,12 +52,30 @@ mod juce {
             should_be_set: bool,
         ) -> &mut BigInteger;
 
+        /// Sets a bit.
+        #[cxx_name = "setBit"]
+        fn set_bit(self: &mut BigInteger, bit: i32) -> &mut BigInteger;
+
+        /// Returns the index of the first set bit at or after `index`, or -1 if there isn't one.
+        #[cxx_name = "findNextSetBit"]
+        fn find_next_set_bit(self: &BigInteger, index: i32) -> i32;
+
         /// Converts the integer to a string in the given base.
         #[cxx_name = "toString"]
         fn to_string(self: &BigInteger, base: i32, min_chars: i32) -> JuceString;
     }
 }
 
+impl BigInteger {
+    /// Returns the indices of the set bits, in ascending order.
+    pub fn set_bits(&self) -> impl Iterator<Item = i32> + '_ {
+        std::iter::successors(Some(self.find_next_set_bit(0)), |&bit| {
+            Some(self.find_next_set_bit(bit + 1))
+        })
+        .take_while(|&bit| bit >= 0)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -74,6 +92,15 @@ mod test {
         assert_eq!(integer.count_number_of_set_bits(), 3);
     }
 
+    #[test]
+    fn iterate_set_bits() {
+        let mut integer = BigInteger::default();
+        assert_eq!(integer.set_bits().count(), 0);
+
+        integer.set_bit(4).set_bit(5).set_bit(70);
+        assert_eq!(integer.set_bits().collect::<Vec<_>>(), [4, 5, 70]);
+    }
+
     #[test]
     fn to_string() {
         let mut integer = BigInteger::default();
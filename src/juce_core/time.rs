@@ -65,5 +65,12 @@ mod juce {
 
         #[cxx_name = "toMilliseconds"]
         fn to_milliseconds(self_: &Time) -> i64;
+
+        /// Returns a high-resolution millisecond counter, measured from system start-up.
+        ///
+        /// This is the clock that JUCE uses for MIDI timestamps and scheduled MIDI output.
+        #[Self = "Time"]
+        #[cxx_name = "getMillisecondCounterHiRes"]
+        fn get_millisecond_counter_hi_res() -> f64;
     }
 }
@@ -4,6 +4,8 @@
 
 pub mod juce_audio_basics;
 pub mod juce_audio_devices;
+#[cfg(feature = "juce_audio_formats")]
+pub mod juce_audio_formats;
 #[cfg(feature = "juce_audio_processors")]
 pub mod juce_audio_processors;
 pub mod juce_core;
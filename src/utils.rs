@@ -59,6 +59,16 @@ impl std::fmt::Debug for PhantomUnsend {
     }
 }
 
+/// Locks a mutex, recovering the guard if another thread panicked while holding it.
+pub(crate) fn lock<T: ?Sized>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+/// Creates a [`JuceError`](crate::JuceError) with the given message.
+pub(crate) fn error(message: impl AsRef<str>) -> crate::JuceError {
+    crate::JuceError(crate::juce_core::JuceString::new(message))
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! define_juce_type {
@@ -397,14 +407,16 @@ macro_rules! define_trait {
         $cxx_name:literal,
         $(
             $(#[$attr:meta])*
-            fn $method_name:ident ( $($args:tt)* ) $(-> $ret:ty)? $(, @$tag:tt)*;
+            fn $method_name:ident ( $($args:tt)* ) $(-> $ret:ty)? $(, @$tag:tt)* $($default:block)? $(;)?
         )*
     ) => {
         $(#[$trait_attr])*
         pub trait $trait_name $( : $($trait_bounds)+ )? {
             $(
-                $(#[$attr])*
-                fn $method_name( $($args)* ) $(-> $ret)?;
+                define_trait!(@trait_method
+                    $(#[$attr])*
+                    fn $method_name( $($args)* ) $(-> $ret)? $($default)?
+                );
             )*
         }
 
@@ -431,6 +443,20 @@ macro_rules! define_trait {
             )*
         }
     };
+    (@trait_method
+        $(#[$attr:meta])*
+        fn $method:ident ( $($args:tt)* ) $(-> $ret:ty)?
+    ) => {
+        $(#[$attr])*
+        fn $method( $($args)* ) $(-> $ret)?;
+    };
+    (@trait_method
+        $(#[$attr:meta])*
+        fn $method:ident ( $($args:tt)* ) $(-> $ret:ty)? $default:block
+    ) => {
+        $(#[$attr])*
+        fn $method( $($args)* ) $(-> $ret)? $default
+    };
     (@handle_method
         $trait_name:ident,
         $method:ident,
@@ -1,17 +1,28 @@
 use cxx::UniquePtr;
 use cxx_juce::{
+    juce_audio_basics::{AudioSampleBuffer, AudioSource, MidiMessage},
     juce_audio_devices::{
-        AudioDevice, AudioDeviceManager, AudioDeviceSetup, AudioDeviceType, AudioIODevice,
-        ChannelCount,
+        AudioDevice, AudioDeviceCallback, AudioDeviceCallbackContext, AudioDeviceCallbackHandle,
+        AudioDeviceListNotifier, AudioDeviceManager, AudioDeviceManagerOptions, AudioDeviceSetup,
+        AudioDeviceType, AudioIODevice, AudioSourcePlayer, CallbackTimingHistogram, ChannelCount,
+        LatencyTester, MidiDeviceInfoArray, MidiInput, MidiInputCallback, MidiOutput,
+        OfflineAudioDevice, OfflineAudioDeviceType, OfflineDeviceOptions, OfflineInput,
+        OfflineOutput, OfflineRecording, OfflineSpeed, StartedAudioDevice,
     },
-    juce_core::{DoubleArray, IntArray, JuceString, StringArray},
+    juce_core::{BigInteger, DoubleArray, IntArray, JuceString, StringArray},
     JUCE,
 };
+use std::{
+    pin::Pin,
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 #[derive(Default)]
 struct MockAudioDeviceType {
     input_devices: StringArray,
     output_devices: StringArray,
+    notifier: Arc<Mutex<Option<AudioDeviceListNotifier>>>,
 }
 
 impl AudioDeviceType for MockAudioDeviceType {
@@ -45,6 +56,7 @@ impl AudioDeviceType for MockAudioDeviceType {
             type_name: self.name(),
             sample_rate: 44100.0,
             buffer_size: 128,
+            callback: None,
         });
 
         device.into()
@@ -57,6 +69,10 @@ impl AudioDeviceType for MockAudioDeviceType {
     fn has_separate_inputs_and_outputs(&self) -> bool {
         true
     }
+
+    fn set_device_list_notifier(&mut self, notifier: AudioDeviceListNotifier) {
+        *self.notifier.lock().unwrap() = Some(notifier);
+    }
 }
 
 struct MockAudioDevice {
@@ -64,6 +80,7 @@ struct MockAudioDevice {
     type_name: String,
     sample_rate: f64,
     buffer_size: i32,
+    callback: Option<AudioDeviceCallbackHandle>,
 }
 
 impl AudioDevice for MockAudioDevice {
@@ -124,7 +141,7 @@ impl AudioDevice for MockAudioDevice {
     }
 
     fn is_playing(&self) -> bool {
-        false
+        self.callback.is_some()
     }
 
     fn last_error(&self) -> String {
@@ -159,9 +176,151 @@ impl AudioDevice for MockAudioDevice {
         0
     }
 
-    fn start(&mut self) {}
+    fn start(&mut self, mut callback: AudioDeviceCallbackHandle) {
+        let input = AudioSampleBuffer::new(2, self.buffer_size);
+        let mut output = AudioSampleBuffer::new(2, self.buffer_size);
+
+        callback.about_to_start();
+        callback.process_block_with_context(
+            &input,
+            output.pin_mut(),
+            &AudioDeviceCallbackContext {
+                host_time_ns: Some(1_000_000),
+            },
+        );
+
+        if self.name.starts_with("Headset") {
+            callback.error("Headset disconnected");
+        }
+
+        self.callback = Some(callback);
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut callback) = self.callback.take() {
+            callback.stopped();
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct EventLog(Arc<Mutex<Vec<String>>>);
+
+impl EventLog {
+    fn push(&self, event: impl Into<String>) {
+        self.0.lock().unwrap().push(event.into());
+    }
+
+    fn events(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl AudioDeviceCallback for EventLog {
+    fn about_to_start(&mut self, mut device: Pin<&mut AudioIODevice>) {
+        self.push(format!(
+            "about to start at {}",
+            device.as_mut().get_current_sample_rate()
+        ));
+    }
+
+    fn process_block(&mut self, _input: &AudioSampleBuffer, output: Pin<&mut AudioSampleBuffer>) {
+        self.push(format!("process {} samples", output.get_num_samples()));
+    }
+
+    fn stopped(&mut self) {
+        self.push("stopped");
+    }
+
+    fn device_error(&mut self, message: &str) {
+        self.push(format!("error: {message}"));
+    }
+}
+
+#[derive(Clone, Default)]
+struct HostTimeLog(Arc<Mutex<Vec<Option<u64>>>>);
+
+impl AudioDeviceCallback for HostTimeLog {
+    fn about_to_start(&mut self, _device: Pin<&mut AudioIODevice>) {}
+
+    fn process_block(&mut self, _input: &AudioSampleBuffer, _output: Pin<&mut AudioSampleBuffer>) {
+        self.0.lock().unwrap().push(None);
+    }
+
+    fn process_block_with_context(
+        &mut self,
+        _input: &AudioSampleBuffer,
+        _output: Pin<&mut AudioSampleBuffer>,
+        context: &AudioDeviceCallbackContext,
+    ) {
+        self.0.lock().unwrap().push(context.host_time_ns);
+    }
+
+    fn stopped(&mut self) {}
+}
+
+struct PassThrough;
+
+impl AudioDeviceCallback for PassThrough {
+    fn about_to_start(&mut self, _device: Pin<&mut AudioIODevice>) {}
+
+    fn process_block(
+        &mut self,
+        input: &AudioSampleBuffer,
+        mut output: Pin<&mut AudioSampleBuffer>,
+    ) {
+        for channel in 0..output.get_num_channels().min(input.get_num_channels()) {
+            output
+                .as_mut()
+                .get_write_slice(channel)
+                .copy_from_slice(input.get_read_slice(channel));
+        }
+    }
+
+    fn stopped(&mut self) {}
+}
+
+struct ConstantSource(f32);
+
+impl AudioSource for ConstantSource {
+    fn prepare_to_play(&mut self, _samples_per_block_expected: i32, _sample_rate: f64) {}
+
+    fn get_next_audio_block(
+        &mut self,
+        mut buffer: Pin<&mut AudioSampleBuffer>,
+        start_sample: i32,
+        num_samples: i32,
+    ) {
+        let range = start_sample as usize..(start_sample + num_samples) as usize;
+        for channel in 0..buffer.get_num_channels() {
+            buffer.as_mut().get_write_slice(channel)[range.clone()].fill(self.0);
+        }
+    }
+}
+
+fn open_offline_device(
+    options: OfflineDeviceOptions,
+    sample_rate: f64,
+    buffer_size: i32,
+) -> UniquePtr<AudioIODevice> {
+    let mut channels = BigInteger::default();
+    channels.set_range(0, 1, true);
+
+    let device: Box<dyn AudioDevice> = Box::new(OfflineAudioDevice::new(options));
+    let mut device: UniquePtr<AudioIODevice> = device.into();
+    let error = device
+        .pin_mut()
+        .open(&channels, &channels, sample_rate, buffer_size);
+    assert_eq!(error, "");
 
-    fn stop(&mut self) {}
+    device
+}
+
+fn wait_for_samples(recording: &OfflineRecording, num_samples: usize) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while recording.len() < num_samples && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(1));
+    }
 }
 
 #[test]
@@ -243,6 +402,127 @@ fn can_create_devices() {
     assert_eq!(device.get_type_name(), "Test");
 }
 
+#[test]
+fn can_query_device_properties() {
+    let juce = JUCE::initialise();
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType::default());
+    audio_device_manager.set_current_audio_device_type("Test", true);
+
+    let mut device = audio_device_manager
+        .current_device_type()
+        .unwrap()
+        .create_device("Microphone", "Speakers")
+        .expect("failed to create device");
+
+    assert!(!device.pin_mut().is_open());
+    assert!(!device.pin_mut().is_playing());
+    assert_eq!(device.pin_mut().get_last_error(), "");
+    assert_eq!(
+        device.pin_mut().get_output_channel_names().as_slice(),
+        ["Left", "Right"]
+    );
+    assert_eq!(
+        device.pin_mut().get_input_channel_names().as_slice(),
+        ["Left", "Right"]
+    );
+    assert_eq!(device.pin_mut().get_default_buffer_size(), 512);
+    assert_eq!(device.pin_mut().get_current_bit_depth(), 24);
+    assert_eq!(device.pin_mut().get_output_latency_in_samples(), 0);
+    assert_eq!(device.pin_mut().get_input_latency_in_samples(), 0);
+    assert_eq!(device.get_xrun_count(), 0);
+}
+
+#[test]
+fn rust_devices_drive_audio_callbacks() {
+    let juce = JUCE::initialise();
+    let log = EventLog::default();
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType::default());
+    audio_device_manager.set_current_audio_device_type("Test", true);
+    audio_device_manager
+        .current_device_type()
+        .unwrap()
+        .scan_for_devices();
+
+    let _handle = audio_device_manager.add_audio_callback(log.clone());
+
+    let setup = AudioDeviceSetup::default()
+        .with_buffer_size(256)
+        .with_sample_rate(48000.0)
+        .with_input_device_name("Microphone")
+        .with_output_device_name("Speakers");
+
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+
+    assert_eq!(
+        log.events(),
+        ["about to start at 48000", "process 256 samples"]
+    );
+
+    drop(audio_device_manager);
+
+    assert_eq!(log.events().last().map(String::as_str), Some("stopped"));
+}
+
+#[test]
+fn audio_callbacks_receive_the_host_time() {
+    let juce = JUCE::initialise();
+    let log = HostTimeLog::default();
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType::default());
+    audio_device_manager.set_current_audio_device_type("Test", true);
+    audio_device_manager
+        .current_device_type()
+        .unwrap()
+        .scan_for_devices();
+
+    let _handle = audio_device_manager.add_audio_callback(log.clone());
+
+    let setup = AudioDeviceSetup::default()
+        .with_input_device_name("Microphone")
+        .with_output_device_name("Speakers");
+
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+
+    assert_eq!(*log.0.lock().unwrap(), [Some(1_000_000)]);
+}
+
+#[test]
+fn audio_callbacks_receive_device_errors() {
+    let juce = JUCE::initialise();
+    let log = EventLog::default();
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType::default());
+    audio_device_manager.set_current_audio_device_type("Test", true);
+    audio_device_manager
+        .current_device_type()
+        .unwrap()
+        .scan_for_devices();
+
+    let _handle = audio_device_manager.add_audio_callback(log.clone());
+
+    let setup = AudioDeviceSetup::default()
+        .with_input_device_name("Headset")
+        .with_output_device_name("Speakers");
+
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+
+    assert!(log
+        .events()
+        .contains(&"error: Headset disconnected".to_string()));
+}
+
 #[test]
 fn can_configure_channel_count_in_audio_device_setup() {
     let setup = AudioDeviceSetup::default()
@@ -252,3 +532,1215 @@ fn can_configure_channel_count_in_audio_device_setup() {
     assert_eq!(setup.input_channels(), ChannelCount::Custom(4));
     assert_eq!(setup.output_channels(), ChannelCount::Default);
 }
+
+fn sixteen_channel_names() -> StringArray {
+    (1..=16)
+        .map(|index| JuceString::new(format!("Input {index}")))
+        .collect()
+}
+
+#[test]
+fn can_select_channels_by_index_in_audio_device_setup() {
+    let channel_names = sixteen_channel_names();
+
+    let setup = AudioDeviceSetup::default()
+        .with_input_channel_indices(&channel_names, [4, 5])
+        .unwrap()
+        .with_output_channels(ChannelCount::Default);
+
+    assert_eq!(setup.input_channel_indices(), [4, 5]);
+    assert_eq!(setup.input_channels(), ChannelCount::Custom(2));
+    assert!(setup.output_channel_indices().is_empty());
+
+    let setup = setup
+        .with_output_channel_indices(&channel_names, [0, 15])
+        .unwrap();
+    assert_eq!(setup.output_channel_indices(), [0, 15]);
+    assert_eq!(setup.output_channels(), ChannelCount::Custom(2));
+}
+
+#[test]
+fn selecting_channels_that_do_not_exist_is_an_error() {
+    let channel_names = sixteen_channel_names();
+
+    for index in [-1, 16] {
+        let Err(error) =
+            AudioDeviceSetup::default().with_input_channel_indices(&channel_names, [0, index])
+        else {
+            panic!("expected channel {index} to be an error");
+        };
+        assert_eq!(
+            error.to_string(),
+            format!("The device has no input channel {index}")
+        );
+    }
+}
+
+#[test]
+fn can_select_channels_by_name_in_audio_device_setup() {
+    let channel_names = sixteen_channel_names();
+
+    let setup = AudioDeviceSetup::default()
+        .with_input_channel_names(&channel_names, ["Input 5", "Input 6"])
+        .unwrap();
+    assert_eq!(setup.input_channel_indices(), [4, 5]);
+
+    let Err(error) =
+        AudioDeviceSetup::default().with_output_channel_names(&channel_names, ["Input 17"])
+    else {
+        panic!("expected an unknown channel name to be an error");
+    };
+    assert_eq!(
+        error.to_string(),
+        "The device has no output channel called \"Input 17\""
+    );
+}
+
+#[test]
+fn device_manager_opens_the_selected_channels() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = scanned_device_manager(&juce);
+    audio_device_manager.initialise(2, 2).unwrap();
+
+    let input_channel_names = audio_device_manager
+        .current_device()
+        .unwrap()
+        .get_input_channel_names();
+
+    let setup = audio_device_manager
+        .audio_device_setup()
+        .with_input_channel_names(&input_channel_names, ["Right"])
+        .unwrap();
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+
+    let setup = audio_device_manager.audio_device_setup();
+    assert_eq!(setup.input_channel_indices(), [1]);
+}
+
+#[test]
+fn offline_devices_pass_generated_input_to_memory() {
+    let _juce = JUCE::initialise();
+    let recording = OfflineRecording::new();
+
+    let mut next_sample = 0.0;
+    let options = OfflineDeviceOptions::default()
+        .with_input(OfflineInput::generator(move |mut buffer| {
+            for sample in buffer.as_mut().get_write_slice(0) {
+                *sample = next_sample;
+                next_sample += 1.0;
+            }
+        }))
+        .with_output(OfflineOutput::Memory(recording.clone()))
+        .with_speed(OfflineSpeed::AsFastAsPossible)
+        .with_input_channels(1)
+        .with_output_channels(1)
+        .with_length_in_samples(1000);
+
+    let device = open_offline_device(options, 48000.0, 256);
+    let device = AudioIODevice::start(device, PassThrough);
+    wait_for_samples(&recording, 1000);
+    device.stop();
+
+    let expected: Vec<f32> = (0..1000).map(|sample| sample as f32).collect();
+    assert_eq!(recording.num_channels(), 1);
+    assert_eq!(recording.channel(0), expected);
+}
+
+#[test]
+fn audio_source_players_play_sources_through_devices() {
+    let _juce = JUCE::initialise();
+    let recording = OfflineRecording::new();
+
+    let options = OfflineDeviceOptions::default()
+        .with_output(OfflineOutput::Memory(recording.clone()))
+        .with_speed(OfflineSpeed::AsFastAsPossible)
+        .with_input_channels(1)
+        .with_output_channels(1)
+        .with_length_in_samples(512);
+
+    let mut player = AudioSourcePlayer::with_source(ConstantSource(0.5));
+    player.set_gain(0.5);
+    assert!(player.has_source());
+    assert_eq!(player.gain(), 0.5);
+
+    let device = open_offline_device(options, 48000.0, 256);
+    let device = AudioIODevice::start(device, player);
+    wait_for_samples(&recording, 512);
+    device.stop();
+
+    assert_eq!(recording.channel(0), vec![0.25; 512]);
+}
+
+#[test]
+fn audio_source_players_without_a_source_are_silent() {
+    let _juce = JUCE::initialise();
+    let recording = OfflineRecording::new();
+
+    let options = OfflineDeviceOptions::default()
+        .with_input(OfflineInput::generator(|mut buffer| {
+            buffer.as_mut().get_write_slice(0).fill(1.0);
+        }))
+        .with_output(OfflineOutput::Memory(recording.clone()))
+        .with_speed(OfflineSpeed::AsFastAsPossible)
+        .with_input_channels(1)
+        .with_output_channels(1)
+        .with_length_in_samples(256);
+
+    let mut player = AudioSourcePlayer::with_source(ConstantSource(1.0));
+    player.clear_source();
+    assert!(!player.has_source());
+
+    let device = open_offline_device(options, 48000.0, 256);
+    let device = AudioIODevice::start(device, player);
+    wait_for_samples(&recording, 256);
+    device.stop();
+
+    assert_eq!(recording.channel(0), vec![0.0; 256]);
+}
+
+#[test]
+fn offline_device_type_runs_the_device_manager() {
+    let juce = JUCE::initialise();
+    let log = EventLog::default();
+    let recording = OfflineRecording::new();
+
+    let options = OfflineDeviceOptions::default()
+        .with_output(OfflineOutput::Memory(recording.clone()))
+        .with_speed(OfflineSpeed::AsFastAsPossible)
+        .with_length_in_samples(1024);
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(OfflineAudioDeviceType::new(options));
+    audio_device_manager.set_current_audio_device_type(OfflineAudioDeviceType::NAME, true);
+
+    let _handle = audio_device_manager.add_audio_callback(log.clone());
+
+    let setup = AudioDeviceSetup::default()
+        .with_buffer_size(256)
+        .with_sample_rate(48000.0)
+        .with_input_device_name(OfflineAudioDeviceType::NAME)
+        .with_output_device_name(OfflineAudioDeviceType::NAME);
+
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+
+    wait_for_samples(&recording, 1024);
+    drop(audio_device_manager);
+
+    assert_eq!(recording.len(), 1024);
+    assert_eq!(recording.num_channels(), 2);
+    assert_eq!(log.events().first().unwrap(), "about to start at 48000");
+    assert_eq!(
+        log.events()
+            .iter()
+            .filter(|event| *event == "process 256 samples")
+            .count(),
+        4
+    );
+}
+
+#[cfg(feature = "juce_audio_formats")]
+#[test]
+fn writers_reject_ranges_outside_the_buffer() {
+    let _juce = JUCE::initialise();
+    let directory = std::env::temp_dir().join(format!("cxx-juce-writer-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("output.wav");
+
+    let mut writer = cxx_juce::juce_audio_formats::WavAudioFormat::create_writer_for(
+        &cxx_juce::juce_core::File::from_absolute_path(path.to_string_lossy().as_ref()),
+        44100.0,
+        1,
+        16,
+    )
+    .expect("failed to create the writer");
+
+    let source = AudioSampleBuffer::new(1, 64);
+    assert!(!writer
+        .pin_mut()
+        .write_from_audio_sample_buffer(&source, -1, 16));
+    assert!(!writer
+        .pin_mut()
+        .write_from_audio_sample_buffer(&source, 32, 64));
+    assert!(!writer
+        .pin_mut()
+        .write_from_audio_sample_buffer(&source, 0, -1));
+    assert!(!writer.pin_mut().write_from_audio_sample_buffer(
+        &AudioSampleBuffer::new(0, 64),
+        0,
+        64
+    ));
+    assert!(writer
+        .pin_mut()
+        .write_from_audio_sample_buffer(&source, 32, 32));
+
+    drop(writer);
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[cfg(feature = "juce_audio_formats")]
+#[test]
+fn offline_devices_can_play_and_record_wav_files() {
+    let _juce = JUCE::initialise();
+    let directory = std::env::temp_dir().join(format!("cxx-juce-offline-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let input_path = directory.join("input.wav");
+    let output_path = directory.join("output.wav");
+
+    let generate = OfflineDeviceOptions::default()
+        .with_input(OfflineInput::generator(|mut buffer| {
+            buffer.as_mut().get_write_slice(0).fill(0.5);
+        }))
+        .with_output(OfflineOutput::WavFile(input_path.clone()))
+        .with_speed(OfflineSpeed::AsFastAsPossible)
+        .with_input_channels(1)
+        .with_output_channels(1)
+        .with_length_in_samples(512);
+
+    let copy = OfflineDeviceOptions::default()
+        .with_input(OfflineInput::WavFile(input_path))
+        .with_output(OfflineOutput::WavFile(output_path.clone()))
+        .with_speed(OfflineSpeed::AsFastAsPossible)
+        .with_input_channels(1)
+        .with_output_channels(1)
+        .with_length_in_samples(512);
+
+    for options in [generate, copy] {
+        let device = open_offline_device(options, 44100.0, 128);
+        let mut device = AudioIODevice::start(device, PassThrough);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while device.pin_mut().is_playing() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        device.stop();
+    }
+
+    let mut output = cxx_juce::juce_audio_formats::WavAudioFormat::create_reader_for(
+        &cxx_juce::juce_core::File::from_absolute_path(output_path.to_string_lossy().as_ref()),
+    )
+    .expect("failed to open the recorded file");
+
+    assert_eq!(output.length_in_samples(), 512);
+    assert_eq!(output.num_channels(), 1);
+
+    let mut samples = AudioSampleBuffer::new(1, 512);
+    assert!(output.pin_mut().read(samples.pin_mut(), 0, 512, 0));
+    assert!(samples
+        .get_read_slice(0)
+        .iter()
+        .all(|&sample| sample == 0.5));
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn device_manager_state_can_be_saved_and_restored() {
+    let juce = JUCE::initialise();
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType::default());
+    audio_device_manager.set_current_audio_device_type("Test", false);
+    audio_device_manager
+        .current_device_type()
+        .unwrap()
+        .scan_for_devices();
+
+    assert_eq!(audio_device_manager.create_state_xml(), None);
+
+    let setup = AudioDeviceSetup::default()
+        .with_buffer_size(128)
+        .with_sample_rate(48000.0)
+        .with_input_device_name("Headset")
+        .with_output_device_name("Headphones");
+
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+
+    let state = audio_device_manager
+        .create_state_xml()
+        .expect("the chosen device should be saved");
+    drop(audio_device_manager);
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType::default());
+    audio_device_manager
+        .initialise_with_state(2, 2, &state)
+        .unwrap();
+
+    let restored_setup = audio_device_manager.audio_device_setup();
+
+    assert_eq!(restored_setup.buffer_size, 128);
+    assert_eq!(restored_setup.sample_rate, 48000.0);
+    assert_eq!(restored_setup.input_device_name, "Headset");
+    assert_eq!(restored_setup.output_device_name, "Headphones");
+    assert_eq!(
+        audio_device_manager
+            .current_device_type()
+            .unwrap()
+            .get_type_name(),
+        "Test"
+    );
+}
+
+#[test]
+fn invalid_device_manager_state_is_an_error() {
+    let juce = JUCE::initialise();
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType::default());
+
+    assert!(audio_device_manager
+        .initialise_with_state(2, 2, "not xml")
+        .is_err());
+}
+
+fn scanned_device_manager(juce: &JUCE) -> AudioDeviceManager {
+    let mut audio_device_manager = AudioDeviceManager::new(juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType::default());
+    audio_device_manager.set_current_audio_device_type("Test", false);
+    audio_device_manager
+        .current_device_type()
+        .unwrap()
+        .scan_for_devices();
+    audio_device_manager
+}
+
+const MISSING_DEVICE_STATE: &str = r#"<DEVICESETUP deviceType="Test" audioInputDeviceName="Venue Interface" audioOutputDeviceName="Venue Interface"/>"#;
+
+#[test]
+fn initialise_prefers_matching_devices() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = scanned_device_manager(&juce);
+
+    audio_device_manager
+        .initialise_with(
+            &AudioDeviceManagerOptions::new(2, 2)
+                .with_preferred_device_type("Test")
+                .with_preferred_device_name("head*"),
+        )
+        .unwrap();
+
+    let setup = audio_device_manager.audio_device_setup();
+    assert_eq!(setup.input_device_name, "Headset");
+    assert_eq!(setup.output_device_name, "Headphones");
+}
+
+#[test]
+fn initialise_uses_the_default_devices_when_the_preferred_device_is_missing() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = scanned_device_manager(&juce);
+
+    audio_device_manager
+        .initialise_with(
+            &AudioDeviceManagerOptions::new(2, 2).with_preferred_device_name("Venue Interface"),
+        )
+        .unwrap();
+
+    let setup = audio_device_manager.audio_device_setup();
+    assert_eq!(setup.input_device_name, "Microphone");
+    assert_eq!(setup.output_device_name, "Speakers");
+}
+
+#[test]
+fn initialise_uses_the_default_devices_when_the_saved_device_is_missing() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = scanned_device_manager(&juce);
+
+    audio_device_manager
+        .initialise_with(
+            &AudioDeviceManagerOptions::new(2, 2)
+                .with_saved_state(MISSING_DEVICE_STATE)
+                .with_fall_back_to_default_device(true),
+        )
+        .unwrap();
+
+    let setup = audio_device_manager.audio_device_setup();
+    assert_eq!(setup.input_device_name, "Microphone");
+    assert_eq!(setup.output_device_name, "Speakers");
+}
+
+#[test]
+fn initialise_fails_when_the_saved_device_is_missing() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = scanned_device_manager(&juce);
+
+    assert!(audio_device_manager
+        .initialise_with(
+            &AudioDeviceManagerOptions::new(2, 2).with_saved_state(MISSING_DEVICE_STATE)
+        )
+        .is_err());
+}
+
+#[test]
+fn initialise_fails_when_the_preferred_device_type_is_missing() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = scanned_device_manager(&juce);
+
+    assert!(audio_device_manager
+        .initialise_with(&AudioDeviceManagerOptions::new(2, 2).with_preferred_device_type("Venue"))
+        .is_err());
+}
+
+#[test]
+fn initialise_uses_the_preferred_setup() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = scanned_device_manager(&juce);
+
+    let preferred_setup = AudioDeviceSetup::default()
+        .with_buffer_size(256)
+        .with_sample_rate(48000.0)
+        .with_input_device_name("Audio Interface")
+        .with_output_device_name("Headphones");
+
+    audio_device_manager
+        .initialise_with(
+            &AudioDeviceManagerOptions::new(2, 2).with_preferred_setup(preferred_setup),
+        )
+        .unwrap();
+
+    let setup = audio_device_manager.audio_device_setup();
+    assert_eq!(setup.buffer_size, 256);
+    assert_eq!(setup.sample_rate, 48000.0);
+    assert_eq!(setup.input_device_name, "Audio Interface");
+    assert_eq!(setup.output_device_name, "Headphones");
+}
+
+#[test]
+fn device_manager_reports_device_changes() {
+    let juce = JUCE::initialise();
+    let notifier = Arc::default();
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType {
+        notifier: Arc::clone(&notifier),
+        ..MockAudioDeviceType::default()
+    });
+    audio_device_manager.set_current_audio_device_type("Test", false);
+    audio_device_manager
+        .current_device_type()
+        .unwrap()
+        .scan_for_devices();
+    audio_device_manager.dispatch_pending_messages();
+
+    let setup_changes = Arc::new(Mutex::new(Vec::new()));
+    let _setup_listener = audio_device_manager.add_change_listener({
+        let setup_changes = Arc::clone(&setup_changes);
+        move |previous, current| {
+            setup_changes.lock().unwrap().push((
+                previous.output_device_name.to_string(),
+                current.output_device_name.to_string(),
+            ));
+        }
+    });
+
+    let device_list_changes = Arc::new(Mutex::new(0));
+    let _device_list_listener = audio_device_manager.add_device_list_listener({
+        let device_list_changes = Arc::clone(&device_list_changes);
+        move || *device_list_changes.lock().unwrap() += 1
+    });
+
+    let setup = AudioDeviceSetup::default()
+        .with_input_device_name("Microphone")
+        .with_output_device_name("Headphones");
+
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+    audio_device_manager.dispatch_pending_messages();
+
+    assert_eq!(
+        setup_changes.lock().unwrap().first(),
+        Some(&("Speakers".to_string(), "Headphones".to_string()))
+    );
+
+    notifier
+        .lock()
+        .unwrap()
+        .as_ref()
+        .expect("the device type should have been given a notifier")
+        .notify();
+
+    assert_eq!(*device_list_changes.lock().unwrap(), 1);
+}
+
+struct IgnoreMidi;
+
+impl MidiInputCallback for IgnoreMidi {
+    fn handle_incoming_midi_message(&mut self, _source: &MidiInput, _message: &MidiMessage) {}
+}
+
+#[test]
+fn device_manager_ignores_missing_midi_devices() {
+    let juce = JUCE::initialise();
+    let missing = JuceString::new("missing MIDI device");
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+
+    audio_device_manager.set_midi_input_device_enabled(&missing, true);
+    assert!(!audio_device_manager.is_midi_input_device_enabled(&missing));
+
+    let handle = audio_device_manager.add_midi_input_device_callback(&missing, IgnoreMidi);
+    audio_device_manager.remove_midi_input_device_callback(handle);
+
+    audio_device_manager.set_default_midi_output_device(&missing);
+    assert!(audio_device_manager.default_midi_output().is_none());
+
+    audio_device_manager.set_default_midi_output_device(&JuceString::default());
+    assert_eq!(audio_device_manager.default_midi_output_identifier(), "");
+}
+
+struct ForwardNotes(mpsc::Sender<i32>);
+
+impl MidiInputCallback for ForwardNotes {
+    fn handle_incoming_midi_message(&mut self, _source: &MidiInput, message: &MidiMessage) {
+        let _ = self.0.send(message.get_note_number());
+    }
+}
+
+fn midi_device_identifier(devices: MidiDeviceInfoArray, name: &str) -> Option<JuceString> {
+    devices
+        .as_slice()
+        .iter()
+        .find(|device| device.name == name)
+        .map(|device| device.identifier.clone())
+}
+
+#[test]
+fn device_manager_routes_and_restores_virtual_midi_devices() {
+    let juce = JUCE::initialise();
+
+    // Virtual devices aren't available on every platform or in every sandbox.
+    let mut virtual_output = MidiOutput::create_new_device(&JuceString::new("cxx-juce source"));
+    let Some(virtual_input) =
+        MidiInput::create_new_device(&JuceString::new("cxx-juce sink"), |_| {})
+    else {
+        return;
+    };
+    if virtual_output.is_null() {
+        return;
+    }
+    let (Some(input), Some(output)) = (
+        midi_device_identifier(MidiInput::get_available_devices(), "cxx-juce source"),
+        midi_device_identifier(MidiOutput::get_available_devices(), "cxx-juce sink"),
+    ) else {
+        return;
+    };
+
+    let mut audio_device_manager = scanned_device_manager(&juce);
+    audio_device_manager.set_midi_input_device_enabled(&input, true);
+    assert!(audio_device_manager.is_midi_input_device_enabled(&input));
+
+    let (sender, receiver) = mpsc::channel();
+    let handle = audio_device_manager.add_midi_input_device_callback(&input, ForwardNotes(sender));
+
+    virtual_output
+        .pin_mut()
+        .send_message_now(&MidiMessage::note_on(1, 64, 0.5));
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(64));
+
+    audio_device_manager.remove_midi_input_device_callback(handle);
+
+    audio_device_manager.set_default_midi_output_device(&output);
+    assert!(audio_device_manager.default_midi_output().is_some());
+
+    let state = audio_device_manager
+        .create_state_xml()
+        .expect("the MIDI settings should be saved");
+    drop(audio_device_manager);
+
+    let mut audio_device_manager = scanned_device_manager(&juce);
+    audio_device_manager
+        .initialise_with_state(0, 0, &state)
+        .unwrap();
+
+    assert!(audio_device_manager.is_midi_input_device_enabled(&input));
+    assert_eq!(
+        audio_device_manager.default_midi_output_identifier(),
+        output
+    );
+
+    drop(virtual_input);
+}
+
+struct CompleteSysex(mpsc::Sender<String>);
+
+impl MidiInputCallback for CompleteSysex {
+    fn handle_incoming_midi_message(&mut self, source: &MidiInput, message: &MidiMessage) {
+        if message.is_sys_ex() {
+            let _ = self.0.send(source.get_device_info().name.to_string());
+        }
+    }
+}
+
+struct PartialSysex {
+    complete: CompleteSysex,
+    partial: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl MidiInputCallback for PartialSysex {
+    fn handle_incoming_midi_message(&mut self, source: &MidiInput, message: &MidiMessage) {
+        self.complete.handle_incoming_midi_message(source, message);
+    }
+
+    fn handle_partial_sysex_message(&mut self, _source: &MidiInput, data: &[u8], _: f64) {
+        self.partial.lock().unwrap().push(data.to_vec());
+    }
+}
+
+#[test]
+fn midi_input_callbacks_receive_their_source_and_partial_sysex() {
+    let _juce = JUCE::initialise();
+
+    // Virtual devices aren't available on every platform or in every sandbox.
+    let mut virtual_output = MidiOutput::create_new_device(&JuceString::new("cxx-juce sysex"));
+    if virtual_output.is_null() {
+        return;
+    }
+    let Some(identifier) =
+        midi_device_identifier(MidiInput::get_available_devices(), "cxx-juce sysex")
+    else {
+        return;
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let partial = Arc::new(Mutex::new(Vec::new()));
+
+    let mut with_partial = MidiInput::open_with_callback(
+        &identifier,
+        PartialSysex {
+            complete: CompleteSysex(sender.clone()),
+            partial: Arc::clone(&partial),
+        },
+    )
+    .expect("failed to open the virtual device");
+    let mut without_partial = MidiInput::open_with_callback(&identifier, CompleteSysex(sender))
+        .expect("failed to open the virtual device");
+    with_partial.pin_mut().start();
+    without_partial.pin_mut().start();
+
+    // Long enough to be split up on the way, so it arrives in pieces.
+    let mut sysex = vec![0xf0, 0x7d];
+    sysex.extend((0..40_000).map(|index| (index % 128) as u8));
+    sysex.push(0xf7);
+
+    virtual_output
+        .pin_mut()
+        .send_message_now(&MidiMessage::from_bytes(&sysex).unwrap());
+
+    for _ in 0..2 {
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+            "cxx-juce sysex"
+        );
+    }
+
+    let partial = partial.lock().unwrap();
+    assert!(!partial.is_empty());
+    assert!(partial
+        .iter()
+        .all(|data| data.len() < sysex.len() && sysex.starts_with(data)));
+
+    with_partial.pin_mut().stop();
+    without_partial.pin_mut().stop();
+}
+
+#[test]
+fn device_manager_reports_load_and_xruns_while_stopped() {
+    let juce = JUCE::initialise();
+    let audio_device_manager = AudioDeviceManager::new(&juce);
+
+    assert_eq!(audio_device_manager.cpu_usage(), 0.0);
+    assert_eq!(audio_device_manager.xrun_count(), 0);
+}
+
+struct SlowCallback;
+
+impl AudioDeviceCallback for SlowCallback {
+    fn about_to_start(&mut self, _device: Pin<&mut AudioIODevice>) {}
+
+    fn process_block(&mut self, _input: &AudioSampleBuffer, _output: Pin<&mut AudioSampleBuffer>) {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    fn stopped(&mut self) {}
+}
+
+#[test]
+fn device_manager_reports_load_while_running() {
+    let juce = JUCE::initialise();
+    let recording = OfflineRecording::new();
+
+    let options = OfflineDeviceOptions::default()
+        .with_output(OfflineOutput::Memory(recording.clone()))
+        .with_speed(OfflineSpeed::AsFastAsPossible);
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(OfflineAudioDeviceType::new(options));
+    audio_device_manager.set_current_audio_device_type(OfflineAudioDeviceType::NAME, false);
+
+    let _handle = audio_device_manager.add_audio_callback(SlowCallback);
+
+    let setup = AudioDeviceSetup::default()
+        .with_buffer_size(256)
+        .with_sample_rate(48000.0)
+        .with_input_device_name(OfflineAudioDeviceType::NAME)
+        .with_output_device_name(OfflineAudioDeviceType::NAME);
+
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+
+    wait_for_samples(&recording, 4096);
+
+    assert!(audio_device_manager.cpu_usage() > 0.0);
+}
+
+#[test]
+fn timed_callbacks_record_every_block() {
+    let juce = JUCE::initialise();
+    let histogram = CallbackTimingHistogram::new();
+
+    let mut audio_device_manager = scanned_device_manager(&juce);
+    let _handle = audio_device_manager.add_audio_callback(histogram.wrap(EventLog::default()));
+
+    let setup = AudioDeviceSetup::default()
+        .with_buffer_size(256)
+        .with_sample_rate(48000.0)
+        .with_input_device_name("Microphone")
+        .with_output_device_name("Speakers");
+
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+
+    assert_eq!(histogram.total(), 1);
+}
+
+#[test]
+fn device_manager_meters_input_and_output_levels() {
+    let juce = JUCE::initialise();
+    let recording = OfflineRecording::new();
+
+    let options = OfflineDeviceOptions::default()
+        .with_input(OfflineInput::generator(|mut buffer| {
+            for channel in 0..buffer.get_num_channels() {
+                buffer.as_mut().get_write_slice(channel).fill(0.5);
+            }
+        }))
+        .with_output(OfflineOutput::Memory(recording.clone()))
+        .with_speed(OfflineSpeed::AsFastAsPossible)
+        .with_length_in_samples(4096);
+
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(OfflineAudioDeviceType::new(options));
+    audio_device_manager.set_current_audio_device_type(OfflineAudioDeviceType::NAME, false);
+
+    let input_meter = audio_device_manager.input_level_meter();
+    let output_meter = audio_device_manager.output_level_meter();
+    assert_eq!(input_meter.get_current_level(), 0.0);
+
+    let _handle = audio_device_manager.add_audio_callback(PassThrough);
+
+    let setup = AudioDeviceSetup::default()
+        .with_buffer_size(256)
+        .with_sample_rate(48000.0)
+        .with_input_device_name(OfflineAudioDeviceType::NAME)
+        .with_output_device_name(OfflineAudioDeviceType::NAME)
+        .with_input_channels(ChannelCount::Custom(2))
+        .with_output_channels(ChannelCount::Custom(2));
+
+    audio_device_manager
+        .set_audio_device_setup(&setup, true)
+        .unwrap();
+
+    wait_for_samples(&recording, 4096);
+
+    assert!(input_meter.get_current_level() > 0.0);
+    assert!(output_meter.get_current_level() > 0.0);
+}
+
+#[cfg(feature = "juce_audio_formats")]
+mod recorder {
+    use super::*;
+    use cxx_juce::{
+        juce_audio_formats::{
+            AudioFormatReader, AudioRecorder, AudioRecorderOptions, FlacAudioFormat,
+            RecordingFormat, WavAudioFormat,
+        },
+        juce_core::File,
+    };
+    use std::{
+        path::PathBuf,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    fn temp_path(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("cxx-juce-recorder-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        directory.join(name)
+    }
+
+    fn start_device(
+        recorder: &AudioRecorder,
+        input: OfflineInput,
+    ) -> cxx_juce::juce_audio_devices::StartedAudioDevice {
+        let options = OfflineDeviceOptions::default()
+            .with_input(input)
+            .with_input_channels(1)
+            .with_output_channels(1);
+
+        AudioIODevice::start(open_offline_device(options, 48000.0, 256), recorder.clone())
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn read_all(mut reader: UniquePtr<AudioFormatReader>) -> Vec<f32> {
+        let length = reader.length_in_samples() as i32;
+        let mut buffer = AudioSampleBuffer::new(1, length);
+        assert!(reader.pin_mut().read(buffer.pin_mut(), 0, length, 0));
+        buffer.get_read_slice(0).to_vec()
+    }
+
+    fn constant(value: f32) -> OfflineInput {
+        OfflineInput::generator(move |mut buffer| buffer.as_mut().get_write_slice(0).fill(value))
+    }
+
+    #[test]
+    fn recorders_write_device_input_to_disk() {
+        let _juce = JUCE::initialise();
+        let path = temp_path("constant.wav");
+        let recorder = AudioRecorder::new(AudioRecorderOptions::default());
+        let device = start_device(&recorder, constant(0.5));
+
+        recorder.start(&path).unwrap();
+        assert!(recorder.is_recording());
+        wait_until(|| recorder.recorded_samples() >= 4800);
+        assert!(recorder.stop());
+        assert!(!recorder.stop());
+        device.stop();
+
+        let reader = WavAudioFormat::create_reader_for(&File::from_absolute_path(
+            path.to_string_lossy().as_ref(),
+        ))
+        .expect("failed to open the recording");
+        assert_eq!(reader.num_channels(), 1);
+        assert_eq!(reader.bits_per_sample(), 24);
+        assert_eq!(
+            reader.length_in_samples() as u64,
+            recorder.recorded_samples()
+        );
+
+        let samples = read_all(reader);
+        assert!(samples.iter().all(|sample| (sample - 0.5).abs() < 1e-4));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn recorders_can_write_flac_files() {
+        let _juce = JUCE::initialise();
+        let path = temp_path("constant.flac");
+        let options = AudioRecorderOptions::default()
+            .with_format(RecordingFormat::Flac)
+            .with_bits_per_sample(16);
+        let recorder = AudioRecorder::new(options);
+        let device = start_device(&recorder, constant(0.25));
+
+        recorder.start(&path).unwrap();
+        wait_until(|| recorder.recorded_samples() >= 4800);
+        recorder.stop();
+        device.stop();
+
+        let reader = FlacAudioFormat::create_reader_for(&File::from_absolute_path(
+            path.to_string_lossy().as_ref(),
+        ))
+        .expect("failed to open the recording");
+        assert_eq!(reader.bits_per_sample(), 16);
+        assert_eq!(
+            reader.length_in_samples() as u64,
+            recorder.recorded_samples()
+        );
+
+        let samples = read_all(reader);
+        assert!(samples.iter().all(|sample| (sample - 0.25).abs() < 1e-3));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn recorders_include_the_pre_roll() {
+        let _juce = JUCE::initialise();
+        let path = temp_path("pre-roll.wav");
+        let options = AudioRecorderOptions::default()
+            .with_bits_per_sample(32)
+            .with_pre_roll(Duration::from_millis(50));
+        let recorder = AudioRecorder::new(options);
+
+        let generated = Arc::new(AtomicU64::new(0));
+        let input = OfflineInput::generator({
+            let generated = Arc::clone(&generated);
+            move |mut buffer| {
+                for sample in buffer.as_mut().get_write_slice(0) {
+                    *sample = generated.fetch_add(1, Ordering::Relaxed) as f32;
+                }
+            }
+        });
+        let device = start_device(&recorder, input);
+
+        wait_until(|| generated.load(Ordering::Relaxed) >= 4800);
+        let generated_before_start = generated.load(Ordering::Relaxed) as f32;
+        recorder.start(&path).unwrap();
+        wait_until(|| recorder.recorded_samples() >= 4800);
+        recorder.stop();
+        device.stop();
+
+        let reader = WavAudioFormat::create_reader_for(&File::from_absolute_path(
+            path.to_string_lossy().as_ref(),
+        ))
+        .expect("failed to open the recording");
+        let samples = read_all(reader);
+
+        assert!(samples[0] < generated_before_start - 2000.0);
+        assert!(samples.windows(2).all(|pair| pair[1] == pair[0] + 1.0));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn paused_recorders_skip_input() {
+        let _juce = JUCE::initialise();
+        let path = temp_path("paused.wav");
+        let recorder = AudioRecorder::new(AudioRecorderOptions::default());
+        let device = start_device(&recorder, constant(0.5));
+
+        recorder.start(&path).unwrap();
+        wait_until(|| recorder.recorded_samples() > 0);
+
+        recorder.pause();
+        assert!(recorder.is_paused());
+        assert!(recorder.is_recording());
+        let recorded = recorder.recorded_samples();
+        std::thread::sleep(Duration::from_millis(50));
+        // A block that was being written as the recorder was paused may still land.
+        assert!(recorder.recorded_samples() <= recorded + 256);
+
+        let recorded = recorder.recorded_samples();
+        recorder.resume();
+        wait_until(|| recorder.recorded_samples() > recorded);
+        recorder.stop();
+        device.stop();
+
+        let reader = WavAudioFormat::create_reader_for(&File::from_absolute_path(
+            path.to_string_lossy().as_ref(),
+        ))
+        .expect("failed to open the recording");
+        assert_eq!(
+            reader.length_in_samples() as u64,
+            recorder.recorded_samples()
+        );
+
+        let samples = read_all(reader);
+        assert!(samples.iter().all(|sample| (sample - 0.5).abs() < 1e-4));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn recorders_report_input_levels() {
+        let _juce = JUCE::initialise();
+        let recorder = AudioRecorder::new(AudioRecorderOptions::default());
+        let levels = Arc::new(Mutex::new(Vec::new()));
+        recorder.on_levels({
+            let levels = Arc::clone(&levels);
+            move |block_levels| {
+                if let Ok(mut levels) = levels.try_lock() {
+                    levels.clear();
+                    levels.extend_from_slice(block_levels);
+                }
+            }
+        });
+
+        let device = start_device(&recorder, constant(-0.75));
+        wait_until(|| !levels.lock().unwrap().is_empty());
+        device.stop();
+
+        assert_eq!(*levels.lock().unwrap(), [0.75]);
+    }
+
+    #[test]
+    fn recorders_need_a_running_device() {
+        let _juce = JUCE::initialise();
+        let recorder = AudioRecorder::new(AudioRecorderOptions::default());
+
+        assert!(recorder.start(temp_path("unused.wav")).is_err());
+        assert!(!recorder.is_recording());
+    }
+}
+
+fn start_latency_tester(input: OfflineInput, tester: &LatencyTester) -> StartedAudioDevice {
+    let options = OfflineDeviceOptions::default()
+        .with_input(input)
+        .with_speed(OfflineSpeed::AsFastAsPossible)
+        .with_input_channels(1)
+        .with_output_channels(1);
+
+    AudioIODevice::start(open_offline_device(options, 48000.0, 256), tester.clone())
+}
+
+#[test]
+fn latency_testers_measure_loopback_latency() {
+    let _juce = JUCE::initialise();
+    let tester = LatencyTester::new();
+    let device = start_latency_tester(OfflineInput::Loopback { delay: 100 }, &tester);
+
+    let measurement = tester.measure(Duration::from_secs(5)).unwrap();
+    device.stop();
+
+    assert_eq!(measurement.measured_latency, 256 + 100);
+    assert_eq!(measurement.reported_latency, 256 + 100);
+    assert_eq!(measurement.difference(), 0);
+    assert_eq!(measurement.sample_rate, 48000.0);
+    assert!(!tester.is_measuring());
+}
+
+#[test]
+fn latency_testers_report_missing_signals() {
+    let _juce = JUCE::initialise();
+    let tester = LatencyTester::with_max_latency(Duration::from_millis(100));
+    let device = start_latency_tester(OfflineInput::Silence, &tester);
+
+    let result = tester.measure(Duration::from_secs(5));
+    device.stop();
+
+    assert!(result.is_err());
+    assert!(matches!(tester.result(), Some(Err(_))));
+}
+
+#[test]
+fn latency_testers_need_a_running_device() {
+    let tester = LatencyTester::new();
+
+    assert!(tester.start().is_err());
+    assert!(tester.result().is_none());
+}
+
+#[test]
+fn device_types_describe_devices() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = scanned_device_manager(&juce);
+    let mut device_type = audio_device_manager.current_device_type().unwrap();
+
+    let report = device_type
+        .as_mut()
+        .describe_device("Microphone")
+        .expect("failed to describe the device");
+
+    assert_eq!(report.name, "Microphone / ");
+    assert_eq!(report.type_name, "Test");
+    assert_eq!(report.input_channel_names, ["Left", "Right"]);
+    assert_eq!(report.output_channel_names, ["Left", "Right"]);
+    assert_eq!(report.sample_rates, [44100.0, 48000.0]);
+    assert_eq!(report.buffer_sizes, [128, 256, 512]);
+    assert_eq!(report.default_buffer_size, 512);
+    assert_eq!(report.input_latency, Some(0));
+    assert_eq!(report.output_latency, Some(0));
+    assert_eq!(report.bit_depth, Some(24));
+    assert_eq!(report.open_error, None);
+
+    assert!(device_type.describe_device("Missing").is_none());
+}
+
+#[test]
+fn device_manager_probes_every_device() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = AudioDeviceManager::new(&juce);
+    audio_device_manager.add_audio_device_type(MockAudioDeviceType::default());
+
+    let reports = audio_device_manager.probe_devices();
+    let report = reports
+        .iter()
+        .find(|report| report.name == "Test")
+        .expect("missing the test device type");
+
+    let names: Vec<&str> = report
+        .devices
+        .iter()
+        .map(|device| device.name.as_str())
+        .collect();
+
+    assert_eq!(
+        names,
+        [
+            "Microphone / ",
+            "Audio Interface / ",
+            "Headset / ",
+            " / Speakers",
+            " / Headphones"
+        ]
+    );
+    assert!(report
+        .devices
+        .iter()
+        .all(|device| device.sample_rates == [44100.0, 48000.0]));
+}
+
+#[test]
+fn device_manager_does_not_open_devices_beside_the_current_one() {
+    let juce = JUCE::initialise();
+    let mut audio_device_manager = scanned_device_manager(&juce);
+    audio_device_manager.initialise(2, 2).unwrap();
+
+    let reports = audio_device_manager.probe_devices();
+    let report = reports
+        .iter()
+        .find(|report| report.name == "Test")
+        .expect("missing the test device type");
+
+    assert_eq!(report.devices.len(), 5);
+    assert!(report.devices.iter().all(|device| {
+        device.sample_rates == [44100.0, 48000.0]
+            && device.input_latency.is_none()
+            && device.output_latency.is_none()
+            && device.bit_depth.is_none()
+            && device.open_error.is_none()
+    }));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn device_reports_round_trip_through_serde() {
+    use cxx_juce::juce_audio_devices::{AudioDeviceReport, AudioDeviceTypeReport};
+
+    let report = AudioDeviceTypeReport {
+        name: "Test".to_string(),
+        devices: vec![AudioDeviceReport {
+            name: "Microphone".to_string(),
+            type_name: "Test".to_string(),
+            input_channel_names: vec!["Left".to_string(), "Right".to_string()],
+            output_channel_names: vec![],
+            sample_rates: vec![44100.0, 48000.0],
+            buffer_sizes: vec![128, 256, 512],
+            default_buffer_size: 512,
+            input_latency: Some(64),
+            output_latency: None,
+            bit_depth: Some(24),
+            open_error: Some("The device is busy".to_string()),
+        }],
+    };
+
+    let json = serde_json::to_string(&report).unwrap();
+    let round_tripped: AudioDeviceTypeReport = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(round_tripped, report);
+}
@@ -1,17 +1,29 @@
 #![cfg(feature = "juce_audio_processors")]
 
 use cxx::UniquePtr;
-use cxx_juce::juce_audio_basics::{AudioSampleBuffer, MidiBuffer};
+use cxx_juce::juce_audio_basics::{AudioSampleBuffer, MidiBuffer, MidiMessage};
+use cxx_juce::juce_audio_devices::{
+    AudioDevice, AudioIODevice, OfflineAudioDevice, OfflineDeviceOptions, OfflineOutput,
+    OfflineRecording, OfflineSpeed,
+};
+use cxx_juce::juce_core::BigInteger;
 use cxx_juce::juce_core::{FileSearchPath, StringArray};
 use cxx_juce::{
     juce_audio_processors::{
         AudioPlugin, AudioPluginFormat, AudioPluginFormatManager, AudioPluginInstance,
-        OwnedArrayPluginDescription, PluginDescription,
+        AudioProcessorPlayer, OwnedArrayPluginDescription, PluginDescription,
     },
     juce_core::JuceString,
     JUCE,
 };
-use std::pin::Pin;
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 struct MockPluginFormat;
 
@@ -40,7 +52,7 @@ impl AudioPluginFormat for MockPluginFormat {
         _buffer_size: i32,
     ) -> UniquePtr<AudioPluginInstance> {
         if description.name == "Mock Plugin" {
-            let plugin: Box<dyn AudioPlugin> = Box::new(MockPlugin);
+            let plugin: Box<dyn AudioPlugin> = Box::new(MockPlugin::default());
             return plugin.into();
         }
 
@@ -84,7 +96,10 @@ impl AudioPluginFormat for MockPluginFormat {
     }
 }
 
-struct MockPlugin;
+#[derive(Clone, Default)]
+struct MockPlugin {
+    midi_events: Arc<AtomicUsize>,
+}
 
 impl AudioPlugin for MockPlugin {
     fn get_name(&self) -> JuceString {
@@ -95,14 +110,17 @@ impl AudioPlugin for MockPlugin {
 
     fn release_resources(&mut self) {}
 
-    fn process_block(&mut self, _audio: Pin<&mut AudioSampleBuffer>, _midi: Pin<&mut MidiBuffer>) {}
+    fn process_block(&mut self, _audio: Pin<&mut AudioSampleBuffer>, midi: Pin<&mut MidiBuffer>) {
+        self.midi_events
+            .fetch_add(midi.get_num_events() as usize, Ordering::Relaxed);
+    }
 
     fn get_tail_length_seconds(&self) -> f64 {
         0.0
     }
 
     fn accepts_midi(&self) -> bool {
-        false
+        true
     }
 
     fn produces_midi(&self) -> bool {
@@ -162,3 +180,52 @@ fn creating_an_instance_of_an_audio_plugin() {
     let processor = instance.pin_mut().cast_mut();
     assert_eq!(processor.get_name(), "Mock Plugin");
 }
+
+#[test]
+fn audio_processor_players_route_midi_into_the_processor() {
+    let _juce = JUCE::initialise();
+    let plugin = MockPlugin::default();
+    let recording = OfflineRecording::new();
+
+    let instance: Box<dyn AudioPlugin> = Box::new(plugin.clone());
+    let player = AudioProcessorPlayer::with_processor(instance.into());
+    assert_eq!(
+        player
+            .processor()
+            .map(|processor| processor.cast().get_name()),
+        Some(JuceString::new("Mock Plugin"))
+    );
+
+    let midi_input = player.midi_input();
+
+    let options = OfflineDeviceOptions::default()
+        .with_output(OfflineOutput::Memory(recording.clone()))
+        .with_speed(OfflineSpeed::RealTime)
+        .with_input_channels(1)
+        .with_output_channels(1);
+
+    let mut channels = BigInteger::default();
+    channels.set_range(0, 1, true);
+
+    let device: Box<dyn AudioDevice> = Box::new(OfflineAudioDevice::new(options));
+    let mut device: UniquePtr<AudioIODevice> = device.into();
+    let error = device.pin_mut().open(&channels, &channels, 48000.0, 256);
+    assert_eq!(error, "");
+
+    // The device tells the player it is about to start, which resets its MIDI input, before
+    // `start` returns, so the message can't be lost.
+    let device = AudioIODevice::start(device, player);
+    midi_input.add_message(&MidiMessage::note_on(1, 60, 0.5));
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while plugin.midi_events.load(Ordering::Relaxed) == 0 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    device.stop();
+
+    assert_eq!(plugin.midi_events.load(Ordering::Relaxed), 1);
+
+    // The input outlives the player, and quietly drops anything sent to it.
+    midi_input.add_message(&MidiMessage::note_off(1, 60, 0.5));
+}